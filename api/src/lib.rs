@@ -5,7 +5,7 @@ use thrift::transport::{ReadHalf, WriteHalf};
 use thrift::transport::{TFramedReadTransport, TFramedWriteTransport, TIoChannel, TTcpChannel};
 use thrift::transport::{TFramedReadTransportFactory, TFramedWriteTransportFactory};
 
-type ThreadSafeProcessor = Box<dyn TProcessor + Send + Sync>;
+pub type ThreadSafeProcessor = Box<dyn TProcessor + Send + Sync>;
 pub type ClientInputProtocol = TCompactInputProtocol<TFramedReadTransport<ReadHalf<TTcpChannel>>>;
 pub type ClientOutputProtocol = TMultiplexedOutputProtocol<
     TCompactOutputProtocol<TFramedWriteTransport<WriteHalf<TTcpChannel>>>,
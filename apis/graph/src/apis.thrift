@@ -0,0 +1,25 @@
+struct GnodeApi {
+  1: string name,
+  2: i32 index,
+  3: list<string> next_names,
+  4: list<i32> next_nodes,
+  5: i64 depth,
+  6: i64 enqed,
+  7: i64 drops,
+  8: i64 perf_avg,
+  9: i64 perf_pkt,
+}
+
+struct GraphApi {
+  1: i32 thread,
+  2: list<GnodeApi> nodes,
+}
+
+exception GraphErr {
+  1: string why
+}
+
+service Graph {
+    list<GraphApi> show() throws (1:GraphErr ouch),
+    string dot(1:i32 thread) throws (1:GraphErr ouch),
+}
@@ -16,6 +16,12 @@ exception InterfaceErr {
 
 service Interface {
     void add_if(1:string ifname, 2:i32 ifindex, 3:string mac) throws (1:InterfaceErr ouch),
+    void del_if(1:string ifname) throws (1:InterfaceErr ouch),
+    void set_feature(1:string ifname, 2:string arc, 3:string feature, 4:bool enable) throws (1:InterfaceErr ouch),
     void add_ip(1:string ifname, 2:string ip_and_mask) throws (1:InterfaceErr ouch),
-    void add_class(1:string ifname, 2:string name, 3:string parent, 4:i32 qlimit, 5:bool is_leaf, 6:CurvesApi curves) throws (1:InterfaceErr ouch)
+    void set_mtu(1:string ifname, 2:i32 mtu) throws (1:InterfaceErr ouch),
+    void set_vrf(1:string ifname, 2:string vrf) throws (1:InterfaceErr ouch),
+    void add_class(1:string ifname, 2:string name, 3:string parent, 4:i32 qlimit, 5:bool is_leaf, 6:CurvesApi curves) throws (1:InterfaceErr ouch),
+    string show_macs(1:string ifname) throws (1:InterfaceErr ouch),
+    string show_classes(1:string ifname) throws (1:InterfaceErr ouch),
 }
\ No newline at end of file
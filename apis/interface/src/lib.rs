@@ -316,8 +316,14 @@ impl Display for InterfaceErr {
 
 pub trait TInterfaceSyncClient {
   fn add_if(&mut self, ifname: String, ifindex: i32, mac: String) -> thrift::Result<()>;
+  fn del_if(&mut self, ifname: String) -> thrift::Result<()>;
+  fn set_feature(&mut self, ifname: String, arc: String, feature: String, enable: bool) -> thrift::Result<()>;
   fn add_ip(&mut self, ifname: String, ip_and_mask: String) -> thrift::Result<()>;
+  fn set_mtu(&mut self, ifname: String, mtu: i32) -> thrift::Result<()>;
+  fn set_vrf(&mut self, ifname: String, vrf: String) -> thrift::Result<()>;
   fn add_class(&mut self, ifname: String, name: String, parent: String, qlimit: i32, is_leaf: bool, curves: CurvesApi) -> thrift::Result<()>;
+  fn show_macs(&mut self, ifname: String) -> thrift::Result<String>;
+  fn show_classes(&mut self, ifname: String) -> thrift::Result<String>;
 }
 
 pub trait TInterfaceSyncClientMarker {}
@@ -371,6 +377,60 @@ impl <C: TThriftClient + TInterfaceSyncClientMarker> TInterfaceSyncClient for C
       result.ok_or()
     }
   }
+  fn del_if(&mut self, ifname: String) -> thrift::Result<()> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("del_if", TMessageType::Call, self.sequence_number());
+        let call_args = InterfaceDelIfArgs { ifname: ifname };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("del_if", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = InterfaceDelIfResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
+  fn set_feature(&mut self, ifname: String, arc: String, feature: String, enable: bool) -> thrift::Result<()> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("set_feature", TMessageType::Call, self.sequence_number());
+        let call_args = InterfaceSetFeatureArgs { ifname: ifname, arc: arc, feature: feature, enable: enable };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("set_feature", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = InterfaceSetFeatureResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
   fn add_ip(&mut self, ifname: String, ip_and_mask: String) -> thrift::Result<()> {
     (
       {
@@ -398,6 +458,60 @@ impl <C: TThriftClient + TInterfaceSyncClientMarker> TInterfaceSyncClient for C
       result.ok_or()
     }
   }
+  fn set_mtu(&mut self, ifname: String, mtu: i32) -> thrift::Result<()> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("set_mtu", TMessageType::Call, self.sequence_number());
+        let call_args = InterfaceSetMtuArgs { ifname: ifname, mtu: mtu };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("set_mtu", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = InterfaceSetMtuResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
+  fn set_vrf(&mut self, ifname: String, vrf: String) -> thrift::Result<()> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("set_vrf", TMessageType::Call, self.sequence_number());
+        let call_args = InterfaceSetVrfArgs { ifname: ifname, vrf: vrf };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("set_vrf", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = InterfaceSetVrfResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
   fn add_class(&mut self, ifname: String, name: String, parent: String, qlimit: i32, is_leaf: bool, curves: CurvesApi) -> thrift::Result<()> {
     (
       {
@@ -425,6 +539,60 @@ impl <C: TThriftClient + TInterfaceSyncClientMarker> TInterfaceSyncClient for C
       result.ok_or()
     }
   }
+  fn show_macs(&mut self, ifname: String) -> thrift::Result<String> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("show_macs", TMessageType::Call, self.sequence_number());
+        let call_args = InterfaceShowMacsArgs { ifname: ifname };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("show_macs", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = InterfaceShowMacsResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
+  fn show_classes(&mut self, ifname: String) -> thrift::Result<String> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("show_classes", TMessageType::Call, self.sequence_number());
+        let call_args = InterfaceShowClassesArgs { ifname: ifname };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("show_classes", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = InterfaceShowClassesResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
 }
 
 //
@@ -433,8 +601,14 @@ impl <C: TThriftClient + TInterfaceSyncClientMarker> TInterfaceSyncClient for C
 
 pub trait InterfaceSyncHandler {
   fn handle_add_if(&self, ifname: String, ifindex: i32, mac: String) -> thrift::Result<()>;
+  fn handle_del_if(&self, ifname: String) -> thrift::Result<()>;
+  fn handle_set_feature(&self, ifname: String, arc: String, feature: String, enable: bool) -> thrift::Result<()>;
   fn handle_add_ip(&self, ifname: String, ip_and_mask: String) -> thrift::Result<()>;
+  fn handle_set_mtu(&self, ifname: String, mtu: i32) -> thrift::Result<()>;
+  fn handle_set_vrf(&self, ifname: String, vrf: String) -> thrift::Result<()>;
   fn handle_add_class(&self, ifname: String, name: String, parent: String, qlimit: i32, is_leaf: bool, curves: CurvesApi) -> thrift::Result<()>;
+  fn handle_show_macs(&self, ifname: String) -> thrift::Result<String>;
+  fn handle_show_classes(&self, ifname: String) -> thrift::Result<String>;
 }
 
 pub struct InterfaceSyncProcessor<H: InterfaceSyncHandler> {
@@ -450,12 +624,30 @@ impl <H: InterfaceSyncHandler> InterfaceSyncProcessor<H> {
   fn process_add_if(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     TInterfaceProcessFunctions::process_add_if(&self.handler, incoming_sequence_number, i_prot, o_prot)
   }
+  fn process_del_if(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TInterfaceProcessFunctions::process_del_if(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
+  fn process_set_feature(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TInterfaceProcessFunctions::process_set_feature(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
   fn process_add_ip(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     TInterfaceProcessFunctions::process_add_ip(&self.handler, incoming_sequence_number, i_prot, o_prot)
   }
+  fn process_set_mtu(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TInterfaceProcessFunctions::process_set_mtu(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
+  fn process_set_vrf(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TInterfaceProcessFunctions::process_set_vrf(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
   fn process_add_class(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     TInterfaceProcessFunctions::process_add_class(&self.handler, incoming_sequence_number, i_prot, o_prot)
   }
+  fn process_show_macs(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TInterfaceProcessFunctions::process_show_macs(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
+  fn process_show_classes(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TInterfaceProcessFunctions::process_show_classes(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
 }
 
 pub struct TInterfaceProcessFunctions;
@@ -521,6 +713,126 @@ impl TInterfaceProcessFunctions {
       },
     }
   }
+  pub fn process_del_if<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceDelIfArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_del_if(args.ifname) {
+      Ok(_) => {
+        let message_ident = TMessageIdentifier::new("del_if", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = InterfaceDelIfResult { ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<InterfaceErr>().is_some() {
+              let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
+              let ret_err = InterfaceDelIfResult{ ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("del_if", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("del_if", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("del_if", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("del_if", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
+  pub fn process_set_feature<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceSetFeatureArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_set_feature(args.ifname, args.arc, args.feature, args.enable) {
+      Ok(_) => {
+        let message_ident = TMessageIdentifier::new("set_feature", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = InterfaceSetFeatureResult { ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<InterfaceErr>().is_some() {
+              let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
+              let ret_err = InterfaceSetFeatureResult{ ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("set_feature", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("set_feature", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("set_feature", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("set_feature", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
   pub fn process_add_ip<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     let args = InterfaceAddIpArgs::read_from_in_protocol(i_prot)?;
     match handler.handle_add_ip(args.ifname, args.ip_and_mask) {
@@ -581,13 +893,13 @@ impl TInterfaceProcessFunctions {
       },
     }
   }
-  pub fn process_add_class<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
-    let args = InterfaceAddClassArgs::read_from_in_protocol(i_prot)?;
-    match handler.handle_add_class(args.ifname, args.name, args.parent, args.qlimit, args.is_leaf, args.curves) {
+  pub fn process_set_mtu<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceSetMtuArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_set_mtu(args.ifname, args.mtu) {
       Ok(_) => {
-        let message_ident = TMessageIdentifier::new("add_class", TMessageType::Reply, incoming_sequence_number);
+        let message_ident = TMessageIdentifier::new("set_mtu", TMessageType::Reply, incoming_sequence_number);
         o_prot.write_message_begin(&message_ident)?;
-        let ret = InterfaceAddClassResult { ouch: None };
+        let ret = InterfaceSetMtuResult { ouch: None };
         ret.write_to_out_protocol(o_prot)?;
         o_prot.write_message_end()?;
         o_prot.flush()
@@ -597,8 +909,8 @@ impl TInterfaceProcessFunctions {
           thrift::Error::User(usr_err) => {
             if usr_err.downcast_ref::<InterfaceErr>().is_some() {
               let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
-              let ret_err = InterfaceAddClassResult{ ouch: Some(*err) };
-              let message_ident = TMessageIdentifier::new("add_class", TMessageType::Reply, incoming_sequence_number);
+              let ret_err = InterfaceSetMtuResult{ ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("set_mtu", TMessageType::Reply, incoming_sequence_number);
               o_prot.write_message_begin(&message_ident)?;
               ret_err.write_to_out_protocol(o_prot)?;
               o_prot.write_message_end()?;
@@ -610,7 +922,7 @@ impl TInterfaceProcessFunctions {
                   usr_err.description()
                 )
               };
-              let message_ident = TMessageIdentifier::new("add_class", TMessageType::Exception, incoming_sequence_number);
+              let message_ident = TMessageIdentifier::new("set_mtu", TMessageType::Exception, incoming_sequence_number);
               o_prot.write_message_begin(&message_ident)?;
               thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
               o_prot.write_message_end()?;
@@ -618,7 +930,7 @@ impl TInterfaceProcessFunctions {
             }
           },
           thrift::Error::Application(app_err) => {
-            let message_ident = TMessageIdentifier::new("add_class", TMessageType::Exception, incoming_sequence_number);
+            let message_ident = TMessageIdentifier::new("set_mtu", TMessageType::Exception, incoming_sequence_number);
             o_prot.write_message_begin(&message_ident)?;
             thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
             o_prot.write_message_end()?;
@@ -631,7 +943,7 @@ impl TInterfaceProcessFunctions {
                 e.description()
               )
             };
-            let message_ident = TMessageIdentifier::new("add_class", TMessageType::Exception, incoming_sequence_number);
+            let message_ident = TMessageIdentifier::new("set_mtu", TMessageType::Exception, incoming_sequence_number);
             o_prot.write_message_begin(&message_ident)?;
             thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
             o_prot.write_message_end()?;
@@ -641,21 +953,279 @@ impl TInterfaceProcessFunctions {
       },
     }
   }
-}
-
-impl <H: InterfaceSyncHandler> TProcessor for InterfaceSyncProcessor<H> {
-  fn process(&self, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
-    let message_ident = i_prot.read_message_begin()?;
-    let res = match &*message_ident.name {
-      "add_if" => {
-        self.process_add_if(message_ident.sequence_number, i_prot, o_prot)
+  pub fn process_set_vrf<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceSetVrfArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_set_vrf(args.ifname, args.vrf) {
+      Ok(_) => {
+        let message_ident = TMessageIdentifier::new("set_vrf", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = InterfaceSetVrfResult { ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<InterfaceErr>().is_some() {
+              let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
+              let ret_err = InterfaceSetVrfResult{ ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("set_vrf", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("set_vrf", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("set_vrf", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("set_vrf", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
+  pub fn process_add_class<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceAddClassArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_add_class(args.ifname, args.name, args.parent, args.qlimit, args.is_leaf, args.curves) {
+      Ok(_) => {
+        let message_ident = TMessageIdentifier::new("add_class", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = InterfaceAddClassResult { ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<InterfaceErr>().is_some() {
+              let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
+              let ret_err = InterfaceAddClassResult{ ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("add_class", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("add_class", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("add_class", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("add_class", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
+  pub fn process_show_macs<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceShowMacsArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_show_macs(args.ifname) {
+      Ok(handler_return) => {
+        let message_ident = TMessageIdentifier::new("show_macs", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = InterfaceShowMacsResult { result_value: Some(handler_return), ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<InterfaceErr>().is_some() {
+              let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
+              let ret_err = InterfaceShowMacsResult{ result_value: None, ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("show_macs", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("show_macs", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("show_macs", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("show_macs", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
+  pub fn process_show_classes<H: InterfaceSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = InterfaceShowClassesArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_show_classes(args.ifname) {
+      Ok(handler_return) => {
+        let message_ident = TMessageIdentifier::new("show_classes", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = InterfaceShowClassesResult { result_value: Some(handler_return), ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<InterfaceErr>().is_some() {
+              let err = usr_err.downcast::<InterfaceErr>().expect("downcast already checked");
+              let ret_err = InterfaceShowClassesResult{ result_value: None, ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("show_classes", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("show_classes", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("show_classes", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("show_classes", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
+}
+
+impl <H: InterfaceSyncHandler> TProcessor for InterfaceSyncProcessor<H> {
+  fn process(&self, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let message_ident = i_prot.read_message_begin()?;
+    let res = match &*message_ident.name {
+      "add_if" => {
+        self.process_add_if(message_ident.sequence_number, i_prot, o_prot)
+      },
+      "del_if" => {
+        self.process_del_if(message_ident.sequence_number, i_prot, o_prot)
+      },
+      "set_feature" => {
+        self.process_set_feature(message_ident.sequence_number, i_prot, o_prot)
       },
       "add_ip" => {
         self.process_add_ip(message_ident.sequence_number, i_prot, o_prot)
       },
+      "set_mtu" => {
+        self.process_set_mtu(message_ident.sequence_number, i_prot, o_prot)
+      },
+      "set_vrf" => {
+        self.process_set_vrf(message_ident.sequence_number, i_prot, o_prot)
+      },
       "add_class" => {
         self.process_add_class(message_ident.sequence_number, i_prot, o_prot)
       },
+      "show_macs" => {
+        self.process_show_macs(message_ident.sequence_number, i_prot, o_prot)
+      },
+      "show_classes" => {
+        self.process_show_classes(message_ident.sequence_number, i_prot, o_prot)
+      },
       method => {
         Err(
           thrift::Error::Application(
@@ -672,22 +1242,514 @@ impl <H: InterfaceSyncHandler> TProcessor for InterfaceSyncProcessor<H> {
 }
 
 //
-// InterfaceAddIfArgs
+// InterfaceAddIfArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceAddIfArgs {
+  ifname: String,
+  ifindex: i32,
+  mac: String,
+}
+
+impl InterfaceAddIfArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIfArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = None;
+    let mut f_2: Option<i32> = None;
+    let mut f_3: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        2 => {
+          let val = i_prot.read_i32()?;
+          f_2 = Some(val);
+        },
+        3 => {
+          let val = i_prot.read_string()?;
+          f_3 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("InterfaceAddIfArgs.ifname", &f_1)?;
+    verify_required_field_exists("InterfaceAddIfArgs.ifindex", &f_2)?;
+    verify_required_field_exists("InterfaceAddIfArgs.mac", &f_3)?;
+    let ret = InterfaceAddIfArgs {
+      ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
+      ifindex: f_2.expect("auto-generated code should have checked for presence of required fields"),
+      mac: f_3.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("add_if_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
+    o_prot.write_string(&self.ifname)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifindex", TType::I32, 2))?;
+    o_prot.write_i32(self.ifindex)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("mac", TType::String, 3))?;
+    o_prot.write_string(&self.mac)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// InterfaceAddIfResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceAddIfResult {
+  ouch: Option<InterfaceErr>,
+}
+
+impl InterfaceAddIfResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIfResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<InterfaceErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = InterfaceErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = InterfaceAddIfResult {
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("InterfaceAddIfResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<()> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+//
+// InterfaceDelIfArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceDelIfArgs {
+  ifname: String,
+}
+
+impl InterfaceDelIfArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceDelIfArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("InterfaceDelIfArgs.ifname", &f_1)?;
+    let ret = InterfaceDelIfArgs {
+      ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("del_if_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
+    o_prot.write_string(&self.ifname)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// InterfaceDelIfResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceDelIfResult {
+  ouch: Option<InterfaceErr>,
+}
+
+impl InterfaceDelIfResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceDelIfResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<InterfaceErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = InterfaceErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = InterfaceDelIfResult {
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("InterfaceDelIfResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<()> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+//
+// InterfaceSetFeatureArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceSetFeatureArgs {
+  ifname: String,
+  arc: String,
+  feature: String,
+  enable: bool,
+}
+
+impl InterfaceSetFeatureArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceSetFeatureArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = None;
+    let mut f_2: Option<String> = None;
+    let mut f_3: Option<String> = None;
+    let mut f_4: Option<bool> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        2 => {
+          let val = i_prot.read_string()?;
+          f_2 = Some(val);
+        },
+        3 => {
+          let val = i_prot.read_string()?;
+          f_3 = Some(val);
+        },
+        4 => {
+          let val = i_prot.read_bool()?;
+          f_4 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("InterfaceSetFeatureArgs.ifname", &f_1)?;
+    verify_required_field_exists("InterfaceSetFeatureArgs.arc", &f_2)?;
+    verify_required_field_exists("InterfaceSetFeatureArgs.feature", &f_3)?;
+    verify_required_field_exists("InterfaceSetFeatureArgs.enable", &f_4)?;
+    let ret = InterfaceSetFeatureArgs {
+      ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
+      arc: f_2.expect("auto-generated code should have checked for presence of required fields"),
+      feature: f_3.expect("auto-generated code should have checked for presence of required fields"),
+      enable: f_4.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("set_feature_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
+    o_prot.write_string(&self.ifname)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("arc", TType::String, 2))?;
+    o_prot.write_string(&self.arc)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("feature", TType::String, 3))?;
+    o_prot.write_string(&self.feature)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("enable", TType::Bool, 4))?;
+    o_prot.write_bool(self.enable)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// InterfaceSetFeatureResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceSetFeatureResult {
+  ouch: Option<InterfaceErr>,
+}
+
+impl InterfaceSetFeatureResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceSetFeatureResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<InterfaceErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = InterfaceErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = InterfaceSetFeatureResult {
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("InterfaceSetFeatureResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<()> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+//
+// InterfaceAddIpArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceAddIpArgs {
+  ifname: String,
+  ip_and_mask: String,
+}
+
+impl InterfaceAddIpArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIpArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = None;
+    let mut f_2: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        2 => {
+          let val = i_prot.read_string()?;
+          f_2 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("InterfaceAddIpArgs.ifname", &f_1)?;
+    verify_required_field_exists("InterfaceAddIpArgs.ip_and_mask", &f_2)?;
+    let ret = InterfaceAddIpArgs {
+      ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
+      ip_and_mask: f_2.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("add_ip_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
+    o_prot.write_string(&self.ifname)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ip_and_mask", TType::String, 2))?;
+    o_prot.write_string(&self.ip_and_mask)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// InterfaceAddIpResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceAddIpResult {
+  ouch: Option<InterfaceErr>,
+}
+
+impl InterfaceAddIpResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIpResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<InterfaceErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = InterfaceErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = InterfaceAddIpResult {
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("InterfaceAddIpResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<()> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+//
+// InterfaceSetMtuArgs
 //
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct InterfaceAddIfArgs {
+struct InterfaceSetMtuArgs {
   ifname: String,
-  ifindex: i32,
-  mac: String,
+  mtu: i32,
 }
 
-impl InterfaceAddIfArgs {
-  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIfArgs> {
+impl InterfaceSetMtuArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceSetMtuArgs> {
     i_prot.read_struct_begin()?;
     let mut f_1: Option<String> = None;
     let mut f_2: Option<i32> = None;
-    let mut f_3: Option<String> = None;
     loop {
       let field_ident = i_prot.read_field_begin()?;
       if field_ident.field_type == TType::Stop {
@@ -703,10 +1765,6 @@ impl InterfaceAddIfArgs {
           let val = i_prot.read_i32()?;
           f_2 = Some(val);
         },
-        3 => {
-          let val = i_prot.read_string()?;
-          f_3 = Some(val);
-        },
         _ => {
           i_prot.skip(field_ident.field_type)?;
         },
@@ -714,27 +1772,22 @@ impl InterfaceAddIfArgs {
       i_prot.read_field_end()?;
     }
     i_prot.read_struct_end()?;
-    verify_required_field_exists("InterfaceAddIfArgs.ifname", &f_1)?;
-    verify_required_field_exists("InterfaceAddIfArgs.ifindex", &f_2)?;
-    verify_required_field_exists("InterfaceAddIfArgs.mac", &f_3)?;
-    let ret = InterfaceAddIfArgs {
+    verify_required_field_exists("InterfaceSetMtuArgs.ifname", &f_1)?;
+    verify_required_field_exists("InterfaceSetMtuArgs.mtu", &f_2)?;
+    let ret = InterfaceSetMtuArgs {
       ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
-      ifindex: f_2.expect("auto-generated code should have checked for presence of required fields"),
-      mac: f_3.expect("auto-generated code should have checked for presence of required fields"),
+      mtu: f_2.expect("auto-generated code should have checked for presence of required fields"),
     };
     Ok(ret)
   }
   fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
-    let struct_ident = TStructIdentifier::new("add_if_args");
+    let struct_ident = TStructIdentifier::new("set_mtu_args");
     o_prot.write_struct_begin(&struct_ident)?;
     o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
     o_prot.write_string(&self.ifname)?;
     o_prot.write_field_end()?;
-    o_prot.write_field_begin(&TFieldIdentifier::new("ifindex", TType::I32, 2))?;
-    o_prot.write_i32(self.ifindex)?;
-    o_prot.write_field_end()?;
-    o_prot.write_field_begin(&TFieldIdentifier::new("mac", TType::String, 3))?;
-    o_prot.write_string(&self.mac)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("mtu", TType::I32, 2))?;
+    o_prot.write_i32(self.mtu)?;
     o_prot.write_field_end()?;
     o_prot.write_field_stop()?;
     o_prot.write_struct_end()
@@ -742,16 +1795,16 @@ impl InterfaceAddIfArgs {
 }
 
 //
-// InterfaceAddIfResult
+// InterfaceSetMtuResult
 //
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct InterfaceAddIfResult {
+struct InterfaceSetMtuResult {
   ouch: Option<InterfaceErr>,
 }
 
-impl InterfaceAddIfResult {
-  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIfResult> {
+impl InterfaceSetMtuResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceSetMtuResult> {
     i_prot.read_struct_begin()?;
     let mut f_1: Option<InterfaceErr> = None;
     loop {
@@ -772,13 +1825,13 @@ impl InterfaceAddIfResult {
       i_prot.read_field_end()?;
     }
     i_prot.read_struct_end()?;
-    let ret = InterfaceAddIfResult {
+    let ret = InterfaceSetMtuResult {
       ouch: f_1,
     };
     Ok(ret)
   }
   fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
-    let struct_ident = TStructIdentifier::new("InterfaceAddIfResult");
+    let struct_ident = TStructIdentifier::new("InterfaceSetMtuResult");
     o_prot.write_struct_begin(&struct_ident)?;
     if let Some(ref fld_var) = self.ouch {
       o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
@@ -801,17 +1854,17 @@ impl InterfaceAddIfResult {
 }
 
 //
-// InterfaceAddIpArgs
+// InterfaceSetVrfArgs
 //
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct InterfaceAddIpArgs {
+struct InterfaceSetVrfArgs {
   ifname: String,
-  ip_and_mask: String,
+  vrf: String,
 }
 
-impl InterfaceAddIpArgs {
-  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIpArgs> {
+impl InterfaceSetVrfArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceSetVrfArgs> {
     i_prot.read_struct_begin()?;
     let mut f_1: Option<String> = None;
     let mut f_2: Option<String> = None;
@@ -837,22 +1890,22 @@ impl InterfaceAddIpArgs {
       i_prot.read_field_end()?;
     }
     i_prot.read_struct_end()?;
-    verify_required_field_exists("InterfaceAddIpArgs.ifname", &f_1)?;
-    verify_required_field_exists("InterfaceAddIpArgs.ip_and_mask", &f_2)?;
-    let ret = InterfaceAddIpArgs {
+    verify_required_field_exists("InterfaceSetVrfArgs.ifname", &f_1)?;
+    verify_required_field_exists("InterfaceSetVrfArgs.vrf", &f_2)?;
+    let ret = InterfaceSetVrfArgs {
       ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
-      ip_and_mask: f_2.expect("auto-generated code should have checked for presence of required fields"),
+      vrf: f_2.expect("auto-generated code should have checked for presence of required fields"),
     };
     Ok(ret)
   }
   fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
-    let struct_ident = TStructIdentifier::new("add_ip_args");
+    let struct_ident = TStructIdentifier::new("set_vrf_args");
     o_prot.write_struct_begin(&struct_ident)?;
     o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
     o_prot.write_string(&self.ifname)?;
     o_prot.write_field_end()?;
-    o_prot.write_field_begin(&TFieldIdentifier::new("ip_and_mask", TType::String, 2))?;
-    o_prot.write_string(&self.ip_and_mask)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("vrf", TType::String, 2))?;
+    o_prot.write_string(&self.vrf)?;
     o_prot.write_field_end()?;
     o_prot.write_field_stop()?;
     o_prot.write_struct_end()
@@ -860,16 +1913,16 @@ impl InterfaceAddIpArgs {
 }
 
 //
-// InterfaceAddIpResult
+// InterfaceSetVrfResult
 //
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct InterfaceAddIpResult {
+struct InterfaceSetVrfResult {
   ouch: Option<InterfaceErr>,
 }
 
-impl InterfaceAddIpResult {
-  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceAddIpResult> {
+impl InterfaceSetVrfResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceSetVrfResult> {
     i_prot.read_struct_begin()?;
     let mut f_1: Option<InterfaceErr> = None;
     loop {
@@ -890,13 +1943,13 @@ impl InterfaceAddIpResult {
       i_prot.read_field_end()?;
     }
     i_prot.read_struct_end()?;
-    let ret = InterfaceAddIpResult {
+    let ret = InterfaceSetVrfResult {
       ouch: f_1,
     };
     Ok(ret)
   }
   fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
-    let struct_ident = TStructIdentifier::new("InterfaceAddIpResult");
+    let struct_ident = TStructIdentifier::new("InterfaceSetVrfResult");
     o_prot.write_struct_begin(&struct_ident)?;
     if let Some(ref fld_var) = self.ouch {
       o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
@@ -1079,3 +2132,265 @@ impl InterfaceAddClassResult {
     }
   }
 }
+
+//
+// InterfaceShowMacsArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceShowMacsArgs {
+  ifname: String,
+}
+
+impl InterfaceShowMacsArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceShowMacsArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("InterfaceShowMacsArgs.ifname", &f_1)?;
+    let ret = InterfaceShowMacsArgs {
+      ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("show_macs_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
+    o_prot.write_string(&self.ifname)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// InterfaceShowMacsResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceShowMacsResult {
+  result_value: Option<String>,
+  ouch: Option<InterfaceErr>,
+}
+
+impl InterfaceShowMacsResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceShowMacsResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_0: Option<String> = None;
+    let mut f_1: Option<InterfaceErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        0 => {
+          let val = i_prot.read_string()?;
+          f_0 = Some(val);
+        },
+        1 => {
+          let val = InterfaceErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = InterfaceShowMacsResult {
+      result_value: f_0,
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("InterfaceShowMacsResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.result_value {
+      o_prot.write_field_begin(&TFieldIdentifier::new("result_value", TType::String, 0))?;
+      o_prot.write_string(fld_var)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<String> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else if self.result_value.is_some() {
+      Ok(self.result_value.unwrap())
+    } else {
+      Err(
+        thrift::Error::Application(
+          ApplicationError::new(
+            ApplicationErrorKind::MissingResult,
+            "no result received for InterfaceShowMacs"
+          )
+        )
+      )
+    }
+  }
+}
+
+//
+// InterfaceShowClassesArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceShowClassesArgs {
+  ifname: String,
+}
+
+impl InterfaceShowClassesArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceShowClassesArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("InterfaceShowClassesArgs.ifname", &f_1)?;
+    let ret = InterfaceShowClassesArgs {
+      ifname: f_1.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("show_classes_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 1))?;
+    o_prot.write_string(&self.ifname)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// InterfaceShowClassesResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct InterfaceShowClassesResult {
+  result_value: Option<String>,
+  ouch: Option<InterfaceErr>,
+}
+
+impl InterfaceShowClassesResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<InterfaceShowClassesResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_0: Option<String> = None;
+    let mut f_1: Option<InterfaceErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        0 => {
+          let val = i_prot.read_string()?;
+          f_0 = Some(val);
+        },
+        1 => {
+          let val = InterfaceErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = InterfaceShowClassesResult {
+      result_value: f_0,
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("InterfaceShowClassesResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.result_value {
+      o_prot.write_field_begin(&TFieldIdentifier::new("result_value", TType::String, 0))?;
+      o_prot.write_string(fld_var)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<String> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else if self.result_value.is_some() {
+      Ok(self.result_value.unwrap())
+    } else {
+      Err(
+        thrift::Error::Application(
+          ApplicationError::new(
+            ApplicationErrorKind::MissingResult,
+            "no result received for InterfaceShowClasses"
+          )
+        )
+      )
+    }
+  }
+}
@@ -0,0 +1,27 @@
+// A policy based routing rule. The match fields left empty match any packet - src and dst
+// are prefixes like 10.1.0.0/16, sport and dport are a port or a range of ports like
+// 8000-8080, proto and dscp are -1 for any protocol and any DSCP, and ifname is the input
+// interface. A packet matching the rule is routed towards nhop and/or in the route table
+// of vrf, at least one of the two has to be there. The rules are tried in the order of seq
+struct PbrRule {
+  1: i32 seq,
+  2: string src,
+  3: string dst,
+  4: i32 proto,
+  5: string sport,
+  6: string dport,
+  7: i32 dscp,
+  8: string ifname,
+  9: string nhop,
+  10: string vrf,
+}
+
+exception PbrErr {
+  1: string why
+}
+
+service Pbr {
+    void add_rule(1:PbrRule rule) throws (1:PbrErr ouch),
+    void del_rule(1:i32 seq) throws (1:PbrErr ouch),
+    string show() throws (1:PbrErr ouch),
+}
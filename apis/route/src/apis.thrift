@@ -1,10 +1,22 @@
+// kind is empty for a route through nhop/ifname, or one of "blackhole" (drop the packets),
+// "reject" (drop the packets and send an ICMP unreachable) or "local" (packets for R2 itself,
+// on the interface ifname) - nhop is ignored for those
+struct RouteEntry {
+  1: string ip_and_mask,
+  2: string nhop,
+  3: string ifname,
+  4: string kind,
+}
 
 exception RouteErr {
   1: string why
 }
 
+// vrf is the name of the VRF whose route table the routes are in, empty for the default VRF
 service Route {
-    void add_route(1:string ip_and_mask, 2:string nhop, 3:string ifname) throws (1:RouteErr ouch),
-    void del_route(1:string ip_and_mask, 2:string nhop, 3:string ifname) throws (1:RouteErr ouch),
-    string show(1:string prefix, 2:string filename) throws (1:RouteErr ouch),
+    void add_route(1:string ip_and_mask, 2:string nhop, 3:string ifname, 4:string kind, 5:string vrf) throws (1:RouteErr ouch),
+    void del_route(1:string ip_and_mask, 2:string nhop, 3:string ifname, 4:string kind, 5:string vrf) throws (1:RouteErr ouch),
+    list<string> add_routes(1:list<RouteEntry> routes, 2:string vrf) throws (1:RouteErr ouch),
+    list<string> del_routes(1:list<RouteEntry> routes, 2:string vrf) throws (1:RouteErr ouch),
+    string show(1:string prefix, 2:string filename, 3:string vrf) throws (1:RouteErr ouch),
 }
\ No newline at end of file
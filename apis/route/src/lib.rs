@@ -29,6 +29,122 @@ use thrift::protocol::verify_expected_service_call;
 use thrift::protocol::verify_required_field_exists;
 use thrift::server::TProcessor;
 
+//
+// RouteEntry
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RouteEntry {
+  pub ip_and_mask: Option<String>,
+  pub nhop: Option<String>,
+  pub ifname: Option<String>,
+  pub kind: Option<String>,
+}
+
+impl RouteEntry {
+  pub fn new<F1, F2, F3, F4>(ip_and_mask: F1, nhop: F2, ifname: F3, kind: F4) -> RouteEntry where F1: Into<Option<String>>, F2: Into<Option<String>>, F3: Into<Option<String>>, F4: Into<Option<String>> {
+    RouteEntry {
+      ip_and_mask: ip_and_mask.into(),
+      nhop: nhop.into(),
+      ifname: ifname.into(),
+      kind: kind.into(),
+    }
+  }
+  pub fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<RouteEntry> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<String> = Some("".to_owned());
+    let mut f_2: Option<String> = Some("".to_owned());
+    let mut f_3: Option<String> = Some("".to_owned());
+    let mut f_4: Option<String> = Some("".to_owned());
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let val = i_prot.read_string()?;
+          f_1 = Some(val);
+        },
+        2 => {
+          let val = i_prot.read_string()?;
+          f_2 = Some(val);
+        },
+        3 => {
+          let val = i_prot.read_string()?;
+          f_3 = Some(val);
+        },
+        4 => {
+          let val = i_prot.read_string()?;
+          f_4 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = RouteEntry {
+      ip_and_mask: f_1,
+      nhop: f_2,
+      ifname: f_3,
+      kind: f_4,
+    };
+    Ok(ret)
+  }
+  pub fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("RouteEntry");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.ip_and_mask {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ip_and_mask", TType::String, 1))?;
+      o_prot.write_string(fld_var)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.nhop {
+      o_prot.write_field_begin(&TFieldIdentifier::new("nhop", TType::String, 2))?;
+      o_prot.write_string(fld_var)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.ifname {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 3))?;
+      o_prot.write_string(fld_var)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.kind {
+      o_prot.write_field_begin(&TFieldIdentifier::new("kind", TType::String, 4))?;
+      o_prot.write_string(fld_var)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+impl Default for RouteEntry {
+  fn default() -> Self {
+    RouteEntry{
+      ip_and_mask: Some("".to_owned()),
+      nhop: Some("".to_owned()),
+      ifname: Some("".to_owned()),
+      kind: Some("".to_owned()),
+    }
+  }
+}
+
 //
 // RouteErr
 //
@@ -117,9 +233,11 @@ impl Display for RouteErr {
 //
 
 pub trait TRouteSyncClient {
-  fn add_route(&mut self, ip_and_mask: String, nhop: String, ifname: String) -> thrift::Result<()>;
-  fn del_route(&mut self, ip_and_mask: String, nhop: String, ifname: String) -> thrift::Result<()>;
-  fn show(&mut self, prefix: String, filename: String) -> thrift::Result<String>;
+  fn add_route(&mut self, ip_and_mask: String, nhop: String, ifname: String, kind: String, vrf: String) -> thrift::Result<()>;
+  fn del_route(&mut self, ip_and_mask: String, nhop: String, ifname: String, kind: String, vrf: String) -> thrift::Result<()>;
+  fn add_routes(&mut self, routes: Vec<RouteEntry>, vrf: String) -> thrift::Result<Vec<String>>;
+  fn del_routes(&mut self, routes: Vec<RouteEntry>, vrf: String) -> thrift::Result<Vec<String>>;
+  fn show(&mut self, prefix: String, filename: String, vrf: String) -> thrift::Result<String>;
 }
 
 pub trait TRouteSyncClientMarker {}
@@ -146,12 +264,12 @@ impl <IP, OP> TThriftClient for RouteSyncClient<IP, OP> where IP: TInputProtocol
 impl <IP, OP> TRouteSyncClientMarker for RouteSyncClient<IP, OP> where IP: TInputProtocol, OP: TOutputProtocol {}
 
 impl <C: TThriftClient + TRouteSyncClientMarker> TRouteSyncClient for C {
-  fn add_route(&mut self, ip_and_mask: String, nhop: String, ifname: String) -> thrift::Result<()> {
+  fn add_route(&mut self, ip_and_mask: String, nhop: String, ifname: String, kind: String, vrf: String) -> thrift::Result<()> {
     (
       {
         self.increment_sequence_number();
         let message_ident = TMessageIdentifier::new("add_route", TMessageType::Call, self.sequence_number());
-        let call_args = RouteAddRouteArgs { ip_and_mask: ip_and_mask, nhop: nhop, ifname: ifname };
+        let call_args = RouteAddRouteArgs { ip_and_mask: ip_and_mask, nhop: nhop, ifname: ifname, kind: kind, vrf: vrf };
         self.o_prot_mut().write_message_begin(&message_ident)?;
         call_args.write_to_out_protocol(self.o_prot_mut())?;
         self.o_prot_mut().write_message_end()?;
@@ -173,12 +291,12 @@ impl <C: TThriftClient + TRouteSyncClientMarker> TRouteSyncClient for C {
       result.ok_or()
     }
   }
-  fn del_route(&mut self, ip_and_mask: String, nhop: String, ifname: String) -> thrift::Result<()> {
+  fn del_route(&mut self, ip_and_mask: String, nhop: String, ifname: String, kind: String, vrf: String) -> thrift::Result<()> {
     (
       {
         self.increment_sequence_number();
         let message_ident = TMessageIdentifier::new("del_route", TMessageType::Call, self.sequence_number());
-        let call_args = RouteDelRouteArgs { ip_and_mask: ip_and_mask, nhop: nhop, ifname: ifname };
+        let call_args = RouteDelRouteArgs { ip_and_mask: ip_and_mask, nhop: nhop, ifname: ifname, kind: kind, vrf: vrf };
         self.o_prot_mut().write_message_begin(&message_ident)?;
         call_args.write_to_out_protocol(self.o_prot_mut())?;
         self.o_prot_mut().write_message_end()?;
@@ -200,12 +318,66 @@ impl <C: TThriftClient + TRouteSyncClientMarker> TRouteSyncClient for C {
       result.ok_or()
     }
   }
-  fn show(&mut self, prefix: String, filename: String) -> thrift::Result<String> {
+  fn add_routes(&mut self, routes: Vec<RouteEntry>, vrf: String) -> thrift::Result<Vec<String>> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("add_routes", TMessageType::Call, self.sequence_number());
+        let call_args = RouteAddRoutesArgs { routes: routes, vrf: vrf };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("add_routes", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = RouteAddRoutesResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
+  fn del_routes(&mut self, routes: Vec<RouteEntry>, vrf: String) -> thrift::Result<Vec<String>> {
+    (
+      {
+        self.increment_sequence_number();
+        let message_ident = TMessageIdentifier::new("del_routes", TMessageType::Call, self.sequence_number());
+        let call_args = RouteDelRoutesArgs { routes: routes, vrf: vrf };
+        self.o_prot_mut().write_message_begin(&message_ident)?;
+        call_args.write_to_out_protocol(self.o_prot_mut())?;
+        self.o_prot_mut().write_message_end()?;
+        self.o_prot_mut().flush()
+      }
+    )?;
+    {
+      let message_ident = self.i_prot_mut().read_message_begin()?;
+      verify_expected_sequence_number(self.sequence_number(), message_ident.sequence_number)?;
+      verify_expected_service_call("del_routes", &message_ident.name)?;
+      if message_ident.message_type == TMessageType::Exception {
+        let remote_error = thrift::Error::read_application_error_from_in_protocol(self.i_prot_mut())?;
+        self.i_prot_mut().read_message_end()?;
+        return Err(thrift::Error::Application(remote_error))
+      }
+      verify_expected_message_type(TMessageType::Reply, message_ident.message_type)?;
+      let result = RouteDelRoutesResult::read_from_in_protocol(self.i_prot_mut())?;
+      self.i_prot_mut().read_message_end()?;
+      result.ok_or()
+    }
+  }
+  fn show(&mut self, prefix: String, filename: String, vrf: String) -> thrift::Result<String> {
     (
       {
         self.increment_sequence_number();
         let message_ident = TMessageIdentifier::new("show", TMessageType::Call, self.sequence_number());
-        let call_args = RouteShowArgs { prefix: prefix, filename: filename };
+        let call_args = RouteShowArgs { prefix: prefix, filename: filename, vrf: vrf };
         self.o_prot_mut().write_message_begin(&message_ident)?;
         call_args.write_to_out_protocol(self.o_prot_mut())?;
         self.o_prot_mut().write_message_end()?;
@@ -234,9 +406,11 @@ impl <C: TThriftClient + TRouteSyncClientMarker> TRouteSyncClient for C {
 //
 
 pub trait RouteSyncHandler {
-  fn handle_add_route(&self, ip_and_mask: String, nhop: String, ifname: String) -> thrift::Result<()>;
-  fn handle_del_route(&self, ip_and_mask: String, nhop: String, ifname: String) -> thrift::Result<()>;
-  fn handle_show(&self, prefix: String, filename: String) -> thrift::Result<String>;
+  fn handle_add_route(&self, ip_and_mask: String, nhop: String, ifname: String, kind: String, vrf: String) -> thrift::Result<()>;
+  fn handle_del_route(&self, ip_and_mask: String, nhop: String, ifname: String, kind: String, vrf: String) -> thrift::Result<()>;
+  fn handle_add_routes(&self, routes: Vec<RouteEntry>, vrf: String) -> thrift::Result<Vec<String>>;
+  fn handle_del_routes(&self, routes: Vec<RouteEntry>, vrf: String) -> thrift::Result<Vec<String>>;
+  fn handle_show(&self, prefix: String, filename: String, vrf: String) -> thrift::Result<String>;
 }
 
 pub struct RouteSyncProcessor<H: RouteSyncHandler> {
@@ -255,6 +429,12 @@ impl <H: RouteSyncHandler> RouteSyncProcessor<H> {
   fn process_del_route(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     TRouteProcessFunctions::process_del_route(&self.handler, incoming_sequence_number, i_prot, o_prot)
   }
+  fn process_add_routes(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TRouteProcessFunctions::process_add_routes(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
+  fn process_del_routes(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    TRouteProcessFunctions::process_del_routes(&self.handler, incoming_sequence_number, i_prot, o_prot)
+  }
   fn process_show(&self, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     TRouteProcessFunctions::process_show(&self.handler, incoming_sequence_number, i_prot, o_prot)
   }
@@ -265,7 +445,7 @@ pub struct TRouteProcessFunctions;
 impl TRouteProcessFunctions {
   pub fn process_add_route<H: RouteSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     let args = RouteAddRouteArgs::read_from_in_protocol(i_prot)?;
-    match handler.handle_add_route(args.ip_and_mask, args.nhop, args.ifname) {
+    match handler.handle_add_route(args.ip_and_mask, args.nhop, args.ifname, args.kind, args.vrf) {
       Ok(_) => {
         let message_ident = TMessageIdentifier::new("add_route", TMessageType::Reply, incoming_sequence_number);
         o_prot.write_message_begin(&message_ident)?;
@@ -325,7 +505,7 @@ impl TRouteProcessFunctions {
   }
   pub fn process_del_route<H: RouteSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     let args = RouteDelRouteArgs::read_from_in_protocol(i_prot)?;
-    match handler.handle_del_route(args.ip_and_mask, args.nhop, args.ifname) {
+    match handler.handle_del_route(args.ip_and_mask, args.nhop, args.ifname, args.kind, args.vrf) {
       Ok(_) => {
         let message_ident = TMessageIdentifier::new("del_route", TMessageType::Reply, incoming_sequence_number);
         o_prot.write_message_begin(&message_ident)?;
@@ -383,9 +563,129 @@ impl TRouteProcessFunctions {
       },
     }
   }
+  pub fn process_add_routes<H: RouteSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = RouteAddRoutesArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_add_routes(args.routes, args.vrf) {
+      Ok(handler_return) => {
+        let message_ident = TMessageIdentifier::new("add_routes", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = RouteAddRoutesResult { result_value: Some(handler_return), ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<RouteErr>().is_some() {
+              let err = usr_err.downcast::<RouteErr>().expect("downcast already checked");
+              let ret_err = RouteAddRoutesResult{ result_value: None, ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("add_routes", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("add_routes", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("add_routes", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("add_routes", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
+  pub fn process_del_routes<H: RouteSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let args = RouteDelRoutesArgs::read_from_in_protocol(i_prot)?;
+    match handler.handle_del_routes(args.routes, args.vrf) {
+      Ok(handler_return) => {
+        let message_ident = TMessageIdentifier::new("del_routes", TMessageType::Reply, incoming_sequence_number);
+        o_prot.write_message_begin(&message_ident)?;
+        let ret = RouteDelRoutesResult { result_value: Some(handler_return), ouch: None };
+        ret.write_to_out_protocol(o_prot)?;
+        o_prot.write_message_end()?;
+        o_prot.flush()
+      },
+      Err(e) => {
+        match e {
+          thrift::Error::User(usr_err) => {
+            if usr_err.downcast_ref::<RouteErr>().is_some() {
+              let err = usr_err.downcast::<RouteErr>().expect("downcast already checked");
+              let ret_err = RouteDelRoutesResult{ result_value: None, ouch: Some(*err) };
+              let message_ident = TMessageIdentifier::new("del_routes", TMessageType::Reply, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              ret_err.write_to_out_protocol(o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            } else {
+              let ret_err = {
+                ApplicationError::new(
+                  ApplicationErrorKind::Unknown,
+                  usr_err.description()
+                )
+              };
+              let message_ident = TMessageIdentifier::new("del_routes", TMessageType::Exception, incoming_sequence_number);
+              o_prot.write_message_begin(&message_ident)?;
+              thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+              o_prot.write_message_end()?;
+              o_prot.flush()
+            }
+          },
+          thrift::Error::Application(app_err) => {
+            let message_ident = TMessageIdentifier::new("del_routes", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&app_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+          _ => {
+            let ret_err = {
+              ApplicationError::new(
+                ApplicationErrorKind::Unknown,
+                e.description()
+              )
+            };
+            let message_ident = TMessageIdentifier::new("del_routes", TMessageType::Exception, incoming_sequence_number);
+            o_prot.write_message_begin(&message_ident)?;
+            thrift::Error::write_application_error_to_out_protocol(&ret_err, o_prot)?;
+            o_prot.write_message_end()?;
+            o_prot.flush()
+          },
+        }
+      },
+    }
+  }
   pub fn process_show<H: RouteSyncHandler>(handler: &H, incoming_sequence_number: i32, i_prot: &mut dyn TInputProtocol, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
     let args = RouteShowArgs::read_from_in_protocol(i_prot)?;
-    match handler.handle_show(args.prefix, args.filename) {
+    match handler.handle_show(args.prefix, args.filename, args.vrf) {
       Ok(handler_return) => {
         let message_ident = TMessageIdentifier::new("show", TMessageType::Reply, incoming_sequence_number);
         o_prot.write_message_begin(&message_ident)?;
@@ -455,6 +755,12 @@ impl <H: RouteSyncHandler> TProcessor for RouteSyncProcessor<H> {
       "del_route" => {
         self.process_del_route(message_ident.sequence_number, i_prot, o_prot)
       },
+      "add_routes" => {
+        self.process_add_routes(message_ident.sequence_number, i_prot, o_prot)
+      },
+      "del_routes" => {
+        self.process_del_routes(message_ident.sequence_number, i_prot, o_prot)
+      },
       "show" => {
         self.process_show(message_ident.sequence_number, i_prot, o_prot)
       },
@@ -482,6 +788,8 @@ struct RouteAddRouteArgs {
   ip_and_mask: String,
   nhop: String,
   ifname: String,
+  kind: String,
+  vrf: String,
 }
 
 impl RouteAddRouteArgs {
@@ -490,6 +798,8 @@ impl RouteAddRouteArgs {
     let mut f_1: Option<String> = None;
     let mut f_2: Option<String> = None;
     let mut f_3: Option<String> = None;
+    let mut f_4: Option<String> = None;
+    let mut f_5: Option<String> = None;
     loop {
       let field_ident = i_prot.read_field_begin()?;
       if field_ident.field_type == TType::Stop {
@@ -509,6 +819,14 @@ impl RouteAddRouteArgs {
           let val = i_prot.read_string()?;
           f_3 = Some(val);
         },
+        4 => {
+          let val = i_prot.read_string()?;
+          f_4 = Some(val);
+        },
+        5 => {
+          let val = i_prot.read_string()?;
+          f_5 = Some(val);
+        },
         _ => {
           i_prot.skip(field_ident.field_type)?;
         },
@@ -519,10 +837,14 @@ impl RouteAddRouteArgs {
     verify_required_field_exists("RouteAddRouteArgs.ip_and_mask", &f_1)?;
     verify_required_field_exists("RouteAddRouteArgs.nhop", &f_2)?;
     verify_required_field_exists("RouteAddRouteArgs.ifname", &f_3)?;
+    verify_required_field_exists("RouteAddRouteArgs.kind", &f_4)?;
+    verify_required_field_exists("RouteAddRouteArgs.vrf", &f_5)?;
     let ret = RouteAddRouteArgs {
       ip_and_mask: f_1.expect("auto-generated code should have checked for presence of required fields"),
       nhop: f_2.expect("auto-generated code should have checked for presence of required fields"),
       ifname: f_3.expect("auto-generated code should have checked for presence of required fields"),
+      kind: f_4.expect("auto-generated code should have checked for presence of required fields"),
+      vrf: f_5.expect("auto-generated code should have checked for presence of required fields"),
     };
     Ok(ret)
   }
@@ -538,6 +860,12 @@ impl RouteAddRouteArgs {
     o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 3))?;
     o_prot.write_string(&self.ifname)?;
     o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("kind", TType::String, 4))?;
+    o_prot.write_string(&self.kind)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("vrf", TType::String, 5))?;
+    o_prot.write_string(&self.vrf)?;
+    o_prot.write_field_end()?;
     o_prot.write_field_stop()?;
     o_prot.write_struct_end()
   }
@@ -611,6 +939,8 @@ struct RouteDelRouteArgs {
   ip_and_mask: String,
   nhop: String,
   ifname: String,
+  kind: String,
+  vrf: String,
 }
 
 impl RouteDelRouteArgs {
@@ -619,6 +949,8 @@ impl RouteDelRouteArgs {
     let mut f_1: Option<String> = None;
     let mut f_2: Option<String> = None;
     let mut f_3: Option<String> = None;
+    let mut f_4: Option<String> = None;
+    let mut f_5: Option<String> = None;
     loop {
       let field_ident = i_prot.read_field_begin()?;
       if field_ident.field_type == TType::Stop {
@@ -638,6 +970,14 @@ impl RouteDelRouteArgs {
           let val = i_prot.read_string()?;
           f_3 = Some(val);
         },
+        4 => {
+          let val = i_prot.read_string()?;
+          f_4 = Some(val);
+        },
+        5 => {
+          let val = i_prot.read_string()?;
+          f_5 = Some(val);
+        },
         _ => {
           i_prot.skip(field_ident.field_type)?;
         },
@@ -648,10 +988,14 @@ impl RouteDelRouteArgs {
     verify_required_field_exists("RouteDelRouteArgs.ip_and_mask", &f_1)?;
     verify_required_field_exists("RouteDelRouteArgs.nhop", &f_2)?;
     verify_required_field_exists("RouteDelRouteArgs.ifname", &f_3)?;
+    verify_required_field_exists("RouteDelRouteArgs.kind", &f_4)?;
+    verify_required_field_exists("RouteDelRouteArgs.vrf", &f_5)?;
     let ret = RouteDelRouteArgs {
       ip_and_mask: f_1.expect("auto-generated code should have checked for presence of required fields"),
       nhop: f_2.expect("auto-generated code should have checked for presence of required fields"),
       ifname: f_3.expect("auto-generated code should have checked for presence of required fields"),
+      kind: f_4.expect("auto-generated code should have checked for presence of required fields"),
+      vrf: f_5.expect("auto-generated code should have checked for presence of required fields"),
     };
     Ok(ret)
   }
@@ -667,6 +1011,12 @@ impl RouteDelRouteArgs {
     o_prot.write_field_begin(&TFieldIdentifier::new("ifname", TType::String, 3))?;
     o_prot.write_string(&self.ifname)?;
     o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("kind", TType::String, 4))?;
+    o_prot.write_string(&self.kind)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("vrf", TType::String, 5))?;
+    o_prot.write_string(&self.vrf)?;
+    o_prot.write_field_end()?;
     o_prot.write_field_stop()?;
     o_prot.write_struct_end()
   }
@@ -731,6 +1081,330 @@ impl RouteDelRouteResult {
   }
 }
 
+//
+// RouteAddRoutesArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct RouteAddRoutesArgs {
+  routes: Vec<RouteEntry>,
+  vrf: String,
+}
+
+impl RouteAddRoutesArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<RouteAddRoutesArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<Vec<RouteEntry>> = None;
+    let mut f_2: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let list_ident = i_prot.read_list_begin()?;
+          let mut val: Vec<RouteEntry> = Vec::with_capacity(list_ident.size as usize);
+          for _ in 0..list_ident.size {
+            let list_elem_0 = RouteEntry::read_from_in_protocol(i_prot)?;
+            val.push(list_elem_0);
+          }
+          i_prot.read_list_end()?;
+          f_1 = Some(val);
+        },
+        2 => {
+          let val = i_prot.read_string()?;
+          f_2 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("RouteAddRoutesArgs.routes", &f_1)?;
+    verify_required_field_exists("RouteAddRoutesArgs.vrf", &f_2)?;
+    let ret = RouteAddRoutesArgs {
+      routes: f_1.expect("auto-generated code should have checked for presence of required fields"),
+      vrf: f_2.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("add_routes_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("routes", TType::List, 1))?;
+    o_prot.write_list_begin(&TListIdentifier::new(TType::Struct, self.routes.len() as i32))?;
+    for e in &self.routes {
+      e.write_to_out_protocol(o_prot)?;
+    }
+    o_prot.write_list_end()?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("vrf", TType::String, 2))?;
+    o_prot.write_string(&self.vrf)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// RouteAddRoutesResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct RouteAddRoutesResult {
+  result_value: Option<Vec<String>>,
+  ouch: Option<RouteErr>,
+}
+
+impl RouteAddRoutesResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<RouteAddRoutesResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_0: Option<Vec<String>> = None;
+    let mut f_1: Option<RouteErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        0 => {
+          let list_ident = i_prot.read_list_begin()?;
+          let mut val: Vec<String> = Vec::with_capacity(list_ident.size as usize);
+          for _ in 0..list_ident.size {
+            let list_elem_1 = i_prot.read_string()?;
+            val.push(list_elem_1);
+          }
+          i_prot.read_list_end()?;
+          f_0 = Some(val);
+        },
+        1 => {
+          let val = RouteErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = RouteAddRoutesResult {
+      result_value: f_0,
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("RouteAddRoutesResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.result_value {
+      o_prot.write_field_begin(&TFieldIdentifier::new("result_value", TType::List, 0))?;
+      o_prot.write_list_begin(&TListIdentifier::new(TType::String, fld_var.len() as i32))?;
+      for e in fld_var {
+        o_prot.write_string(e)?;
+      }
+      o_prot.write_list_end()?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<Vec<String>> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else if self.result_value.is_some() {
+      Ok(self.result_value.unwrap())
+    } else {
+      Err(
+        thrift::Error::Application(
+          ApplicationError::new(
+            ApplicationErrorKind::MissingResult,
+            "no result received for RouteAddRoutes"
+          )
+        )
+      )
+    }
+  }
+}
+
+//
+// RouteDelRoutesArgs
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct RouteDelRoutesArgs {
+  routes: Vec<RouteEntry>,
+  vrf: String,
+}
+
+impl RouteDelRoutesArgs {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<RouteDelRoutesArgs> {
+    i_prot.read_struct_begin()?;
+    let mut f_1: Option<Vec<RouteEntry>> = None;
+    let mut f_2: Option<String> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        1 => {
+          let list_ident = i_prot.read_list_begin()?;
+          let mut val: Vec<RouteEntry> = Vec::with_capacity(list_ident.size as usize);
+          for _ in 0..list_ident.size {
+            let list_elem_2 = RouteEntry::read_from_in_protocol(i_prot)?;
+            val.push(list_elem_2);
+          }
+          i_prot.read_list_end()?;
+          f_1 = Some(val);
+        },
+        2 => {
+          let val = i_prot.read_string()?;
+          f_2 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    verify_required_field_exists("RouteDelRoutesArgs.routes", &f_1)?;
+    verify_required_field_exists("RouteDelRoutesArgs.vrf", &f_2)?;
+    let ret = RouteDelRoutesArgs {
+      routes: f_1.expect("auto-generated code should have checked for presence of required fields"),
+      vrf: f_2.expect("auto-generated code should have checked for presence of required fields"),
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("del_routes_args");
+    o_prot.write_struct_begin(&struct_ident)?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("routes", TType::List, 1))?;
+    o_prot.write_list_begin(&TListIdentifier::new(TType::Struct, self.routes.len() as i32))?;
+    for e in &self.routes {
+      e.write_to_out_protocol(o_prot)?;
+    }
+    o_prot.write_list_end()?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("vrf", TType::String, 2))?;
+    o_prot.write_string(&self.vrf)?;
+    o_prot.write_field_end()?;
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+}
+
+//
+// RouteDelRoutesResult
+//
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct RouteDelRoutesResult {
+  result_value: Option<Vec<String>>,
+  ouch: Option<RouteErr>,
+}
+
+impl RouteDelRoutesResult {
+  fn read_from_in_protocol(i_prot: &mut dyn TInputProtocol) -> thrift::Result<RouteDelRoutesResult> {
+    i_prot.read_struct_begin()?;
+    let mut f_0: Option<Vec<String>> = None;
+    let mut f_1: Option<RouteErr> = None;
+    loop {
+      let field_ident = i_prot.read_field_begin()?;
+      if field_ident.field_type == TType::Stop {
+        break;
+      }
+      let field_id = field_id(&field_ident)?;
+      match field_id {
+        0 => {
+          let list_ident = i_prot.read_list_begin()?;
+          let mut val: Vec<String> = Vec::with_capacity(list_ident.size as usize);
+          for _ in 0..list_ident.size {
+            let list_elem_3 = i_prot.read_string()?;
+            val.push(list_elem_3);
+          }
+          i_prot.read_list_end()?;
+          f_0 = Some(val);
+        },
+        1 => {
+          let val = RouteErr::read_from_in_protocol(i_prot)?;
+          f_1 = Some(val);
+        },
+        _ => {
+          i_prot.skip(field_ident.field_type)?;
+        },
+      };
+      i_prot.read_field_end()?;
+    }
+    i_prot.read_struct_end()?;
+    let ret = RouteDelRoutesResult {
+      result_value: f_0,
+      ouch: f_1,
+    };
+    Ok(ret)
+  }
+  fn write_to_out_protocol(&self, o_prot: &mut dyn TOutputProtocol) -> thrift::Result<()> {
+    let struct_ident = TStructIdentifier::new("RouteDelRoutesResult");
+    o_prot.write_struct_begin(&struct_ident)?;
+    if let Some(ref fld_var) = self.result_value {
+      o_prot.write_field_begin(&TFieldIdentifier::new("result_value", TType::List, 0))?;
+      o_prot.write_list_begin(&TListIdentifier::new(TType::String, fld_var.len() as i32))?;
+      for e in fld_var {
+        o_prot.write_string(e)?;
+      }
+      o_prot.write_list_end()?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    if let Some(ref fld_var) = self.ouch {
+      o_prot.write_field_begin(&TFieldIdentifier::new("ouch", TType::Struct, 1))?;
+      fld_var.write_to_out_protocol(o_prot)?;
+      o_prot.write_field_end()?;
+      ()
+    } else {
+      ()
+    }
+    o_prot.write_field_stop()?;
+    o_prot.write_struct_end()
+  }
+  fn ok_or(self) -> thrift::Result<Vec<String>> {
+    if self.ouch.is_some() {
+      Err(thrift::Error::User(Box::new(self.ouch.unwrap())))
+    } else if self.result_value.is_some() {
+      Ok(self.result_value.unwrap())
+    } else {
+      Err(
+        thrift::Error::Application(
+          ApplicationError::new(
+            ApplicationErrorKind::MissingResult,
+            "no result received for RouteDelRoutes"
+          )
+        )
+      )
+    }
+  }
+}
+
 //
 // RouteShowArgs
 //
@@ -739,6 +1413,7 @@ impl RouteDelRouteResult {
 struct RouteShowArgs {
   prefix: String,
   filename: String,
+  vrf: String,
 }
 
 impl RouteShowArgs {
@@ -746,6 +1421,7 @@ impl RouteShowArgs {
     i_prot.read_struct_begin()?;
     let mut f_1: Option<String> = None;
     let mut f_2: Option<String> = None;
+    let mut f_3: Option<String> = None;
     loop {
       let field_ident = i_prot.read_field_begin()?;
       if field_ident.field_type == TType::Stop {
@@ -761,6 +1437,10 @@ impl RouteShowArgs {
           let val = i_prot.read_string()?;
           f_2 = Some(val);
         },
+        3 => {
+          let val = i_prot.read_string()?;
+          f_3 = Some(val);
+        },
         _ => {
           i_prot.skip(field_ident.field_type)?;
         },
@@ -770,9 +1450,11 @@ impl RouteShowArgs {
     i_prot.read_struct_end()?;
     verify_required_field_exists("RouteShowArgs.prefix", &f_1)?;
     verify_required_field_exists("RouteShowArgs.filename", &f_2)?;
+    verify_required_field_exists("RouteShowArgs.vrf", &f_3)?;
     let ret = RouteShowArgs {
       prefix: f_1.expect("auto-generated code should have checked for presence of required fields"),
       filename: f_2.expect("auto-generated code should have checked for presence of required fields"),
+      vrf: f_3.expect("auto-generated code should have checked for presence of required fields"),
     };
     Ok(ret)
   }
@@ -785,6 +1467,9 @@ impl RouteShowArgs {
     o_prot.write_field_begin(&TFieldIdentifier::new("filename", TType::String, 2))?;
     o_prot.write_string(&self.filename)?;
     o_prot.write_field_end()?;
+    o_prot.write_field_begin(&TFieldIdentifier::new("vrf", TType::String, 3))?;
+    o_prot.write_string(&self.vrf)?;
+    o_prot.write_field_end()?;
     o_prot.write_field_stop()?;
     o_prot.write_struct_end()
   }
@@ -0,0 +1,9 @@
+exception TraceErr {
+  1: string why
+}
+
+service Trace {
+    void enable(1:string ifname, 2:i32 count) throws (1:TraceErr ouch),
+    void clear() throws (1:TraceErr ouch),
+    string show() throws (1:TraceErr ouch),
+}
@@ -4,6 +4,9 @@ pub const API_SVR: &str = "127.0.0.1:5555";
 pub const LOG_APIS: &str = "log";
 pub const INTF_APIS: &str = "interface";
 pub const ROUTE_APIS: &str = "route";
+pub const TRACE_APIS: &str = "trace";
+pub const GRAPH_APIS: &str = "graph";
+pub const PBR_APIS: &str = "pbr";
 pub const R2CNT_SHM: &str = "r2cnt";
 pub const R2LOG_SHM: &str = "r2log";
 
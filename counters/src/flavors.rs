@@ -34,6 +34,10 @@ fn counter_name(node: &str, ctype: CounterType, name: &str) -> String {
 }
 
 impl Counter {
+    pub fn get(&self) -> u64 {
+        unsafe { *(self.count as *const u64) }
+    }
+
     pub fn incr(&mut self) -> u64 {
         self.add(1)
     }
@@ -2,6 +2,7 @@ use super::*;
 use counters::flavors::{Counter, CounterType};
 use counters::Counters;
 use crossbeam_queue::ArrayQueue;
+use dpdk_ffi::bindgen::{rte_eth_dev_close, rte_eth_dev_stop};
 use dpdk_ffi::{
     bindgen::lcore_function_t, bindgen::rte_dev_iterator, bindgen::rte_dev_probe,
     bindgen::rte_eal_init, bindgen::rte_eal_remote_launch, bindgen::rte_eth_conf,
@@ -347,6 +348,27 @@ impl Driver for Dpdk {
             }
         }
     }
+
+    // The counters are per port, they go away with the interface
+    fn free(&self, counters: &mut Counters) {
+        self.init_fail.free(counters);
+        self.no_pkts.free(counters);
+        self.send_err.free(counters);
+        self.recv_err.free(counters);
+    }
+}
+
+// The port is released when the interface owning it is deleted, so that the device can
+// be probed again if an interface with the same name is added back
+impl Drop for Dpdk {
+    fn drop(&mut self) {
+        unsafe {
+            if self.init_done {
+                rte_eth_dev_stop(self.port);
+            }
+            rte_eth_dev_close(self.port);
+        }
+    }
 }
 
 fn get_opt(opt: &str) -> *const libc::c_char {
@@ -10,3 +10,32 @@ impl Adjacency {
         Adjacency { nhop, ifindex }
     }
 }
+
+// Equal cost paths to a prefix, a flow is kept on one path by picking the path with a hash
+// of the flow
+pub struct Multipath {
+    pub paths: Vec<Arc<Adjacency>>,
+}
+
+impl Multipath {
+    pub fn new(paths: Vec<Arc<Adjacency>>) -> Multipath {
+        assert!(!paths.is_empty());
+        Multipath { paths }
+    }
+
+    pub fn path(&self, hash: u32) -> &Arc<Adjacency> {
+        &self.paths[hash as usize % self.paths.len()]
+    }
+}
+
+// The IPv6 flavour of an adjacency, a zero nhop means the destination is connected
+pub struct Adjacency6 {
+    pub nhop: Ipv6Addr,
+    pub ifindex: usize,
+}
+
+impl Adjacency6 {
+    pub fn new(nhop: Ipv6Addr, ifindex: usize) -> Adjacency6 {
+        Adjacency6 { nhop, ifindex }
+    }
+}
@@ -0,0 +1,80 @@
+// Packets for the unit tests of the graph nodes, that run the nodes in a graph::harness
+use super::*;
+
+/// The fields of the IPv4 header that ipv4_pkt() builds, the ones Ipv4Hdr::new() does not
+/// take are zero, other than the TTL which is IP_DEFAULT_TTL
+#[derive(Clone)]
+pub struct Ipv4Hdr {
+    pub src: Ipv4Addr,
+    pub dst: Ipv4Addr,
+    pub proto: u8,
+    pub ttl: u8,
+    pub tos: u8,
+    pub id: u16,
+    pub frag: u16,
+    pub opts: Vec<u8>,
+}
+
+impl Ipv4Hdr {
+    pub fn new(src: Ipv4Addr, dst: Ipv4Addr, proto: u8) -> Ipv4Hdr {
+        Ipv4Hdr {
+            src,
+            dst,
+            proto,
+            ttl: IP_DEFAULT_TTL,
+            tos: 0,
+            id: 0,
+            frag: 0,
+            opts: Vec::new(),
+        }
+    }
+}
+
+/// An IPv4 packet with the header 'hdr' and the payload, with the right header length,
+/// total length and checksum
+pub fn ipv4_pkt(hdr: &Ipv4Hdr, payload: &[u8]) -> Vec<u8> {
+    let ihl = IPHDR_MIN_LEN + hdr.opts.len();
+    let mut pkt = vec![0; IPHDR_MIN_LEN];
+    pkt[0] = 0x40 | (ihl / 4) as u8;
+    pkt[IPHDR_TOS_OFF] = hdr.tos;
+    let totlen = (ihl + payload.len()) as u16;
+    pkt[IPHDR_TOTLEN_OFF..IPHDR_TOTLEN_OFF + 2].copy_from_slice(&totlen.to_be_bytes());
+    pkt[IPHDR_ID_OFF..IPHDR_ID_OFF + 2].copy_from_slice(&hdr.id.to_be_bytes());
+    pkt[IPHDR_FRAG_OFF..IPHDR_FRAG_OFF + 2].copy_from_slice(&hdr.frag.to_be_bytes());
+    pkt[IPHDR_TTL_OFF] = hdr.ttl;
+    pkt[IPHDR_PROTO_OFF] = hdr.proto;
+    pkt[IPHDR_SADDR_OFF..IPHDR_SADDR_OFF + 4].copy_from_slice(&hdr.src.octets());
+    pkt[IPHDR_DADDR_OFF..IPHDR_DADDR_OFF + 4].copy_from_slice(&hdr.dst.octets());
+    pkt.extend_from_slice(&hdr.opts);
+    ipv4_checksum(&mut pkt);
+    pkt.extend_from_slice(payload);
+    pkt
+}
+
+/// Set the header checksum of an IPv4 packet, for the tests that change the header
+pub fn ipv4_checksum(pkt: &mut [u8]) {
+    let ihl = ((pkt[0] & 0xf) as usize) * 4;
+    pkt[IPHDR_CSUM_OFF] = 0;
+    pkt[IPHDR_CSUM_OFF + 1] = 0;
+    let csum = ip_checksum(&pkt[0..ihl]);
+    pkt[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+}
+
+/// An IPv6 packet with the given next header, hop limit and payload
+pub fn ipv6_pkt(src: Ipv6Addr, dst: Ipv6Addr, nexthdr: u8, hlim: u8, payload: &[u8]) -> Vec<u8> {
+    let mut pkt = vec![0; IP6HDR_LEN];
+    pkt[0] = 0x60;
+    let plen = payload.len() as u16;
+    pkt[IP6HDR_PLEN_OFF..IP6HDR_PLEN_OFF + 2].copy_from_slice(&plen.to_be_bytes());
+    pkt[IP6HDR_NEXTHDR_OFF] = nexthdr;
+    pkt[IP6HDR_HLIM_OFF] = hlim;
+    pkt[IP6HDR_SADDR_OFF..IP6HDR_SADDR_OFF + 16].copy_from_slice(&src.octets());
+    pkt[IP6HDR_DADDR_OFF..IP6HDR_DADDR_OFF + 16].copy_from_slice(&dst.octets());
+    pkt.extend_from_slice(payload);
+    pkt
+}
+
+/// A payload of 'len' bytes counting up from zero, so that the pieces of it can be told apart
+pub fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
@@ -1,7 +1,5 @@
 use super::*;
-use std::net::Ipv4Addr;
-
-pub const MAX_INTERFACES: usize = 4 * 1024;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 pub struct Interface {
     pub ifname: String,
@@ -10,8 +8,13 @@ pub struct Interface {
     pub mtu: usize,
     pub ipv4_addr: Ipv4Addr,
     pub mask_len: u32,
+    pub ipv6_addr: Ipv6Addr,
+    pub mask6_len: u32,
     pub l2_addr: Vec<u8>,
     pub headroom: usize,
+    // The VRF the interface is in, packets coming in on the interface are routed using the
+    // route table of the VRF
+    pub vrf: usize,
 }
 
 impl Interface {
@@ -23,8 +26,11 @@ impl Interface {
             mtu: ETHER_MTU,
             ipv4_addr: Ipv4Addr::new(0, 0, 0, 0),
             mask_len: 0,
+            ipv6_addr: ZERO_IP6,
+            mask6_len: 0,
             l2_addr,
             headroom,
+            vrf: DEFAULT_VRF,
         }
     }
 
@@ -36,6 +42,15 @@ impl Interface {
         self.ipv4_addr = addr;
         self.mask_len = mask_len;
     }
+
+    pub fn get_v6addr(&self) -> (Ipv6Addr, u32) {
+        (self.ipv6_addr, self.mask6_len)
+    }
+
+    pub fn set_v6addr(&mut self, addr: Ipv6Addr, mask_len: u32) {
+        self.ipv6_addr = addr;
+        self.mask6_len = mask_len;
+    }
 }
 
 impl Clone for Interface {
@@ -47,8 +62,11 @@ impl Clone for Interface {
             mtu: self.mtu,
             ipv4_addr: self.ipv4_addr,
             mask_len: self.mask_len,
+            ipv6_addr: self.ipv6_addr,
+            mask6_len: self.mask6_len,
             l2_addr: self.l2_addr.clone(),
             headroom: self.headroom,
+            vrf: self.vrf,
         }
     }
 }
@@ -1,18 +1,22 @@
-use super::{Arc, Fwd, IpLookupTable, Ipv4Addr};
+use super::{Adjacency, Arc, Fwd, IpLookupTable, Ipv4Addr, Multipath, ZERO_IP};
+use std::collections::BTreeMap;
 
+// The new route table of a VRF
 pub struct IPv4TableMsg {
+    pub vrf: usize,
     pub table: Arc<IPv4Table>,
 }
 
 impl IPv4TableMsg {
-    pub fn new(table: Arc<IPv4Table>) -> IPv4TableMsg {
-        IPv4TableMsg { table }
+    pub fn new(vrf: usize, table: Arc<IPv4Table>) -> IPv4TableMsg {
+        IPv4TableMsg { vrf, table }
     }
 }
 
 impl Clone for IPv4TableMsg {
     fn clone(&self) -> IPv4TableMsg {
         IPv4TableMsg {
+            vrf: self.vrf,
             table: self.table.clone(),
         }
     }
@@ -28,20 +32,49 @@ impl IPv4Leaf {
     }
 }
 
+// The adjacencies a leaf forwards to
+fn leaf_paths(next: &Fwd) -> Vec<Arc<Adjacency>> {
+    match next {
+        Fwd::Adjacency(adj) => vec![adj.clone()],
+        Fwd::Multipath(mp) => mp.paths.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn paths_leaf(mut paths: Vec<Arc<Adjacency>>) -> Fwd {
+    if paths.len() == 1 {
+        Fwd::Adjacency(paths.pop().unwrap())
+    } else {
+        Fwd::Multipath(Arc::new(Multipath::new(paths)))
+    }
+}
+
+fn same_path(a: &Adjacency, b: &Adjacency) -> bool {
+    a.nhop == b.nhop && a.ifindex == b.ifindex
+}
+
+// The leaves are kept as Options only because the treebitmap remove() fills the slot it
+// frees with zeroes in debug builds, which is not a valid Arc. There are no None leaves.
+// A route is either a set of paths, or a set of recursive nexthops (vias) that are not on
+// a connected subnet. The recursive nexthops are resolved through the other routes in the
+// table, and the route is in the table as a Fwd::IPv4Leaf pointing to a leaf with the paths
+// the nexthops resolved to - only when at least one of them resolved
 #[derive(Default)]
 pub struct IPv4Table {
-    pub root: IpLookupTable<Ipv4Addr, Arc<IPv4Leaf>>,
+    root: IpLookupTable<Ipv4Addr, Option<Arc<IPv4Leaf>>>,
+    recursive: BTreeMap<(Ipv4Addr, u32), Vec<Ipv4Addr>>,
 }
 
 impl IPv4Table {
     pub fn new() -> IPv4Table {
         IPv4Table {
             root: IpLookupTable::new(),
+            recursive: BTreeMap::new(),
         }
     }
 
     pub fn add(&mut self, ip: Ipv4Addr, masklen: u32, value: Arc<IPv4Leaf>) -> bool {
-        let dup = self.root.insert(ip, masklen, value);
+        let dup = self.root.insert(ip, masklen, Some(value));
         dup.is_none()
     }
 
@@ -49,4 +82,208 @@ impl IPv4Table {
         let ret = self.root.remove(ip, masklen);
         ret.is_some()
     }
+
+    pub fn longest_match(&self, ip: Ipv4Addr) -> Option<(Ipv4Addr, u32, &Arc<IPv4Leaf>)> {
+        let (prefix, masklen, leaf) = self.root.longest_match(ip)?;
+        leaf.as_ref().map(|leaf| (prefix, masklen, leaf))
+    }
+
+    pub fn exact_match(&self, ip: Ipv4Addr, masklen: u32) -> Option<&Arc<IPv4Leaf>> {
+        self.root.exact_match(ip, masklen)?.as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Ipv4Addr, u32, &Arc<IPv4Leaf>)> {
+        let iter = self.root.iter();
+        iter.filter_map(|(ip, masklen, leaf)| leaf.as_ref().map(|leaf| (ip, masklen, leaf)))
+    }
+
+    // The adjacencies of a route, none if the route is not there or does not forward packets
+    // or is a recursive route
+    pub fn paths(&self, ip: Ipv4Addr, masklen: u32) -> Vec<Arc<Adjacency>> {
+        match self.exact_match(ip, masklen) {
+            Some(leaf) => leaf_paths(&leaf.next),
+            None => Vec::new(),
+        }
+    }
+
+    fn set_paths(&mut self, ip: Ipv4Addr, masklen: u32, paths: Vec<Arc<Adjacency>>) {
+        self.add(ip, masklen, Arc::new(IPv4Leaf::new(paths_leaf(paths))));
+    }
+
+    // Add a path to a route, the route is created with this path if it does not exist. Returns
+    // false if the route already has the path
+    pub fn add_path(&mut self, ip: Ipv4Addr, masklen: u32, adj: Arc<Adjacency>) -> bool {
+        let mut paths = self.paths(ip, masklen);
+        if paths.iter().any(|p| same_path(p, &adj)) {
+            return false;
+        }
+        paths.push(adj);
+        self.set_paths(ip, masklen, paths);
+        true
+    }
+
+    // Remove a path from a route, the route goes away with its last path. Returns false if
+    // the route does not have the path
+    pub fn del_path(&mut self, ip: Ipv4Addr, masklen: u32, nhop: Ipv4Addr, ifindex: usize) -> bool {
+        let mut paths = self.paths(ip, masklen);
+        let len = paths.len();
+        paths.retain(|p| p.nhop != nhop || p.ifindex != ifindex);
+        if paths.len() == len {
+            return false;
+        }
+        if paths.is_empty() {
+            self.del(ip, masklen);
+        } else {
+            self.set_paths(ip, masklen, paths);
+        }
+        true
+    }
+
+    // The recursive nexthops of the routes, resolved or not
+    pub fn recursive(&self) -> impl Iterator<Item = (Ipv4Addr, u32, &Vec<Ipv4Addr>)> {
+        self.recursive
+            .iter()
+            .map(|((ip, masklen), vias)| (*ip, *masklen, vias))
+    }
+
+    fn is_recursive(&self, ip: Ipv4Addr, masklen: u32) -> bool {
+        match self.exact_match(ip, masklen) {
+            Some(leaf) => matches!(leaf.next, Fwd::IPv4Leaf(_)),
+            None => false,
+        }
+    }
+
+    // Add a recursive nexthop to a route, the route forwards packets only once resolve() finds
+    // a route to the nexthop. Returns false if the route already has the nexthop
+    pub fn add_via(&mut self, ip: Ipv4Addr, masklen: u32, via: Ipv4Addr) -> bool {
+        let vias = self.recursive.entry((ip, masklen)).or_default();
+        if vias.contains(&via) {
+            return false;
+        }
+        vias.push(via);
+        true
+    }
+
+    // Remove a recursive nexthop from a route, the route goes away with its last nexthop.
+    // Returns false if the route does not have the nexthop
+    pub fn del_via(&mut self, ip: Ipv4Addr, masklen: u32, via: Ipv4Addr) -> bool {
+        let vias = match self.recursive.get_mut(&(ip, masklen)) {
+            Some(vias) => vias,
+            None => return false,
+        };
+        let len = vias.len();
+        vias.retain(|v| *v != via);
+        if vias.len() == len {
+            return false;
+        }
+        if vias.is_empty() {
+            self.recursive.remove(&(ip, masklen));
+            if self.is_recursive(ip, masklen) {
+                self.del(ip, masklen);
+            }
+        }
+        true
+    }
+
+    // The paths to a recursive nexthop, those of the route the nexthop matches in the table.
+    // On a connected subnet, the nexthop itself is the neighbor to send the packets to
+    fn resolve_via(&self, via: Ipv4Addr) -> Vec<Arc<Adjacency>> {
+        let next = match self.longest_match(via) {
+            Some((_, _, leaf)) => &leaf.next,
+            None => return Vec::new(),
+        };
+        let next = match next {
+            Fwd::IPv4Leaf(resolved) => &resolved.next,
+            next => next,
+        };
+        let paths = leaf_paths(next).into_iter();
+        paths
+            .map(|adj| {
+                if adj.nhop == ZERO_IP {
+                    Arc::new(Adjacency::new(via, adj.ifindex))
+                } else {
+                    adj
+                }
+            })
+            .collect()
+    }
+
+    // Resolve all the recursive routes again, this has to be done after every change to the
+    // table. The nexthops are looked up in the table with the recursive routes resolved so
+    // far, a pass at a time till nothing changes, so a recursive route can resolve through
+    // another recursive route. A route does not resolve through itself, and a route with
+    // paths of its own ignores its recursive nexthops
+    pub fn resolve(&mut self) {
+        if self.recursive.is_empty() {
+            return;
+        }
+        let routes: Vec<(Ipv4Addr, u32, Vec<Ipv4Addr>)> = self
+            .recursive()
+            .map(|(ip, masklen, vias)| (ip, masklen, vias.clone()))
+            .collect();
+        // Start from scratch so that recursive routes can not keep each other resolved
+        for (ip, masklen, _) in routes.iter() {
+            if self.is_recursive(*ip, *masklen) {
+                self.del(*ip, *masklen);
+            }
+        }
+        for _ in 0..=routes.len() {
+            let mut changed = false;
+            for (ip, masklen, vias) in routes.iter() {
+                let (ip, masklen) = (*ip, *masklen);
+                let installed = self.exact_match(ip, masklen).cloned();
+                let current = match installed.as_ref().map(|leaf| &leaf.next) {
+                    Some(Fwd::IPv4Leaf(resolved)) => leaf_paths(&resolved.next),
+                    Some(_) => continue,
+                    None => Vec::new(),
+                };
+                if installed.is_some() {
+                    self.del(ip, masklen);
+                }
+                let mut paths: Vec<Arc<Adjacency>> = Vec::new();
+                for via in vias.iter() {
+                    for adj in self.resolve_via(*via) {
+                        if !paths.iter().any(|p| same_path(p, &adj)) {
+                            paths.push(adj);
+                        }
+                    }
+                }
+                let same = paths.len() == current.len()
+                    && paths
+                        .iter()
+                        .zip(current.iter())
+                        .all(|(a, b)| same_path(a, b));
+                if !same {
+                    changed = true;
+                }
+                match installed {
+                    // Nothing changed, put the same leaf back
+                    Some(leaf) if same => {
+                        self.add(ip, masklen, leaf);
+                    }
+                    _ if !paths.is_empty() => {
+                        let resolved = Arc::new(IPv4Leaf::new(paths_leaf(paths)));
+                        let leaf = IPv4Leaf::new(Fwd::IPv4Leaf(resolved));
+                        self.add(ip, masklen, Arc::new(leaf));
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+// The copy shares the leaves with the original table, only the trie itself is copied
+impl Clone for IPv4Table {
+    fn clone(&self) -> IPv4Table {
+        let mut table = IPv4Table::new();
+        for (ip, masklen, leaf) in self.iter() {
+            table.add(ip, masklen, leaf.clone());
+        }
+        table.recursive = self.recursive.clone();
+        table
+    }
 }
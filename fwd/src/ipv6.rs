@@ -0,0 +1,79 @@
+use super::{Arc, Fwd, IpLookupTable, Ipv6Addr};
+
+pub struct IPv6TableMsg {
+    pub table: Arc<IPv6Table>,
+}
+
+impl IPv6TableMsg {
+    pub fn new(table: Arc<IPv6Table>) -> IPv6TableMsg {
+        IPv6TableMsg { table }
+    }
+}
+
+impl Clone for IPv6TableMsg {
+    fn clone(&self) -> IPv6TableMsg {
+        IPv6TableMsg {
+            table: self.table.clone(),
+        }
+    }
+}
+
+pub struct IPv6Leaf {
+    pub next: Fwd,
+}
+
+impl IPv6Leaf {
+    pub fn new(fwd: Fwd) -> IPv6Leaf {
+        IPv6Leaf { next: fwd }
+    }
+}
+
+// The leaves are kept as Options only because the treebitmap remove() fills the slot it
+// frees with zeroes in debug builds, which is not a valid Arc. There are no None leaves
+#[derive(Default)]
+pub struct IPv6Table {
+    root: IpLookupTable<Ipv6Addr, Option<Arc<IPv6Leaf>>>,
+}
+
+impl IPv6Table {
+    pub fn new() -> IPv6Table {
+        IPv6Table {
+            root: IpLookupTable::new(),
+        }
+    }
+
+    pub fn add(&mut self, ip: Ipv6Addr, masklen: u32, value: Arc<IPv6Leaf>) -> bool {
+        let dup = self.root.insert(ip, masklen, Some(value));
+        dup.is_none()
+    }
+
+    pub fn del(&mut self, ip: Ipv6Addr, masklen: u32) -> bool {
+        let ret = self.root.remove(ip, masklen);
+        ret.is_some()
+    }
+
+    pub fn longest_match(&self, ip: Ipv6Addr) -> Option<(Ipv6Addr, u32, &Arc<IPv6Leaf>)> {
+        let (prefix, masklen, leaf) = self.root.longest_match(ip)?;
+        leaf.as_ref().map(|leaf| (prefix, masklen, leaf))
+    }
+
+    pub fn exact_match(&self, ip: Ipv6Addr, masklen: u32) -> Option<&Arc<IPv6Leaf>> {
+        self.root.exact_match(ip, masklen)?.as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Ipv6Addr, u32, &Arc<IPv6Leaf>)> {
+        let iter = self.root.iter();
+        iter.filter_map(|(ip, masklen, leaf)| leaf.as_ref().map(|leaf| (ip, masklen, leaf)))
+    }
+}
+
+// The copy shares the leaves with the original table, only the trie itself is copied
+impl Clone for IPv6Table {
+    fn clone(&self) -> IPv6Table {
+        let mut table = IPv6Table::new();
+        for (ip, masklen, leaf) in self.iter() {
+            table.add(ip, masklen, leaf.clone());
+        }
+        table
+    }
+}
@@ -1,16 +1,23 @@
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
 use treebitmap::IpLookupTable;
 pub mod ipv4;
 use ipv4::IPv4Leaf;
 pub mod adj;
-use adj::Adjacency;
+pub mod ipv6;
+use adj::{Adjacency, Adjacency6, Multipath};
 pub mod intf;
 use intf::Interface;
+pub mod neigh;
+pub mod pbr;
+#[cfg(any(test, feature = "test-harness"))]
+pub mod harness;
+use neigh::NeighState;
 use std::str::FromStr;
 
 pub const ETH_TYPE_ARP: u16 = 0x0806;
 pub const ETH_TYPE_IPV4: u16 = 0x0800;
+pub const ETH_TYPE_IPV6: u16 = 0x86dd;
 pub const ARP_HWTYPE_ETH: u16 = 0x0001;
 pub const ARP_OPCODE_REQ: u16 = 0x0001;
 pub const ARP_OPCODE_REPLY: u16 = 0x0002;
@@ -20,8 +27,64 @@ pub const ETHER_MTU: usize = 1500;
 pub const BCAST_MAC: &[u8; ETH_ALEN] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
 pub const ZERO_MAC: &[u8; ETH_ALEN] = &[0, 0, 0, 0, 0, 0];
 pub const ZERO_IP: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
+pub const ZERO_IP6: Ipv6Addr = Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0);
+// The id of the VRF (the routing table) interfaces are in unless they are put in another one
+pub const DEFAULT_VRF: usize = 0;
 pub const IPHDR_MIN_LEN: usize = 20;
+pub const IPHDR_TOS_OFF: usize = 1;
+pub const IPHDR_TOTLEN_OFF: usize = 2;
+pub const IPHDR_ID_OFF: usize = 4;
+pub const IPHDR_FRAG_OFF: usize = 6;
+pub const IPHDR_TTL_OFF: usize = 8;
+pub const IPHDR_PROTO_OFF: usize = 9;
+pub const IPHDR_CSUM_OFF: usize = 10;
+pub const IPHDR_SADDR_OFF: usize = 12;
 pub const IPHDR_DADDR_OFF: usize = 16;
+pub const IPHDR_FRAG_MASK: u16 = 0x1fff;
+pub const IPHDR_MF: u16 = 0x2000;
+pub const IPHDR_DF: u16 = 0x4000;
+// Every IPv4 link has to carry packets of this size without fragmenting them (RFC 791)
+pub const IP_MIN_MTU: usize = 68;
+pub const IP_DEFAULT_TTL: u8 = 64;
+pub const IP_PROTO_ICMP: u8 = 1;
+pub const IP_PROTO_TCP: u8 = 6;
+pub const IP_PROTO_UDP: u8 = 17;
+pub const ICMP_HDR_LEN: usize = 8;
+pub const ICMP_TYPE_OFF: usize = 0;
+pub const ICMP_CODE_OFF: usize = 1;
+pub const ICMP_CSUM_OFF: usize = 2;
+// The next-hop MTU in a fragmentation needed (RFC 1191)
+pub const ICMP_MTU_OFF: usize = 6;
+pub const ICMP_ECHO_REPLY: u8 = 0;
+pub const ICMP_DEST_UNREACH: u8 = 3;
+pub const ICMP_ECHO_REQUEST: u8 = 8;
+// The codes of a destination unreachable for a packet with no route, for a packet to a
+// reject route, and for a packet too big for the MTU with DF set
+pub const ICMP_NET_UNREACH: u8 = 0;
+pub const ICMP_HOST_UNREACH: u8 = 1;
+pub const ICMP_FRAG_NEEDED: u8 = 4;
+pub const ICMP_TIME_EXCEEDED: u8 = 11;
+pub const IP_PROTO_ICMP6: u8 = 58;
+// The ICMPv6 errors (RFC 4443), the types below 128 are all errors. A packet too big carries
+// the 32 bit MTU of the link the packet could not go out on at ICMP6_MTU_OFF
+pub const ICMP6_DEST_UNREACH: u8 = 1;
+pub const ICMP6_PKT_TOO_BIG: u8 = 2;
+pub const ICMP6_TIME_EXCEEDED: u8 = 3;
+pub const ICMP6_INFO_MIN: u8 = 128;
+// The codes of a destination unreachable for a packet with no route, and for a packet to a
+// reject route
+pub const ICMP6_NO_ROUTE: u8 = 0;
+pub const ICMP6_REJECT_ROUTE: u8 = 5;
+pub const ICMP6_MTU_OFF: usize = 4;
+// Every IPv6 link has to carry packets of this size (RFC 8200), an ICMPv6 error is never
+// bigger than this
+pub const IP6_MIN_MTU: usize = 1280;
+pub const IP6HDR_LEN: usize = 40;
+pub const IP6HDR_PLEN_OFF: usize = 4;
+pub const IP6HDR_NEXTHDR_OFF: usize = 6;
+pub const IP6HDR_HLIM_OFF: usize = 7;
+pub const IP6HDR_SADDR_OFF: usize = 8;
+pub const IP6HDR_DADDR_OFF: usize = 24;
 
 pub enum EthOffsets {
     EthDaddrOff = 0,
@@ -49,10 +112,15 @@ pub struct EthMacRaw {
     pub bytes: Arc<Vec<u8>>,
 }
 
+// An ARP or ND neighbor's mac, state is Reachable if the neighbor just confirmed it. A mac
+// from a neighbor advertisement (advert) only updates an existing entry, see
+// NeighCache::advert()
 pub struct EthMacAddMsg {
     pub ifindex: usize,
-    pub ip: Ipv4Addr,
+    pub ip: IpAddr,
     pub mac: EthMacRaw,
+    pub state: NeighState,
+    pub advert: bool,
 }
 
 impl Clone for EthMacAddMsg {
@@ -63,6 +131,8 @@ impl Clone for EthMacAddMsg {
             mac: EthMacRaw {
                 bytes: self.mac.bytes.clone(),
             },
+            state: self.state,
+            advert: self.advert,
         }
     }
 }
@@ -87,7 +157,16 @@ pub struct IpHdr {
 pub enum Fwd {
     IPv4Leaf(Arc<IPv4Leaf>),
     Adjacency(Arc<Adjacency>),
+    // More than one adjacency for the same prefix (ECMP)
+    Multipath(Arc<Multipath>),
+    Adjacency6(Arc<Adjacency6>),
     Interface(Arc<Interface>),
+    // An address owned by R2 on the interface with this ifindex, the packets are for R2 itself
+    Local(usize),
+    // A null route, the packets are dropped silently
+    Blackhole,
+    // The packets are dropped and the sender is told the destination is unreachable
+    Reject,
 }
 
 pub fn str_to_mac(mac: &str) -> Option<Vec<u8>> {
@@ -107,6 +186,65 @@ pub fn str_to_mac(mac: &str) -> Option<Vec<u8>> {
     }
 }
 
+// Add up the data as big endian 16 bit words, the data is padded with a zero byte if the
+// length is odd
+fn csum_add(mut sum: u32, data: &[u8]) -> u32 {
+    for word in data.chunks(2) {
+        if word.len() == 2 {
+            sum += u32::from(u16::from_be_bytes([word[0], word[1]]));
+        } else {
+            sum += u32::from(word[0]) << 8;
+        }
+    }
+    sum
+}
+
+fn csum_fold(mut sum: u32) -> u16 {
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// The internet checksum (RFC 1071) of the data, used in IP, ICMP etc.. headers. The checksum
+/// field in the data is expected to be zero when the checksum is calculated
+pub fn ip_checksum(data: &[u8]) -> u16 {
+    csum_fold(csum_add(0, data))
+}
+
+/// Update a checksum when one 16 bit word covered by the checksum changes from 'old' to
+/// 'new', without going over all the data again (RFC 1624)
+pub fn ip_checksum_update(csum: u16, old: u16, new: u16) -> u16 {
+    let sum = u32::from(!csum) + u32::from(!old) + u32::from(new);
+    csum_fold(sum)
+}
+
+/// Whether the ICMP message is a query (echo, timestamp, address mask and the like) rather
+/// than an error. ICMP errors are never sent about ICMP errors (RFC 1122 section 3.2.2), and
+/// types we dont know of are taken to be errors to stay on the safe side
+pub fn icmp_is_query(icmp_type: u8) -> bool {
+    matches!(
+        icmp_type,
+        ICMP_ECHO_REPLY | ICMP_ECHO_REQUEST | 9 | 10 | 13..=18
+    )
+}
+
+pub fn ipv6_addr(bytes: &[u8]) -> Ipv6Addr {
+    let mut octets = [0; 16];
+    octets.copy_from_slice(&bytes[0..16]);
+    Ipv6Addr::from(octets)
+}
+
+/// The ICMPv6 checksum, which also covers a pseudo header of the source and destination
+/// addresses, the ICMPv6 message length and the next header value (RFC 8200 section 8.1)
+pub fn icmp6_checksum(saddr: &Ipv6Addr, daddr: &Ipv6Addr, msg: &[u8]) -> u16 {
+    let len = msg.len() as u32;
+    let mut sum = csum_add(0, &saddr.octets());
+    sum = csum_add(sum, &daddr.octets());
+    sum += (len >> 16) + (len & 0xffff) + u32::from(IP_PROTO_ICMP6);
+    csum_fold(csum_add(sum, msg))
+}
+
 pub fn ip_mask_decode(ip_and_mask: &str) -> Option<(Ipv4Addr, u32)> {
     let im = ip_and_mask.split('/');
     let im: Vec<&str> = im.collect();
@@ -114,12 +252,26 @@ pub fn ip_mask_decode(ip_and_mask: &str) -> Option<(Ipv4Addr, u32)> {
         return None;
     }
     if let Ok(ipv4) = Ipv4Addr::from_str(im[0]) {
-        if let Ok(masklen) = im[1].parse::<u32>() {
-            Some((ipv4, masklen))
-        } else {
-            None
+        match im[1].parse::<u32>() {
+            Ok(masklen) if masklen <= 32 => Some((ipv4, masklen)),
+            _ => None,
         }
     } else {
         None
     }
 }
+
+pub fn ip6_mask_decode(ip_and_mask: &str) -> Option<(Ipv6Addr, u32)> {
+    let im: Vec<&str> = ip_and_mask.split('/').collect();
+    if im.len() != 2 {
+        return None;
+    }
+    let ipv6 = Ipv6Addr::from_str(im[0]).ok()?;
+    match im[1].parse::<u32>() {
+        Ok(masklen) if masklen <= 128 => Some((ipv6, masklen)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,324 @@
+use super::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+
+pub const ICMP6_ROUTER_SOLICIT: u8 = 133;
+pub const ICMP6_ROUTER_ADVERT: u8 = 134;
+pub const ICMP6_NEIGH_SOLICIT: u8 = 135;
+pub const ICMP6_NEIGH_ADVERT: u8 = 136;
+// ND messages are only accepted with this hop limit, proving they did not cross a router
+pub const ND_HOP_LIMIT: u8 = 255;
+// The solicitation/advertisement header is type, code, checksum, flags + reserved and the
+// target address, followed by the options
+pub const ND_HDR_LEN: usize = 24;
+pub const ND_CODE_OFF: usize = 1;
+pub const ND_CSUM_OFF: usize = 2;
+pub const ND_FLAGS_OFF: usize = 4;
+pub const ND_TARGET_OFF: usize = 8;
+pub const ND_OPT_SRC_LLADDR: u8 = 1;
+pub const ND_OPT_TGT_LLADDR: u8 = 2;
+// Options are in units of 8 bytes, a link layer address option for ethernet is one unit
+pub const ND_OPT_UNIT: usize = 8;
+pub const ND_NA_ROUTER: u8 = 0x80;
+pub const ND_NA_SOLICITED: u8 = 0x40;
+pub const ND_NA_OVERRIDE: u8 = 0x20;
+pub const ALL_NODES_IP6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+
+// How long a neighbor stays reachable after it was last confirmed, how long to wait between
+// solicitations, and how many unanswered unicast solicitations before giving up on it
+pub const REACHABLE_MSECS: u64 = 30_000;
+pub const RETRANS_MSECS: u64 = 1_000;
+pub const MAX_UNICAST_SOLICIT: u8 = 3;
+// The most entries a cache holds. Entries not in use age out of a full cache: Incomplete
+// entries not solicited for INCOMPLETE_MSECS and Stale entries not used for STALE_MSECS
+pub const MAX_NEIGHS: usize = 1024;
+pub const INCOMPLETE_MSECS: u64 = MAX_UNICAST_SOLICIT as u64 * RETRANS_MSECS;
+pub const STALE_MSECS: u64 = 60_000;
+
+// The neighbor reachability states of RFC 4861, used for ARP entries too. The DELAY state
+// is skipped, a STALE entry in use is probed right away
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NeighState {
+    // Solicited, no mac yet
+    Incomplete,
+    // The neighbor answered a solicitation recently
+    Reachable,
+    // The mac is known but not confirmed recently, or was learnt from the neighbor's own
+    // solicitation
+    Stale,
+    // The mac is in use and unicast solicitations are out to confirm it
+    Probe,
+}
+
+impl fmt::Display for NeighState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            NeighState::Incomplete => "incomplete",
+            NeighState::Reachable => "reachable",
+            NeighState::Stale => "stale",
+            NeighState::Probe => "probe",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+struct NeighEntry {
+    mac: [u8; ETH_ALEN],
+    state: NeighState,
+    // When the state last changed, or when the last solicitation went out
+    updated: u64,
+    probes: u8,
+}
+
+// What to do with a packet to a neighbor
+#[derive(Debug, PartialEq)]
+pub enum Resolve {
+    // Send the packet to this mac
+    Mac([u8; ETH_ALEN]),
+    // Send the packet to this mac, and also a unicast solicitation to the neighbor
+    Probe([u8; ETH_ALEN]),
+    // The mac is not known, send a multicast (or broadcast) solicitation
+    Solicit,
+    // The mac is not known and a solicitation went out recently, or the cache is full
+    Wait,
+}
+
+// The ip to mac cache of an interface, ARP and ND entries alike. The cache does not have a
+// clock of its own, the callers pass in the current time in milliseconds. The cache holds
+// at most MAX_NEIGHS entries, see make_room()
+#[derive(Default)]
+pub struct NeighCache {
+    entries: HashMap<IpAddr, NeighEntry>,
+}
+
+impl NeighCache {
+    pub fn new() -> NeighCache {
+        NeighCache {
+            entries: HashMap::new(),
+        }
+    }
+
+    // The neighbor told us its mac. A Reachable update is a confirmation (a solicited
+    // advertisement or an ARP reply), a Stale update just carries the mac and leaves an
+    // existing entry alone if the mac has not changed
+    pub fn update(&mut self, ip: IpAddr, mac: &[u8], state: NeighState, now: u64) {
+        match self.entries.get(&ip) {
+            Some(e) => {
+                if state != NeighState::Reachable
+                    && e.state != NeighState::Incomplete
+                    && e.mac[..] == mac[0..ETH_ALEN]
+                {
+                    return;
+                }
+            }
+            None => {
+                if !self.make_room(now) {
+                    return;
+                }
+            }
+        }
+        let mut bytes = [0; ETH_ALEN];
+        bytes.copy_from_slice(&mac[0..ETH_ALEN]);
+        let entry = NeighEntry {
+            mac: bytes,
+            state,
+            updated: now,
+            probes: 0,
+        };
+        self.entries.insert(ip, entry);
+    }
+
+    // A neighbor advertisement only updates an entry that is already there, an unsolicited
+    // advertisement for a neighbor we never asked about is ignored (RFC 4861 section 7.2.5)
+    pub fn advert(&mut self, ip: IpAddr, mac: &[u8], state: NeighState, now: u64) {
+        if self.entries.contains_key(&ip) {
+            self.update(ip, mac, state, now);
+        }
+    }
+
+    // Make room for a new entry if the cache is full. The Incomplete and Stale entries that
+    // are not in use any more age out first, and if that is not enough the oldest of the
+    // Incomplete and Stale entries goes. Reachable and Probe entries are never evicted, if
+    // the cache is full of those there is no room for the new entry
+    fn make_room(&mut self, now: u64) -> bool {
+        if self.entries.len() < MAX_NEIGHS {
+            return true;
+        }
+        self.entries.retain(|_, e| {
+            let elapsed = now.saturating_sub(e.updated);
+            match e.state {
+                NeighState::Incomplete => elapsed < INCOMPLETE_MSECS,
+                NeighState::Stale => elapsed < STALE_MSECS,
+                NeighState::Reachable | NeighState::Probe => true,
+            }
+        });
+        if self.entries.len() < MAX_NEIGHS {
+            return true;
+        }
+        let oldest = self
+            .entries
+            .iter()
+            .filter(|(_, e)| matches!(e.state, NeighState::Incomplete | NeighState::Stale))
+            .min_by_key(|(_, e)| e.updated)
+            .map(|(ip, _)| *ip);
+        match oldest {
+            Some(ip) => {
+                self.entries.remove(&ip);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn resolve(&mut self, ip: IpAddr, now: u64) -> Resolve {
+        let e = match self.entries.get_mut(&ip) {
+            Some(e) => e,
+            None => {
+                // No room to remember the solicitation, the packet just waits
+                if !self.make_room(now) {
+                    return Resolve::Wait;
+                }
+                let entry = NeighEntry {
+                    mac: [0; ETH_ALEN],
+                    state: NeighState::Incomplete,
+                    updated: now,
+                    probes: 0,
+                };
+                self.entries.insert(ip, entry);
+                return Resolve::Solicit;
+            }
+        };
+        let elapsed = now.saturating_sub(e.updated);
+        match e.state {
+            NeighState::Incomplete => {
+                if elapsed >= RETRANS_MSECS {
+                    e.updated = now;
+                    Resolve::Solicit
+                } else {
+                    Resolve::Wait
+                }
+            }
+            NeighState::Reachable => {
+                if elapsed >= REACHABLE_MSECS {
+                    e.state = NeighState::Stale;
+                    e.updated = now;
+                }
+                Resolve::Mac(e.mac)
+            }
+            NeighState::Stale => {
+                e.state = NeighState::Probe;
+                e.updated = now;
+                e.probes = 1;
+                Resolve::Probe(e.mac)
+            }
+            NeighState::Probe => {
+                if elapsed < RETRANS_MSECS {
+                    Resolve::Mac(e.mac)
+                } else if e.probes < MAX_UNICAST_SOLICIT {
+                    e.updated = now;
+                    e.probes += 1;
+                    Resolve::Probe(e.mac)
+                } else {
+                    // The neighbor is gone, start over
+                    e.state = NeighState::Incomplete;
+                    e.updated = now;
+                    e.probes = 0;
+                    Resolve::Solicit
+                }
+            }
+        }
+    }
+
+    pub fn dump(&self) -> Vec<(IpAddr, Vec<u8>, NeighState)> {
+        let mut entries: Vec<(IpAddr, Vec<u8>, NeighState)> = self
+            .entries
+            .iter()
+            .map(|(ip, e)| (*ip, e.mac.to_vec(), e.state))
+            .collect();
+        entries.sort_by_key(|e| e.0);
+        entries
+    }
+}
+
+// The solicited-node multicast address that a solicitation for the address is sent to
+pub fn solicited_node(addr: &Ipv6Addr) -> Ipv6Addr {
+    let o = addr.octets();
+    Ipv6Addr::from([
+        0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0xff, o[13], o[14], o[15],
+    ])
+}
+
+// The mac address for an IPv6 multicast address is 33:33 followed by the last four bytes of
+// the address (RFC 2464), None if the address is not a multicast address
+pub fn multicast_mac6(addr: &Ipv6Addr) -> Option<[u8; ETH_ALEN]> {
+    if !addr.is_multicast() {
+        return None;
+    }
+    let o = addr.octets();
+    Some([0x33, 0x33, o[12], o[13], o[14], o[15]])
+}
+
+// A neighbor solicitation or advertisement to build, the link layer address option carries
+// smac - as the source address in a solicitation and the target address in an advertisement
+pub struct NdPkt<'a> {
+    pub icmp_type: u8,
+    pub flags: u8,
+    pub dmac: &'a [u8],
+    pub smac: &'a [u8],
+    pub saddr: Ipv6Addr,
+    pub daddr: Ipv6Addr,
+    pub target: Ipv6Addr,
+}
+
+// Write the ethernet frame for the ND message into raw and return the length of the frame
+pub fn nd_build(raw: &mut [u8], nd: &NdPkt) -> usize {
+    let plen = ND_HDR_LEN + ND_OPT_UNIT;
+    raw[0..ETH_ALEN].copy_from_slice(&nd.dmac[0..ETH_ALEN]);
+    raw[ETH_ALEN..2 * ETH_ALEN].copy_from_slice(&nd.smac[0..ETH_ALEN]);
+    raw[2 * ETH_ALEN..ETHER_HDR_LEN].copy_from_slice(&ETH_TYPE_IPV6.to_be_bytes());
+
+    let ip = &mut raw[ETHER_HDR_LEN..ETHER_HDR_LEN + IP6HDR_LEN + plen];
+    ip[0..IP6HDR_PLEN_OFF].copy_from_slice(&[0x60, 0, 0, 0]);
+    ip[IP6HDR_PLEN_OFF..IP6HDR_PLEN_OFF + 2].copy_from_slice(&(plen as u16).to_be_bytes());
+    ip[IP6HDR_NEXTHDR_OFF] = IP_PROTO_ICMP6;
+    ip[IP6HDR_HLIM_OFF] = ND_HOP_LIMIT;
+    ip[IP6HDR_SADDR_OFF..IP6HDR_SADDR_OFF + 16].copy_from_slice(&nd.saddr.octets());
+    ip[IP6HDR_DADDR_OFF..IP6HDR_DADDR_OFF + 16].copy_from_slice(&nd.daddr.octets());
+
+    let icmp = &mut ip[IP6HDR_LEN..];
+    icmp[0] = nd.icmp_type;
+    icmp[ND_CODE_OFF] = 0;
+    icmp[ND_CSUM_OFF..ND_CSUM_OFF + 2].copy_from_slice(&[0, 0]);
+    icmp[ND_FLAGS_OFF..ND_TARGET_OFF].copy_from_slice(&[nd.flags, 0, 0, 0]);
+    icmp[ND_TARGET_OFF..ND_HDR_LEN].copy_from_slice(&nd.target.octets());
+    let opt = if nd.icmp_type == ICMP6_NEIGH_SOLICIT {
+        ND_OPT_SRC_LLADDR
+    } else {
+        ND_OPT_TGT_LLADDR
+    };
+    icmp[ND_HDR_LEN] = opt;
+    icmp[ND_HDR_LEN + 1] = 1;
+    icmp[ND_HDR_LEN + 2..ND_HDR_LEN + ND_OPT_UNIT].copy_from_slice(&nd.smac[0..ETH_ALEN]);
+    let csum = icmp6_checksum(&nd.saddr, &nd.daddr, icmp);
+    icmp[ND_CSUM_OFF..ND_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+    ETHER_HDR_LEN + IP6HDR_LEN + plen
+}
+
+// Find the link layer address option of the given type among the options of an ND message,
+// None if it is not there or if the options are malformed
+pub fn nd_lladdr_opt(opts: &[u8], opt: u8) -> Option<&[u8]> {
+    let mut opts = opts;
+    while opts.len() >= 2 {
+        let len = opts[1] as usize * ND_OPT_UNIT;
+        if len == 0 || len > opts.len() {
+            return None;
+        }
+        if opts[0] == opt && len >= 2 + ETH_ALEN {
+            return Some(&opts[2..2 + ETH_ALEN]);
+        }
+        opts = &opts[len..];
+    }
+    None
+}
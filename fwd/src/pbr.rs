@@ -0,0 +1,80 @@
+use super::*;
+
+// The new set of policy based routing rules
+pub struct PbrRulesMsg {
+    pub rules: Arc<Vec<PbrRule>>,
+}
+
+impl PbrRulesMsg {
+    pub fn new(rules: Arc<Vec<PbrRule>>) -> PbrRulesMsg {
+        PbrRulesMsg { rules }
+    }
+}
+
+impl Clone for PbrRulesMsg {
+    fn clone(&self) -> PbrRulesMsg {
+        PbrRulesMsg {
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+// The fields of a packet that the policy based routing rules match on. The ports are
+// None for packets other than TCP/UDP, and for all but the first fragment of a packet
+pub struct PbrKey {
+    pub saddr: Ipv4Addr,
+    pub daddr: Ipv4Addr,
+    pub proto: u8,
+    pub sport: Option<u16>,
+    pub dport: Option<u16>,
+    pub dscp: u8,
+    pub ifindex: usize,
+}
+
+// A policy based routing rule, the match fields that are None (or a zero mask length for
+// the prefixes) match any packet. A packet that matches the rule is routed in the route
+// table of the vrf, if there is one, and towards the nhop instead of its destination, if
+// there is one - at least one of the two is there
+#[derive(Clone, Debug, PartialEq)]
+pub struct PbrRule {
+    // The rules are tried in the order of their sequence numbers, the first match wins
+    pub seq: u32,
+    pub src: (Ipv4Addr, u32),
+    pub dst: (Ipv4Addr, u32),
+    pub proto: Option<u8>,
+    // Port ranges, both ends included
+    pub sport: Option<(u16, u16)>,
+    pub dport: Option<(u16, u16)>,
+    pub dscp: Option<u8>,
+    pub ifindex: Option<usize>,
+    pub vrf: Option<usize>,
+    pub nhop: Option<Ipv4Addr>,
+}
+
+fn prefix_match(addr: Ipv4Addr, prefix: (Ipv4Addr, u32)) -> bool {
+    let (prefix, masklen) = prefix;
+    if masklen == 0 {
+        return true;
+    }
+    (u32::from(addr) ^ u32::from(prefix)) >> (32 - masklen.min(32)) == 0
+}
+
+fn port_match(port: Option<u16>, range: Option<(u16, u16)>) -> bool {
+    match (port, range) {
+        (_, None) => true,
+        (Some(port), Some((lo, hi))) => port >= lo && port <= hi,
+        (None, Some(_)) => false,
+    }
+}
+
+impl PbrRule {
+    pub fn matches(&self, key: &PbrKey) -> bool {
+        prefix_match(key.saddr, self.src)
+            && prefix_match(key.daddr, self.dst)
+            && self.proto.is_none_or(|proto| proto == key.proto)
+            && port_match(key.sport, self.sport)
+            && port_match(key.dport, self.dport)
+            && self.dscp.is_none_or(|dscp| dscp == key.dscp)
+            && self.ifindex.is_none_or(|ifindex| ifindex == key.ifindex)
+    }
+}
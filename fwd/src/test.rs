@@ -0,0 +1,366 @@
+use super::*;
+
+// An IPv4 header from a real packet, with the checksum field at IPHDR_CSUM_OFF
+const IPHDR: [u8; IPHDR_MIN_LEN] = [
+    0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xb8, 0x61, 0xc0, 0xa8, 0x00, 0x01,
+    0xc0, 0xa8, 0x00, 0xc7,
+];
+
+#[test]
+fn checksum() {
+    let mut hdr = IPHDR;
+    assert_eq!(ip_checksum(&hdr), 0);
+    hdr[IPHDR_CSUM_OFF] = 0;
+    hdr[IPHDR_CSUM_OFF + 1] = 0;
+    assert_eq!(ip_checksum(&hdr), 0xb861);
+    // Odd length data is padded with a zero
+    assert_eq!(ip_checksum(&[0x01]), !0x0100);
+}
+
+#[test]
+fn checksum_update() {
+    // Decrement the TTL and check the incremental checksum against a full checksum
+    let mut hdr = IPHDR;
+    for _ in 0..IPHDR[IPHDR_TTL_OFF] {
+        let csum = u16::from_be_bytes([hdr[IPHDR_CSUM_OFF], hdr[IPHDR_CSUM_OFF + 1]]);
+        let old = u16::from_be_bytes([hdr[IPHDR_TTL_OFF], hdr[IPHDR_PROTO_OFF]]);
+        hdr[IPHDR_TTL_OFF] -= 1;
+        let new = u16::from_be_bytes([hdr[IPHDR_TTL_OFF], hdr[IPHDR_PROTO_OFF]]);
+        let csum = ip_checksum_update(csum, old, new);
+        hdr[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+        assert_eq!(ip_checksum(&hdr), 0);
+    }
+}
+
+#[test]
+fn mask_decode() {
+    let ip = Ipv4Addr::new(1, 1, 1, 0);
+    assert_eq!(ip_mask_decode("1.1.1.0/24"), Some((ip, 24)));
+    assert_eq!(ip_mask_decode("1.1.1.0"), None);
+    assert_eq!(ip_mask_decode("1.1.1.0/33"), None);
+    assert_eq!(ip_mask_decode("2001:db8::/32"), None);
+    let ip6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0);
+    assert_eq!(ip6_mask_decode("2001:db8::/32"), Some((ip6, 32)));
+    assert_eq!(ip6_mask_decode("2001:db8::/129"), None);
+    assert_eq!(ip6_mask_decode("1.1.1.0/24"), None);
+}
+
+#[test]
+fn nd_build_parse() {
+    let smac = [0x02, 0, 0, 0, 0, 1];
+    let saddr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+    let target = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0x1234, 0x5678);
+    let daddr = neigh::solicited_node(&target);
+    assert_eq!(daddr, Ipv6Addr::new(0xff02, 0, 0, 0, 0, 1, 0xff34, 0x5678));
+    let dmac = neigh::multicast_mac6(&daddr).unwrap();
+    assert_eq!(dmac, [0x33, 0x33, 0xff, 0x34, 0x56, 0x78]);
+    assert_eq!(neigh::multicast_mac6(&target), None);
+
+    let nd = neigh::NdPkt {
+        icmp_type: neigh::ICMP6_NEIGH_SOLICIT,
+        flags: 0,
+        dmac: &dmac,
+        smac: &smac,
+        saddr,
+        daddr,
+        target,
+    };
+    let mut raw = [0; 128];
+    let len = neigh::nd_build(&mut raw, &nd);
+    assert_eq!(len, ETHER_HDR_LEN + IP6HDR_LEN + 32);
+    assert_eq!(raw[0..ETH_ALEN], dmac);
+    let ip = &raw[ETHER_HDR_LEN..len];
+    assert_eq!(ip[IP6HDR_NEXTHDR_OFF], IP_PROTO_ICMP6);
+    assert_eq!(ipv6_addr(&ip[IP6HDR_DADDR_OFF..]), daddr);
+    let icmp = &ip[IP6HDR_LEN..];
+    // A correct checksum sums up to zero over the message including the checksum
+    assert_eq!(icmp6_checksum(&saddr, &daddr, icmp), 0);
+    assert_eq!(ipv6_addr(&icmp[neigh::ND_TARGET_OFF..]), target);
+    let opts = &icmp[neigh::ND_HDR_LEN..];
+    assert_eq!(
+        neigh::nd_lladdr_opt(opts, neigh::ND_OPT_SRC_LLADDR),
+        Some(&smac[..])
+    );
+    assert_eq!(neigh::nd_lladdr_opt(opts, neigh::ND_OPT_TGT_LLADDR), None);
+    // A zero length option is malformed
+    assert_eq!(neigh::nd_lladdr_opt(&[1, 0, 0, 0], 1), None);
+}
+
+#[test]
+fn neigh_states() {
+    use neigh::{NeighCache, NeighState, Resolve, RETRANS_MSECS};
+    let ip = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2));
+    let mac = [0x02, 0, 0, 0, 0, 2];
+    let mut cache = NeighCache::new();
+
+    // Solicit once, then wait for the retransmit time before soliciting again
+    let mut now = 1000;
+    assert_eq!(cache.resolve(ip, now), Resolve::Solicit);
+    assert_eq!(cache.resolve(ip, now + 1), Resolve::Wait);
+    now += RETRANS_MSECS;
+    assert_eq!(cache.resolve(ip, now), Resolve::Solicit);
+    assert_eq!(cache.dump()[0].2, NeighState::Incomplete);
+
+    // The advertisement comes back
+    cache.update(ip, &mac, NeighState::Reachable, now);
+    assert_eq!(cache.resolve(ip, now), Resolve::Mac(mac));
+
+    // Not confirmed for a while, the entry goes stale and is probed when used
+    now += neigh::REACHABLE_MSECS;
+    assert_eq!(cache.resolve(ip, now), Resolve::Mac(mac));
+    assert_eq!(cache.dump()[0].2, NeighState::Stale);
+    // Learning the same mac from a solicitation does not change a stale entry
+    cache.update(ip, &mac, NeighState::Stale, now);
+    assert_eq!(cache.resolve(ip, now), Resolve::Probe(mac));
+    assert_eq!(cache.resolve(ip, now + 1), Resolve::Mac(mac));
+    for _ in 1..neigh::MAX_UNICAST_SOLICIT {
+        now += RETRANS_MSECS;
+        assert_eq!(cache.resolve(ip, now), Resolve::Probe(mac));
+    }
+    assert_eq!(cache.dump()[0].2, NeighState::Probe);
+
+    // No answer to the probes, start over
+    now += RETRANS_MSECS;
+    assert_eq!(cache.resolve(ip, now), Resolve::Solicit);
+    assert_eq!(cache.resolve(ip, now), Resolve::Wait);
+
+    // A new mac learnt from a solicitation is used right away, and probed
+    let mac2 = [0x02, 0, 0, 0, 0, 3];
+    cache.update(ip, &mac2, NeighState::Stale, now);
+    assert_eq!(cache.resolve(ip, now), Resolve::Probe(mac2));
+}
+
+#[test]
+fn neigh_advert() {
+    use neigh::{NeighCache, NeighState, Resolve};
+    let ip = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2));
+    let mac = [0x02, 0, 0, 0, 0, 2];
+    let mut cache = NeighCache::new();
+
+    // An unsolicited advertisement does not create an entry
+    cache.advert(ip, &mac, NeighState::Stale, 0);
+    cache.advert(ip, &mac, NeighState::Reachable, 0);
+    assert!(cache.dump().is_empty());
+
+    // It completes an entry that is waiting for the mac
+    assert_eq!(cache.resolve(ip, 0), Resolve::Solicit);
+    cache.advert(ip, &mac, NeighState::Reachable, 1);
+    assert_eq!(cache.resolve(ip, 1), Resolve::Mac(mac));
+}
+
+#[test]
+fn neigh_cap() {
+    use neigh::{NeighCache, NeighState, Resolve, INCOMPLETE_MSECS, MAX_NEIGHS};
+    let ip = |i: usize| IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + i as u32));
+    let mac = [0x02, 0, 0, 0, 0, 2];
+    let mut cache = NeighCache::new();
+
+    // Fill the cache with reachable neighbors, and one incomplete one
+    for i in 0..MAX_NEIGHS - 1 {
+        cache.update(ip(i), &mac, NeighState::Reachable, 0);
+    }
+    assert_eq!(cache.resolve(ip(MAX_NEIGHS), 0), Resolve::Solicit);
+    assert_eq!(cache.dump().len(), MAX_NEIGHS);
+
+    // The incomplete entry is evicted to make room for the next one
+    assert_eq!(cache.resolve(ip(MAX_NEIGHS + 1), 1), Resolve::Solicit);
+    let dump = cache.dump();
+    assert_eq!(dump.len(), MAX_NEIGHS);
+    assert!(dump.iter().all(|e| e.0 != ip(MAX_NEIGHS)));
+
+    // Once the incomplete entry ages out, a new neighbor takes its place
+    let now = 1 + INCOMPLETE_MSECS;
+    cache.update(ip(MAX_NEIGHS + 2), &mac, NeighState::Reachable, now);
+    let dump = cache.dump();
+    assert_eq!(dump.len(), MAX_NEIGHS);
+    assert!(dump.iter().all(|e| e.0 != ip(MAX_NEIGHS + 1)));
+
+    // A cache full of neighbors in use has no room, the packet waits and nothing is added
+    assert_eq!(cache.resolve(ip(MAX_NEIGHS + 3), now), Resolve::Wait);
+    cache.update(ip(MAX_NEIGHS + 3), &mac, NeighState::Stale, now);
+    assert_eq!(cache.dump().len(), MAX_NEIGHS);
+    assert!(cache.dump().iter().all(|e| e.0 != ip(MAX_NEIGHS + 3)));
+}
+
+#[test]
+fn multipath() {
+    use ipv4::IPv4Table;
+    let ip = Ipv4Addr::new(10, 0, 0, 0);
+    let nhop1 = Ipv4Addr::new(1, 1, 1, 1);
+    let nhop2 = Ipv4Addr::new(2, 2, 2, 2);
+    let mut table = IPv4Table::new();
+
+    assert!(table.add_path(ip, 8, Arc::new(Adjacency::new(nhop1, 0))));
+    assert!(!table.add_path(ip, 8, Arc::new(Adjacency::new(nhop1, 0))));
+    // The same nexthop on another interface is another path
+    assert!(table.add_path(ip, 8, Arc::new(Adjacency::new(nhop1, 1))));
+    assert!(table.add_path(ip, 8, Arc::new(Adjacency::new(nhop2, 1))));
+    match &table.exact_match(ip, 8).unwrap().next {
+        Fwd::Multipath(mp) => {
+            assert_eq!(mp.paths.len(), 3);
+            // The paths are in the order they were added
+            assert_eq!(mp.path(4).nhop, nhop1);
+            assert_eq!(mp.path(4).ifindex, 1);
+        }
+        _ => panic!("not multipath"),
+    }
+
+    assert!(!table.del_path(ip, 8, nhop2, 0));
+    assert!(table.del_path(ip, 8, nhop1, 1));
+    assert!(table.del_path(ip, 8, nhop1, 0));
+    match &table.exact_match(ip, 8).unwrap().next {
+        Fwd::Adjacency(adj) => assert_eq!(adj.nhop, nhop2),
+        _ => panic!("not a single path"),
+    }
+    assert!(table.del_path(ip, 8, nhop2, 1));
+    assert!(table.exact_match(ip, 8).is_none());
+    assert!(!table.del_path(ip, 8, nhop2, 1));
+}
+
+#[test]
+fn recursive() {
+    use ipv4::IPv4Table;
+    fn resolved(table: &IPv4Table, ip: Ipv4Addr, masklen: u32) -> Vec<(Ipv4Addr, usize)> {
+        match table.exact_match(ip, masklen).map(|leaf| &leaf.next) {
+            Some(Fwd::IPv4Leaf(leaf)) => match &leaf.next {
+                Fwd::Adjacency(adj) => vec![(adj.nhop, adj.ifindex)],
+                Fwd::Multipath(mp) => mp.paths.iter().map(|a| (a.nhop, a.ifindex)).collect(),
+                _ => panic!("bad resolved leaf"),
+            },
+            Some(_) => panic!("not recursive"),
+            None => Vec::new(),
+        }
+    }
+    let subnet = Ipv4Addr::new(10, 0, 0, 0);
+    let nbr = Ipv4Addr::new(10, 0, 0, 5);
+    let loopback = Ipv4Addr::new(100, 0, 0, 0);
+    let remote = Ipv4Addr::new(200, 0, 0, 0);
+    let mut table = IPv4Table::new();
+
+    // Not resolved till there is a route to the nexthop
+    assert!(table.add_via(loopback, 8, nbr));
+    assert!(!table.add_via(loopback, 8, nbr));
+    assert!(table.add_via(remote, 8, Ipv4Addr::new(100, 1, 1, 1)));
+    table.resolve();
+    assert!(resolved(&table, loopback, 8).is_empty());
+    assert!(table.longest_match(Ipv4Addr::new(200, 1, 1, 1)).is_none());
+
+    // The nexthop is on a connected subnet, and the other route resolves through this one
+    table.add_path(subnet, 24, Arc::new(Adjacency::new(ZERO_IP, 1)));
+    table.resolve();
+    assert_eq!(resolved(&table, loopback, 8), vec![(nbr, 1)]);
+    assert_eq!(resolved(&table, remote, 8), vec![(nbr, 1)]);
+
+    // A more specific route to the nexthop with two paths
+    let nhop1 = Ipv4Addr::new(10, 0, 0, 9);
+    let nhop2 = Ipv4Addr::new(10, 0, 0, 10);
+    table.add_path(nbr, 32, Arc::new(Adjacency::new(nhop1, 1)));
+    table.add_path(nbr, 32, Arc::new(Adjacency::new(nhop2, 2)));
+    table.resolve();
+    assert_eq!(resolved(&table, loopback, 8), vec![(nhop1, 1), (nhop2, 2)]);
+    assert_eq!(resolved(&table, remote, 8), vec![(nhop1, 1), (nhop2, 2)]);
+
+    // A route does not resolve through itself, but through the less specific default route
+    let self_via = Ipv4Addr::new(50, 0, 0, 0);
+    table.add_via(self_via, 8, Ipv4Addr::new(50, 1, 1, 1));
+    table.resolve();
+    assert!(resolved(&table, self_via, 8).is_empty());
+    table.add_path(ZERO_IP, 0, Arc::new(Adjacency::new(nhop1, 1)));
+    table.resolve();
+    assert_eq!(resolved(&table, self_via, 8), vec![(nhop1, 1)]);
+    table.resolve();
+    assert_eq!(resolved(&table, self_via, 8), vec![(nhop1, 1)]);
+    table.del_path(ZERO_IP, 0, nhop1, 1);
+
+    // The routes to the nexthop go away
+    table.del_path(nbr, 32, nhop1, 1);
+    table.del_path(nbr, 32, nhop2, 2);
+    table.del_path(subnet, 24, ZERO_IP, 1);
+    table.resolve();
+    assert!(resolved(&table, loopback, 8).is_empty());
+    assert!(resolved(&table, remote, 8).is_empty());
+    assert_eq!(table.recursive().count(), 3);
+
+    assert!(!table.del_via(loopback, 8, nhop1));
+    assert!(table.del_via(loopback, 8, nbr));
+    assert_eq!(table.recursive().count(), 2);
+}
+
+#[test]
+fn pbr_match() {
+    use pbr::{PbrKey, PbrRule};
+    let any = PbrRule {
+        seq: 10,
+        src: (ZERO_IP, 0),
+        dst: (ZERO_IP, 0),
+        proto: None,
+        sport: None,
+        dport: None,
+        dscp: None,
+        ifindex: None,
+        vrf: Some(1),
+        nhop: None,
+    };
+    let mut key = PbrKey {
+        saddr: Ipv4Addr::new(192, 168, 1, 10),
+        daddr: Ipv4Addr::new(8, 8, 8, 8),
+        proto: IP_PROTO_UDP,
+        sport: Some(1024),
+        dport: Some(53),
+        dscp: 46,
+        ifindex: 2,
+    };
+    assert!(any.matches(&key));
+
+    let rule = PbrRule {
+        src: (Ipv4Addr::new(192, 168, 1, 0), 24),
+        proto: Some(IP_PROTO_UDP),
+        dport: Some((53, 53)),
+        dscp: Some(46),
+        ifindex: Some(2),
+        ..any.clone()
+    };
+    assert!(rule.matches(&key));
+    key.saddr = Ipv4Addr::new(192, 168, 2, 10);
+    assert!(!rule.matches(&key));
+    key.saddr = Ipv4Addr::new(192, 168, 1, 10);
+    key.ifindex = 3;
+    assert!(!rule.matches(&key));
+    key.ifindex = 2;
+    key.dscp = 0;
+    assert!(!rule.matches(&key));
+    key.dscp = 46;
+    // A packet without ports (a non first fragment) does not match a rule with ports
+    key.dport = None;
+    assert!(!rule.matches(&key));
+    key.dport = Some(53);
+
+    // Port ranges include both ends, a /32 is an exact match
+    let rule = PbrRule {
+        dst: (Ipv4Addr::new(8, 8, 8, 8), 32),
+        sport: Some((1000, 1024)),
+        ..any.clone()
+    };
+    assert!(rule.matches(&key));
+    key.sport = Some(1025);
+    assert!(!rule.matches(&key));
+    key.sport = Some(1000);
+    key.daddr = Ipv4Addr::new(8, 8, 8, 9);
+    assert!(!rule.matches(&key));
+}
+
+#[test]
+fn icmp_query() {
+    assert!(icmp_is_query(ICMP_ECHO_REQUEST));
+    assert!(icmp_is_query(ICMP_ECHO_REPLY));
+    // Timestamp request
+    assert!(icmp_is_query(13));
+    assert!(!icmp_is_query(ICMP_DEST_UNREACH));
+    assert!(!icmp_is_query(ICMP_TIME_EXCEEDED));
+    // Source quench, redirect and parameter problem
+    assert!(!icmp_is_query(4));
+    assert!(!icmp_is_query(5));
+    assert!(!icmp_is_query(12));
+    // Unknown types are taken to be errors
+    assert!(!icmp_is_query(200));
+}
@@ -6,7 +6,7 @@ use fwd::intf::Interface;
 use graph::Driver;
 use graph::{Dispatch, Gclient, VEC_SIZE};
 use log::Logger;
-use msg::R2Msg;
+use msg::{NodeQuery, NodeReply, R2Msg};
 use names::l2_eth_decap;
 use packet::BoxPkt;
 use sched::hfsc::Hfsc;
@@ -117,6 +117,15 @@ impl Gclient<R2Msg> for IfNode {
         })
     }
 
+    fn free(&self, counters: &mut Counters) {
+        self.sched_fail.free(counters);
+        self.threadq_fail.free(counters);
+        // Only the owner IfNode has the driver
+        if let Some(ref driver) = self.driver {
+            driver.free(counters);
+        }
+    }
+
     fn dispatch(&mut self, thread: usize, vectors: &mut Dispatch) {
         let owner_thread = self.affinity.is_none() || (self.affinity == Some(thread));
         // Do packet Tx if we are the owner thread (thread the driver/device is pinnned to).
@@ -124,15 +133,19 @@ impl Gclient<R2Msg> for IfNode {
         // queue to the owner thread
         while let Some(p) = vectors.pop() {
             if owner_thread {
+                vectors.trace(&p, || format!("tx {} bytes", p.len()));
                 // TODO: We have the scheduler, but we havent figured out the packet queueing
                 // model. Till then we cant really put the scheduler to use
                 if !self.sched.has_classes() {
                     self.driver.as_mut().unwrap().sendmsg(vectors.pool, p);
                 }
-            } else if self.thread_q.push(p).is_err() {
-                self.threadq_fail.incr();
             } else {
-                self.thread_wakeup.write(1);
+                vectors.trace(&p, || "handoff to owner thread".to_string());
+                if self.thread_q.push(p).is_err() {
+                    self.threadq_fail.incr();
+                } else {
+                    self.thread_wakeup.write(1);
+                }
             }
         }
         if owner_thread {
@@ -142,10 +155,10 @@ impl Gclient<R2Msg> for IfNode {
                 }
             }
         }
-        if self.sched.pkts_queued() != 0 {
-            // Well, we are not caring to return the exact scheduler time at the moment, but
-            // its a TODO to return here the smallest scheduler interval rather than 0
-            vectors.wakeup(0);
+        // The scheduler might have packets that can go out only at a later time (shaping),
+        // in which case ask to be scheduled at that time
+        if let Some(nsecs) = self.sched.wakeup() {
+            vectors.wakeup(nsecs as usize);
         }
         // Do packet Rx, only on the thread this driver is pinned to
         if owner_thread {
@@ -163,11 +176,13 @@ impl Gclient<R2Msg> for IfNode {
                     break;
                 }
                 pkt.in_ifindex = self.intf.ifindex;
+                vectors.trace_start(&mut pkt);
+                vectors.trace(&pkt, || format!("rx {} bytes", pkt.len()));
                 vectors.push(Next::L2EthDecap as usize, pkt);
             }
         }
     }
-    fn control_msg(&mut self, thread: usize, message: R2Msg) {
+    fn control_msg(&mut self, thread: usize, message: R2Msg) -> bool {
         match message {
             R2Msg::ModifyInterface(mod_intf) => {
                 self.intf = mod_intf.intf;
@@ -188,7 +203,20 @@ impl Gclient<R2Msg> for IfNode {
                     self.sched_fail.incr();
                 }
             }
-            _ => panic!("Unknown type"),
+            // Only the owner thread has the scheduler with the classes and the packets
+            R2Msg::NodeQuery(query)
+                if query.query == NodeQuery::Classes
+                    && (self.affinity.is_none() || self.affinity == Some(thread)) =>
+            {
+                let stats = self.sched.class_stats();
+                // The requester might have given up waiting, nothing to do if so
+                let _ = query.reply.send((thread, NodeReply::Classes(stats)));
+            }
+            _ => return false,
         }
+        true
     }
 }
+
+#[cfg(test)]
+mod test;
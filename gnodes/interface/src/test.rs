@@ -0,0 +1,46 @@
+use super::*;
+use packet::PacketPool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// A driver that just counts how many times it was asked to release its resources
+struct FreeDriver {
+    freed: Arc<AtomicUsize>,
+}
+
+impl Driver for FreeDriver {
+    fn fd(&self) -> Option<i32> {
+        None
+    }
+
+    fn sendmsg(&mut self, _pool: &mut dyn PacketPool, _pkt: BoxPkt) -> usize {
+        0
+    }
+
+    fn recvmsg(&mut self, _pool: &mut dyn PacketPool, _headroom: usize) -> Option<BoxPkt> {
+        None
+    }
+
+    fn free(&self, _counters: &mut Counters) {
+        self.freed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn free_driver() {
+    let mut counters = Counters::new("ifnode_free_driver").unwrap();
+    let freed = Arc::new(AtomicUsize::new(0));
+    let driver = Box::new(FreeDriver {
+        freed: freed.clone(),
+    });
+    let intf = Arc::new(Interface::new("eth_free", 1, vec![0; 6], 0));
+    let efd = Arc::new(Efd::new(0).unwrap());
+    let node = IfNode::new(&mut counters, Some(0), efd, intf, driver).unwrap();
+    let log = Arc::new(Logger::new("ifnode_free_driver_log", 32, 1000).unwrap());
+
+    // The clones dont own the driver, only the owner IfNode releases it
+    let clone = node.clone(&mut counters, log);
+    clone.free(&mut counters);
+    assert_eq!(freed.load(Ordering::Relaxed), 0);
+    node.free(&mut counters);
+    assert_eq!(freed.load(Ordering::Relaxed), 1);
+}
@@ -1,11 +1,18 @@
 use counters::flavors::{Counter, CounterType};
 use counters::Counters;
 use fwd::intf::Interface;
-use fwd::EthMacAddMsg;
+use fwd::neigh::{multicast_mac6, nd_build, nd_lladdr_opt, NdPkt, NeighState, ALL_NODES_IP6};
+use fwd::neigh::{ICMP6_NEIGH_ADVERT, ICMP6_NEIGH_SOLICIT, ICMP6_ROUTER_ADVERT};
+use fwd::neigh::{ICMP6_ROUTER_SOLICIT, ND_CODE_OFF, ND_FLAGS_OFF, ND_HDR_LEN, ND_HOP_LIMIT};
+use fwd::neigh::{ND_NA_OVERRIDE, ND_NA_ROUTER, ND_NA_SOLICITED, ND_OPT_SRC_LLADDR};
+use fwd::neigh::{ND_OPT_TGT_LLADDR, ND_TARGET_OFF};
+use fwd::{icmp6_checksum, ipv6_addr, EthMacAddMsg};
 use fwd::{
     EthMacRaw, EthOffsets, ARP_HWTYPE_ETH, ARP_OPCODE_REPLY, ARP_OPCODE_REQ, ETHER_HDR_LEN,
-    ETH_ALEN, ETH_TYPE_ARP, ETH_TYPE_IPV4,
+    ETH_ALEN, ETH_TYPE_ARP, ETH_TYPE_IPV4, ETH_TYPE_IPV6,
 };
+use fwd::{IP6HDR_DADDR_OFF, IP6HDR_HLIM_OFF, IP6HDR_LEN, IP6HDR_NEXTHDR_OFF, IP6HDR_PLEN_OFF};
+use fwd::{IP6HDR_SADDR_OFF, IP_PROTO_ICMP6, ZERO_IP6};
 use graph::Dispatch;
 use graph::Gclient;
 use log::Logger;
@@ -15,7 +22,7 @@ use names::l2_eth_decap;
 use packet::BoxPkt;
 use packet::PacketPool;
 use std::collections::HashMap;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 
@@ -24,15 +31,17 @@ enum Next {
     Drop = 0,
     L3Ipv4Parse,
     TX,
+    L3Ipv6Parse,
 }
 
-const NEXT_NAMES: &[Next] = &[Next::Drop, Next::L3Ipv4Parse, Next::TX];
+const NEXT_NAMES: &[Next] = &[Next::Drop, Next::L3Ipv4Parse, Next::TX, Next::L3Ipv6Parse];
 
 fn next_name(ifindex: usize, next: Next) -> String {
     match next {
         Next::Drop => names::DROP.to_string(),
         Next::L3Ipv4Parse => names::L3_IPV4_PARSE.to_string(),
         Next::TX => names::rx_tx(ifindex),
+        Next::L3Ipv6Parse => names::L3_IPV6_PARSE.to_string(),
     }
 }
 
@@ -42,6 +51,35 @@ struct Cnt {
     not_my_mac: Counter,
     bad_mac: Counter,
     mac_send_fail: Counter,
+    bad_nd: Counter,
+    unknown_nd: Counter,
+    router_nd: Counter,
+}
+
+impl Cnt {
+    fn new(counters: &mut Counters, name: &str) -> Cnt {
+        Cnt {
+            unknown_ethtype: Counter::new(counters, name, CounterType::Error, "unknown_ethtype"),
+            unknown_arp: Counter::new(counters, name, CounterType::Error, "unknown_arp"),
+            not_my_mac: Counter::new(counters, name, CounterType::Error, "not_my_mac"),
+            bad_mac: Counter::new(counters, name, CounterType::Error, "bad_mac"),
+            mac_send_fail: Counter::new(counters, name, CounterType::Error, "mac_send_fail"),
+            bad_nd: Counter::new(counters, name, CounterType::Error, "bad_nd"),
+            unknown_nd: Counter::new(counters, name, CounterType::Error, "unknown_nd"),
+            router_nd: Counter::new(counters, name, CounterType::Pkts, "router_nd"),
+        }
+    }
+
+    fn free(&self, counters: &mut Counters) {
+        self.unknown_ethtype.free(counters);
+        self.unknown_arp.free(counters);
+        self.not_my_mac.free(counters);
+        self.bad_mac.free(counters);
+        self.mac_send_fail.free(counters);
+        self.bad_nd.free(counters);
+        self.unknown_nd.free(counters);
+        self.router_nd.free(counters);
+    }
 }
 
 // The decap node gets a packet from IfNode and removes the layer2 header and forwards
@@ -56,56 +94,28 @@ struct Cnt {
 // plane thread for example for display. The whole mac address learning business will
 // need to be thought of more carefully in time (github issue #4). Also today we just
 // support plain ethernet packets without vlan tags.
+//
+// IPv6 neighbor discovery is handled here the same way as ARP: a neighbor solicitation for
+// the address of the interface gets an advertisement back, and the macs in solicitations and
+// advertisements are learnt. A learnt mac is broadcast again only if it changed or if the
+// neighbor confirmed it (an ARP reply or a solicited advertisement), so that EthEncap can keep
+// the neighbor reachable. R2 does not send router advertisements, and router solicitations
+// and advertisements from others are counted and dropped
 pub struct EthDecap {
     intf: Arc<Interface>,
-    mac: HashMap<Ipv4Addr, EthMacRaw>,
+    mac: HashMap<IpAddr, EthMacRaw>,
     sender: Sender<R2Msg>,
     cnt: Cnt,
 }
 
 impl EthDecap {
     pub fn new(intf: Arc<Interface>, counters: &mut Counters, sender: Sender<R2Msg>) -> Self {
-        let unknown_ethtype = Counter::new(
-            counters,
-            &l2_eth_decap(intf.ifindex),
-            CounterType::Error,
-            "unknown_ethtype",
-        );
-        let unknown_arp = Counter::new(
-            counters,
-            &l2_eth_decap(intf.ifindex),
-            CounterType::Error,
-            "unknown_arp",
-        );
-        let not_my_mac = Counter::new(
-            counters,
-            &l2_eth_decap(intf.ifindex),
-            CounterType::Error,
-            "not_my_mac",
-        );
-        let bad_mac = Counter::new(
-            counters,
-            &l2_eth_decap(intf.ifindex),
-            CounterType::Error,
-            "bad_mac",
-        );
-        let mac_send_fail = Counter::new(
-            counters,
-            &l2_eth_decap(intf.ifindex),
-            CounterType::Error,
-            "mac_send_fail",
-        );
+        let cnt = Cnt::new(counters, &l2_eth_decap(intf.ifindex));
         EthDecap {
             intf,
             mac: HashMap::new(),
             sender,
-            cnt: Cnt {
-                unknown_ethtype,
-                unknown_arp,
-                not_my_mac,
-                bad_mac,
-                mac_send_fail,
-            },
+            cnt,
         }
     }
 
@@ -192,29 +202,37 @@ impl EthDecap {
         }
     }
 
-    fn mac_learn(&mut self, ip: Ipv4Addr, mac: &[u8]) {
-        if let std::collections::hash_map::Entry::Vacant(e) = self.mac.entry(ip) {
-            let mut bytes = Vec::new();
-            bytes.extend(mac);
-            let raw = EthMacRaw {
-                bytes: Arc::new(bytes),
-            };
-            e.insert(EthMacRaw {
-                bytes: raw.bytes.clone(),
-            });
-            if self
-                .sender
-                .send(EthMacAdd(EthMacAddMsg {
-                    ifindex: self.intf.ifindex,
-                    ip,
-                    mac: raw,
-                }))
-                .is_err()
-            {
-                self.cnt.mac_send_fail.incr();
-            }
-        } else {
-            // do nothing
+    // A mac from a neighbor advertisement (advert) only updates the entry the encap nodes
+    // have for the neighbor, if they have one. So it is not remembered here as sent either,
+    // or a solicitation with the same mac later would not create the entry
+    fn mac_learn(&mut self, ip: IpAddr, mac: &[u8], state: NeighState, advert: bool) {
+        let known = self.mac.get(&ip).is_some_and(|m| m.bytes[..] == *mac);
+        if known && state != NeighState::Reachable {
+            return;
+        }
+        let raw = EthMacRaw {
+            bytes: Arc::new(mac.to_vec()),
+        };
+        if !advert {
+            self.mac.insert(
+                ip,
+                EthMacRaw {
+                    bytes: raw.bytes.clone(),
+                },
+            );
+        }
+        if self
+            .sender
+            .send(EthMacAdd(EthMacAddMsg {
+                ifindex: self.intf.ifindex,
+                ip,
+                mac: raw,
+                state,
+                advert,
+            }))
+            .is_err()
+        {
+            self.cnt.mac_send_fail.incr();
         }
     }
 
@@ -235,7 +253,7 @@ impl EthDecap {
         let src_ip = Ipv4Addr::new(mac[off], mac[off + 1], mac[off + 2], mac[off + 3]);
         let off = EthOffsets::EthSenderMacOff as usize;
         let src_mac = &mac[off..off + ETH_ALEN];
-        self.mac_learn(src_ip, src_mac);
+        self.mac_learn(IpAddr::V4(src_ip), src_mac, NeighState::Stale, false);
         self.do_arp_reply(pool, src_ip, src_mac)
     }
 
@@ -256,45 +274,144 @@ impl EthDecap {
         let src_ip = Ipv4Addr::new(mac[off], mac[off + 1], mac[off + 2], mac[off + 3]);
         let off = EthOffsets::EthSenderMacOff as usize;
         let src_mac = &mac[off..off + ETH_ALEN];
-        self.mac_learn(src_ip, src_mac);
+        self.mac_learn(IpAddr::V4(src_ip), src_mac, NeighState::Reachable, false);
     }
 
-    pub fn mac_add(&mut self, ip: Ipv4Addr, mac: EthMacRaw) {
+    fn do_na(
+        &self,
+        pool: &mut dyn PacketPool,
+        daddr: Ipv6Addr,
+        dmac: &[u8],
+        flags: u8,
+    ) -> Option<BoxPkt> {
+        let mut pkt = pool.pkt(0 /* no headroom */)?;
+        let nd = NdPkt {
+            icmp_type: ICMP6_NEIGH_ADVERT,
+            flags,
+            dmac,
+            smac: &self.intf.l2_addr,
+            saddr: self.intf.ipv6_addr,
+            daddr,
+            target: self.intf.ipv6_addr,
+        };
+        let bytes = nd_build(pkt.head_mut(), &nd);
+        pkt.move_tail(bytes as isize);
+        pkt.out_ifindex = self.intf.ifindex;
+        Some(pkt)
+    }
+
+    // frame is the ethernet frame with an ICMPv6 ND message, the caller has checked that
+    // the frame is long enough to have the ICMPv6 type
+    fn process_nd(&mut self, pool: &mut dyn PacketPool, frame: &[u8]) -> Option<BoxPkt> {
+        let ip = &frame[ETHER_HDR_LEN..];
+        let icmp_type = ip[IP6HDR_LEN];
+        if icmp_type == ICMP6_ROUTER_SOLICIT || icmp_type == ICMP6_ROUTER_ADVERT {
+            self.cnt.router_nd.incr();
+            return None;
+        }
+        let plen = u16::from_be_bytes([ip[IP6HDR_PLEN_OFF], ip[IP6HDR_PLEN_OFF + 1]]) as usize;
+        if plen < ND_HDR_LEN || ip.len() < IP6HDR_LEN + plen {
+            self.cnt.bad_nd.incr();
+            return None;
+        }
+        let saddr = ipv6_addr(&ip[IP6HDR_SADDR_OFF..]);
+        let daddr = ipv6_addr(&ip[IP6HDR_DADDR_OFF..]);
+        let icmp = &ip[IP6HDR_LEN..IP6HDR_LEN + plen];
+        if ip[IP6HDR_HLIM_OFF] != ND_HOP_LIMIT
+            || icmp[ND_CODE_OFF] != 0
+            || icmp6_checksum(&saddr, &daddr, icmp) != 0
+        {
+            self.cnt.bad_nd.incr();
+            return None;
+        }
+        let target = ipv6_addr(&icmp[ND_TARGET_OFF..]);
+        let opts = &icmp[ND_HDR_LEN..];
+        if icmp_type == ICMP6_NEIGH_SOLICIT {
+            let off = EthOffsets::EthSaddrOff as usize;
+            let src_mac = &frame[off..off + ETH_ALEN];
+            self.process_ns(pool, saddr, src_mac, target, opts)
+        } else {
+            self.process_na(icmp[ND_FLAGS_OFF], target, opts);
+            None
+        }
+    }
+
+    fn process_ns(
+        &mut self,
+        pool: &mut dyn PacketPool,
+        saddr: Ipv6Addr,
+        src_mac: &[u8],
+        target: Ipv6Addr,
+        opts: &[u8],
+    ) -> Option<BoxPkt> {
+        if self.intf.ipv6_addr == ZERO_IP6 || target != self.intf.ipv6_addr {
+            self.cnt.unknown_nd.incr();
+            return None;
+        }
+        if saddr == ZERO_IP6 {
+            // The sender is checking if the address is taken (duplicate address detection),
+            // the answer goes to all nodes
+            let dmac = multicast_mac6(&ALL_NODES_IP6).unwrap();
+            let flags = ND_NA_ROUTER | ND_NA_OVERRIDE;
+            return self.do_na(pool, ALL_NODES_IP6, &dmac, flags);
+        }
+        let dmac = match nd_lladdr_opt(opts, ND_OPT_SRC_LLADDR) {
+            Some(lladdr) => {
+                self.mac_learn(IpAddr::V6(saddr), lladdr, NeighState::Stale, false);
+                lladdr
+            }
+            None => src_mac,
+        };
+        let flags = ND_NA_ROUTER | ND_NA_SOLICITED | ND_NA_OVERRIDE;
+        self.do_na(pool, saddr, dmac, flags)
+    }
+
+    fn process_na(&mut self, flags: u8, target: Ipv6Addr, opts: &[u8]) {
+        if let Some(lladdr) = nd_lladdr_opt(opts, ND_OPT_TGT_LLADDR) {
+            let state = if flags & ND_NA_SOLICITED != 0 {
+                NeighState::Reachable
+            } else {
+                NeighState::Stale
+            };
+            self.mac_learn(IpAddr::V6(target), lladdr, state, true);
+        } else {
+            self.cnt.unknown_nd.incr();
+        }
+    }
+
+    pub fn mac_add(&mut self, ip: IpAddr, mac: EthMacRaw) {
         if mac.bytes.len() < ETH_ALEN {
             self.cnt.bad_mac.incr();
             return;
         }
-        self.mac.entry(ip).or_insert(mac);
+        self.mac.insert(ip, mac);
     }
 }
 
+// Is the ethernet frame an ICMPv6 neighbor discovery message
+fn is_nd(frame: &[u8]) -> bool {
+    let ip = &frame[ETHER_HDR_LEN..];
+    if ip.len() <= IP6HDR_LEN || ip[IP6HDR_NEXTHDR_OFF] != IP_PROTO_ICMP6 {
+        return false;
+    }
+    let icmp_type = ip[IP6HDR_LEN];
+    (ICMP6_ROUTER_SOLICIT..=ICMP6_NEIGH_ADVERT).contains(&icmp_type)
+}
+
 impl Gclient<R2Msg> for EthDecap {
     fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
-        let unknown_ethtype = Counter::new(
-            counters,
-            &self.name(),
-            CounterType::Error,
-            "unknown_ethtype",
-        );
-        let unknown_arp = Counter::new(counters, &self.name(), CounterType::Error, "unknown_arp");
-        let not_my_mac = Counter::new(counters, &self.name(), CounterType::Error, "not_my_mac");
-        let bad_mac = Counter::new(counters, &self.name(), CounterType::Error, "bad_mac");
-        let mac_send_fail =
-            Counter::new(counters, &self.name(), CounterType::Error, "mac_send_fail");
         Box::new(EthDecap {
             intf: self.intf.clone(),
             mac: HashMap::new(),
             sender: self.sender.clone(),
-            cnt: Cnt {
-                unknown_ethtype,
-                unknown_arp,
-                not_my_mac,
-                bad_mac,
-                mac_send_fail,
-            },
+            cnt: Cnt::new(counters, &self.name()),
         })
     }
 
+    fn free(&self, counters: &mut Counters) {
+        self.cnt.free(counters);
+    }
+
     fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
         while let Some(mut p) = vectors.pop() {
             assert_eq!(p.pull_l2(ETHER_HDR_LEN), ETHER_HDR_LEN);
@@ -302,19 +419,32 @@ impl Gclient<R2Msg> for EthDecap {
 
             let off = EthOffsets::EthTypeOff as usize;
             let ethtype = u16::from_be_bytes([mac[off], mac[off + 1]]);
+            vectors.trace(&p, || format!("ethertype 0x{:04x}", ethtype));
+            // The frame upto the end of the first particle, which is all of it for the
+            // small packets like ARP and ND that are processed right here
+            let frame = &mac[0..mac.len().min(ETHER_HDR_LEN + p.len())];
             if ethtype == ETH_TYPE_ARP {
                 if let Some(arp) = self.process_arp(vectors.pool, mac, len) {
                     vectors.push(Next::TX as usize, arp);
                 }
+            } else if ethtype == ETH_TYPE_IPV6 && is_nd(frame) {
+                // ND messages come to multicast macs, so this is before the check for our mac
+                vectors.trace(&p, || "neighbor discovery".to_string());
+                if let Some(na) = self.process_nd(vectors.pool, frame) {
+                    vectors.push(Next::TX as usize, na);
+                }
             } else {
                 let off = EthOffsets::EthDaddrOff as usize;
                 if self.intf.l2_addr[0..ETH_ALEN] != mac[off..off + ETH_ALEN] {
+                    vectors.trace(&p, || "not my mac".to_string());
                     self.cnt.not_my_mac.incr();
                     continue;
                 }
 
                 if ethtype == ETH_TYPE_IPV4 {
                     vectors.push(Next::L3Ipv4Parse as usize, p);
+                } else if ethtype == ETH_TYPE_IPV6 {
+                    vectors.push(Next::L3Ipv6Parse as usize, p);
                 } else {
                     self.cnt.unknown_ethtype.incr();
                 }
@@ -322,7 +452,7 @@ impl Gclient<R2Msg> for EthDecap {
         }
     }
 
-    fn control_msg(&mut self, _thread: usize, message: R2Msg) {
+    fn control_msg(&mut self, _thread: usize, message: R2Msg) -> bool {
         match message {
             R2Msg::ModifyInterface(mod_intf) => {
                 self.intf = mod_intf.intf;
@@ -330,7 +460,8 @@ impl Gclient<R2Msg> for EthDecap {
             R2Msg::EthMacAdd(mac_add) => {
                 self.mac_add(mac_add.ip, mac_add.mac);
             }
-            _ => panic!("Unknown type"),
+            _ => return false,
         }
+        true
     }
 }
@@ -1,37 +1,47 @@
+use common::time_msecs;
 use counters::flavors::{Counter, CounterType};
 use counters::Counters;
-use fwd::intf::MAX_INTERFACES;
+use fwd::neigh::{multicast_mac6, nd_build, solicited_node, NdPkt, NeighCache, NeighState};
+use fwd::neigh::{Resolve, ICMP6_NEIGH_SOLICIT};
 use fwd::IPHDR_DADDR_OFF;
 use fwd::IPHDR_MIN_LEN;
 use fwd::{
     intf::Interface, EthMacRaw, EthOffsets, ARP_HWTYPE_ETH, ARP_OPCODE_REQ, BCAST_MAC, ETH_ALEN,
-    ETH_TYPE_ARP, ETH_TYPE_IPV4, ZERO_IP, ZERO_MAC,
+    ETH_TYPE_ARP, ETH_TYPE_IPV4, ETH_TYPE_IPV6, ZERO_IP, ZERO_IP6, ZERO_MAC,
 };
 use graph::Dispatch;
 use graph::Gclient;
+use graph::BURST_SIZE;
 use log::Logger;
-use msg::R2Msg;
+use msg::{NodeQuery, NodeReply, R2Msg};
 use names::l2_eth_encap;
 use packet::BoxPkt;
 use packet::PacketPool;
-use std::collections::HashMap;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv6Addr};
 use std::sync::Arc;
 
 // If the system has say 4000 interfaces, EncapMux prevents having every single node that needs
 // to send a packet out to have 4000 nodes as their next-node. Instead those nodes have EncapMux
 // as their next node and EncapMux will have 4000 next nodes. So all that EncapMux does is to
 // take the input packet and enqueu it to the right EthEncap node. This convenience of course
-// comes with the hit that all output packets incur one unnecessary dequeue/enqueue
+// comes with the hit that all output packets incur one unnecessary dequeue/enqueue - to keep
+// that cheap, EncapMux works on bursts of packets and enqueues a run of packets going out of
+// the same interface as one batch. EncapMux starts off with just the drop node as its next
+// node, an edge to the EthEncap node of an interface is added when the interface is added
 #[derive(Default)]
 pub struct EncapMux {
-    next_names: Vec<String>,
+    // The edge to the EthEncap node of each interface, indexed by ifindex
+    edges: Vec<usize>,
+    burst: Vec<BoxPkt>,
+    batch: Vec<BoxPkt>,
 }
 
 impl EncapMux {
     pub fn new() -> EncapMux {
         EncapMux {
-            next_names: (0..MAX_INTERFACES).map(names::l2_eth_encap).collect(),
+            edges: Vec::new(),
+            burst: Vec::with_capacity(BURST_SIZE),
+            batch: Vec::with_capacity(BURST_SIZE),
         }
     }
 
@@ -40,20 +50,61 @@ impl EncapMux {
     }
 
     pub fn next_names(&self) -> Vec<String> {
-        self.next_names.clone()
+        vec![names::DROP.to_string()]
     }
 }
 
-impl<T> Gclient<T> for EncapMux {
-    fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<T>> {
+impl Gclient<R2Msg> for EncapMux {
+    fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
         Box::new(EncapMux {
-            next_names: self.next_names.clone(),
+            edges: self.edges.clone(),
+            burst: Vec::with_capacity(BURST_SIZE),
+            batch: Vec::with_capacity(BURST_SIZE),
         })
     }
 
     fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
-        while let Some(p) = vectors.pop() {
-            vectors.push(p.out_ifindex, p);
+        while vectors.pop_burst(&mut self.burst, BURST_SIZE) != 0 {
+            for p in self.burst.iter() {
+                p.prefetch();
+            }
+            let mut edge = 0;
+            for p in self.burst.drain(..) {
+                // Interfaces we dont have an edge to go to the drop node, which is edge zero
+                let next = if p.out_ifindex < self.edges.len() {
+                    self.edges[p.out_ifindex]
+                } else {
+                    0
+                };
+                vectors.trace(&p, || format!("ifindex {} edge {}", p.out_ifindex, next));
+                if next != edge {
+                    vectors.push_burst(edge, &mut self.batch);
+                    edge = next;
+                }
+                self.batch.push(p);
+            }
+            vectors.push_burst(edge, &mut self.batch);
+        }
+    }
+
+    fn control_msg(&mut self, _thread: usize, message: R2Msg) -> bool {
+        match message {
+            R2Msg::EdgeAdd(edge_add) => {
+                if edge_add.key >= self.edges.len() {
+                    self.edges.resize(edge_add.key + 1, 0);
+                }
+                self.edges[edge_add.key] = edge_add.edge;
+                true
+            }
+            // The interface is gone, its packets go to the drop node till an interface
+            // with the same ifindex gets an edge again
+            R2Msg::EdgeDel(edge_del) => {
+                if edge_del.key < self.edges.len() {
+                    self.edges[edge_del.key] = 0;
+                }
+                true
+            }
+            _ => false,
         }
     }
 }
@@ -75,15 +126,21 @@ fn next_name(ifindex: usize, next: Next) -> String {
 
 struct Cnt {
     bad_mac: Counter,
+    unresolved: Counter,
 }
 
 // Encapsulate an ethernet packet and send it to the interface. If the mac address table
-// does not have the mac address, generate an ARP request. The ARP response will be received
-// on the Decap node and it will broadcast the learned mac which the Encap node will also
-// receive. This mechanism needs rethinking (see github issue #4)
+// does not have the mac address, generate an ARP request (or an IPv6 neighbor solicitation).
+// The response will be received on the Decap node and it will broadcast the learned mac which
+// the Encap node will also receive. This mechanism needs rethinking (see github issue #4).
+// The mac table is a NeighCache which keeps the neighbor reachability state of each entry:
+// the packet waiting for a mac is dropped and solicitations are sent at most once every
+// RETRANS_MSECS, and an entry that has not been confirmed for a while is probed with unicast
+// solicitations while it stays in use, and is forgotten if the probes go unanswered. IPv6
+// packets to multicast addresses go to the multicast mac for the address
 pub struct EthEncap {
     intf: Arc<Interface>,
-    mac: HashMap<Ipv4Addr, EthMacRaw>,
+    mac: NeighCache,
     cnt: Cnt,
 }
 
@@ -95,10 +152,19 @@ impl EthEncap {
             CounterType::Error,
             "bad_mac",
         );
+        let unresolved = Counter::new(
+            counters,
+            &l2_eth_encap(intf.ifindex),
+            CounterType::Pkts,
+            "unresolved",
+        );
         EthEncap {
             intf,
-            mac: HashMap::new(),
-            cnt: Cnt { bad_mac },
+            mac: NeighCache::new(),
+            cnt: Cnt {
+                bad_mac,
+                unresolved,
+            },
         }
     }
 
@@ -115,15 +181,21 @@ impl EthEncap {
         v
     }
 
-    fn do_arp_request(&self, pool: &mut dyn PacketPool, in_pkt: &BoxPkt) -> Option<BoxPkt> {
+    // An ARP request to dmac, which is the broadcast mac unless we are probing a known mac
+    fn do_arp_request(
+        &self,
+        pool: &mut dyn PacketPool,
+        in_pkt: &BoxPkt,
+        dmac: &[u8],
+    ) -> Option<BoxPkt> {
         let pkt = pool.pkt(0 /* no headroom */);
         pkt.as_ref()?;
         let mut pkt = pkt.unwrap();
         let raw = pkt.head_mut();
 
-        // Dest mac all ones
+        // Dest mac
         let off = EthOffsets::EthDaddrOff as usize;
-        raw[off..off + ETH_ALEN].copy_from_slice(BCAST_MAC);
+        raw[off..off + ETH_ALEN].copy_from_slice(dmac);
         // Src mac
         let off = EthOffsets::EthSaddrOff as usize;
         raw[off..off + ETH_ALEN].copy_from_slice(&self.intf.l2_addr[0..ETH_ALEN]);
@@ -172,64 +244,192 @@ impl EthEncap {
         Some(pkt)
     }
 
-    pub fn mac_add(&mut self, ip: Ipv4Addr, mac: EthMacRaw) {
+    // A neighbor solicitation for the nexthop, to the solicited-node multicast address of
+    // the nexthop, or to its mac if we are probing a known mac
+    fn do_ns(
+        &self,
+        pool: &mut dyn PacketPool,
+        nhop: Ipv6Addr,
+        mac: Option<[u8; ETH_ALEN]>,
+    ) -> Option<BoxPkt> {
+        let mut pkt = pool.pkt(0 /* no headroom */)?;
+        let (daddr, dmac) = match mac {
+            Some(mac) => (nhop, mac),
+            None => {
+                let daddr = solicited_node(&nhop);
+                (daddr, multicast_mac6(&daddr).unwrap())
+            }
+        };
+        let nd = NdPkt {
+            icmp_type: ICMP6_NEIGH_SOLICIT,
+            flags: 0,
+            dmac: &dmac,
+            smac: &self.intf.l2_addr,
+            saddr: self.intf.ipv6_addr,
+            daddr,
+            target: nhop,
+        };
+        let bytes = nd_build(pkt.head_mut(), &nd);
+        pkt.move_tail(bytes as isize);
+        pkt.out_ifindex = self.intf.ifindex;
+        Some(pkt)
+    }
+
+    pub fn mac_add(&mut self, ip: IpAddr, mac: EthMacRaw, state: NeighState, advert: bool) {
         if mac.bytes.len() < ETH_ALEN {
             self.cnt.bad_mac.incr();
             return;
         }
-        self.mac.entry(ip).or_insert(mac);
+        if advert {
+            self.mac.advert(ip, &mac.bytes, state, time_msecs());
+        } else {
+            self.mac.update(ip, &mac.bytes, state, time_msecs());
+        }
     }
 
-    fn add_eth_hdr(&self, pool: &mut dyn PacketPool, pkt: &mut BoxPkt, mac: &EthMacRaw) -> bool {
-        if !pkt.prepend(pool, &ETH_TYPE_IPV4.to_be_bytes()) {
+    fn add_eth_hdr(
+        &self,
+        pool: &mut dyn PacketPool,
+        pkt: &mut BoxPkt,
+        mac: &[u8],
+        ethtype: u16,
+    ) -> bool {
+        if !pkt.prepend(pool, &ethtype.to_be_bytes()) {
             return false;
         }
         if !pkt.prepend(pool, &self.intf.l2_addr[0..ETH_ALEN]) {
             return false;
         }
-        if !pkt.prepend(pool, &mac.bytes) {
+        if !pkt.prepend(pool, mac) {
             return false;
         }
         pkt.set_l2(ETH_ALEN);
         true
     }
+
+    fn encap4(&mut self, vectors: &mut Dispatch, mut p: BoxPkt, now: u64) {
+        let nhop = p.out_l3addr;
+        let mac = match self.mac.resolve(IpAddr::V4(nhop), now) {
+            Resolve::Mac(mac) => mac,
+            Resolve::Probe(mac) => {
+                vectors.trace(&p, || format!("nhop {} probe", nhop));
+                if let Some(arp) = self.do_arp_request(vectors.pool, &p, &mac) {
+                    vectors.push(Next::TX as usize, arp);
+                }
+                mac
+            }
+            Resolve::Solicit => {
+                vectors.trace(&p, || format!("nhop {} unresolved, arp", nhop));
+                if let Some(arp) = self.do_arp_request(vectors.pool, &p, BCAST_MAC) {
+                    vectors.push(Next::TX as usize, arp);
+                }
+                self.cnt.unresolved.incr();
+                return;
+            }
+            Resolve::Wait => {
+                vectors.trace(&p, || format!("nhop {} unresolved", nhop));
+                self.cnt.unresolved.incr();
+                return;
+            }
+        };
+        vectors.trace(&p, || format!("nhop {} resolved", nhop));
+        if self.add_eth_hdr(vectors.pool, &mut p, &mac, ETH_TYPE_IPV4) {
+            vectors.push(Next::TX as usize, p);
+        }
+    }
+
+    fn encap6(&mut self, vectors: &mut Dispatch, mut p: BoxPkt, now: u64) {
+        let nhop = p.out_l3addr6;
+        if let Some(mac) = multicast_mac6(&nhop) {
+            vectors.trace(&p, || format!("nhop {} multicast", nhop));
+            if self.add_eth_hdr(vectors.pool, &mut p, &mac, ETH_TYPE_IPV6) {
+                vectors.push(Next::TX as usize, p);
+            }
+            return;
+        }
+        // Without an address of our own, there is nothing to source a solicitation from
+        let resolve = if self.intf.ipv6_addr == ZERO_IP6 {
+            Resolve::Wait
+        } else {
+            self.mac.resolve(IpAddr::V6(nhop), now)
+        };
+        let mac = match resolve {
+            Resolve::Mac(mac) => mac,
+            Resolve::Probe(mac) => {
+                vectors.trace(&p, || format!("nhop {} probe", nhop));
+                if let Some(ns) = self.do_ns(vectors.pool, nhop, Some(mac)) {
+                    vectors.push(Next::TX as usize, ns);
+                }
+                mac
+            }
+            Resolve::Solicit => {
+                vectors.trace(&p, || format!("nhop {} unresolved, solicit", nhop));
+                if let Some(ns) = self.do_ns(vectors.pool, nhop, None) {
+                    vectors.push(Next::TX as usize, ns);
+                }
+                self.cnt.unresolved.incr();
+                return;
+            }
+            Resolve::Wait => {
+                vectors.trace(&p, || format!("nhop {} unresolved", nhop));
+                self.cnt.unresolved.incr();
+                return;
+            }
+        };
+        vectors.trace(&p, || format!("nhop {} resolved", nhop));
+        if self.add_eth_hdr(vectors.pool, &mut p, &mac, ETH_TYPE_IPV6) {
+            vectors.push(Next::TX as usize, p);
+        }
+    }
 }
 
 impl Gclient<R2Msg> for EthEncap {
     fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
         let bad_mac = Counter::new(counters, &self.name(), CounterType::Error, "bad_mac");
+        let unresolved = Counter::new(counters, &self.name(), CounterType::Pkts, "unresolved");
         Box::new(EthEncap {
             intf: self.intf.clone(),
-            mac: HashMap::new(),
-            cnt: Cnt { bad_mac },
+            mac: NeighCache::new(),
+            cnt: Cnt {
+                bad_mac,
+                unresolved,
+            },
         })
     }
 
+    fn free(&self, counters: &mut Counters) {
+        self.cnt.bad_mac.free(counters);
+        self.cnt.unresolved.free(counters);
+    }
+
     fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
-        while let Some(mut p) = vectors.pop() {
-            let mac = self.mac.get(&p.out_l3addr);
-            if let Some(mac) = mac {
-                if self.add_eth_hdr(vectors.pool, &mut p, mac) {
-                    vectors.push(Next::TX as usize, p);
-                }
+        // The neighbor timers are coarse, one look at the clock is good for all the packets
+        let now = time_msecs();
+        while let Some(p) = vectors.pop() {
+            let (l3, _) = p.get_l3();
+            if !l3.is_empty() && l3[0] >> 4 == 6 {
+                self.encap6(vectors, p, now);
             } else {
-                let arp = self.do_arp_request(vectors.pool, &p);
-                if let Some(arp) = arp {
-                    vectors.push(Next::TX as usize, arp);
-                }
+                self.encap4(vectors, p, now);
             }
         }
     }
 
-    fn control_msg(&mut self, _thread: usize, message: R2Msg) {
+    fn control_msg(&mut self, thread: usize, message: R2Msg) -> bool {
         match message {
             R2Msg::ModifyInterface(mod_intf) => {
                 self.intf = mod_intf.intf;
             }
             R2Msg::EthMacAdd(mac_add) => {
-                self.mac_add(mac_add.ip, mac_add.mac);
+                self.mac_add(mac_add.ip, mac_add.mac, mac_add.state, mac_add.advert);
+            }
+            R2Msg::NodeQuery(query) if query.query == NodeQuery::EthMacs => {
+                let macs = self.mac.dump();
+                // The requester might have given up waiting, nothing to do if so
+                let _ = query.reply.send((thread, NodeReply::EthMacs(macs)));
             }
-            _ => panic!("Unknown type"),
+            _ => return false,
         }
+        true
     }
 }
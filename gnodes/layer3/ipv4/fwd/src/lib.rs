@@ -1,11 +1,22 @@
+use common::time_msecs;
 use counters::{flavors::Counter, flavors::CounterType, Counters};
-use fwd::IPHDR_DADDR_OFF;
-use fwd::IPHDR_MIN_LEN;
-use fwd::{ipv4::IPv4Table, Fwd};
+use fwd::intf::Interface;
+use fwd::{icmp_is_query, ip_checksum, ip_checksum_update};
+use fwd::{ipv4::IPv4Table, Fwd, ETHER_MTU, ICMP_FRAG_NEEDED, IPHDR_DF};
+use fwd::{DEFAULT_VRF, ICMP_HOST_UNREACH, ICMP_NET_UNREACH};
+use fwd::{ICMP_CODE_OFF, ICMP_CSUM_OFF, ICMP_DEST_UNREACH, ICMP_HDR_LEN, ICMP_MTU_OFF};
+use fwd::{ICMP_TIME_EXCEEDED, ICMP_TYPE_OFF};
+use fwd::{IPHDR_CSUM_OFF, IPHDR_DADDR_OFF, IPHDR_FRAG_MASK, IPHDR_FRAG_OFF, IPHDR_MIN_LEN};
+use fwd::{IPHDR_MF, IP_DEFAULT_TTL, IP_PROTO_ICMP, IP_PROTO_TCP, IP_PROTO_UDP, ZERO_IP};
+use fwd::{IPHDR_PROTO_OFF, IPHDR_SADDR_OFF, IPHDR_TOTLEN_OFF, IPHDR_TTL_OFF};
+use graph::feature::FeatureArc;
 use graph::Dispatch;
 use graph::Gclient;
+use graph::BURST_SIZE;
 use log::Logger;
 use msg::R2Msg;
+use packet::BoxPkt;
+use packet::PacketPool;
 use std::net::Ipv4Addr;
 use std::sync::Arc;
 
@@ -13,45 +24,190 @@ use std::sync::Arc;
 enum Next {
     Drop = 0,
     EncapMux,
+    L3Ipv4Local,
 }
 
-const NEXT_NAMES: &[Next] = &[Next::Drop, Next::EncapMux];
+const NEXT_NAMES: &[Next] = &[Next::Drop, Next::EncapMux, Next::L3Ipv4Local];
 
 fn next_name(next: Next) -> String {
     match next {
         Next::Drop => names::DROP.to_string(),
         Next::EncapMux => names::ENCAPMUX.to_string(),
+        Next::L3Ipv4Local => names::L3_IPV4_LOCAL.to_string(),
     }
 }
 
 struct IPv4Cnt {
     no_route: Counter,
+    blackhole: Counter,
+    reject: Counter,
     invalid_l3: Counter,
+    ttl_expired: Counter,
+    fragmented: Counter,
+    fragments: Counter,
+    frag_needed: Counter,
+    frag_fail: Counter,
+    pbr_fallback: Counter,
+    icmp_ratelimit: Counter,
+}
+
+impl IPv4Cnt {
+    fn new(counters: &mut Counters) -> IPv4Cnt {
+        let name = names::L3_IPV4_FWD;
+        IPv4Cnt {
+            no_route: Counter::new(counters, name, CounterType::Pkts, "no_route"),
+            blackhole: Counter::new(counters, name, CounterType::Pkts, "blackhole"),
+            reject: Counter::new(counters, name, CounterType::Pkts, "reject"),
+            invalid_l3: Counter::new(counters, name, CounterType::Error, "invalid_l3"),
+            ttl_expired: Counter::new(counters, name, CounterType::Pkts, "ttl_expired"),
+            fragmented: Counter::new(counters, name, CounterType::Pkts, "fragmented"),
+            fragments: Counter::new(counters, name, CounterType::Pkts, "fragments"),
+            frag_needed: Counter::new(counters, name, CounterType::Pkts, "frag_needed"),
+            frag_fail: Counter::new(counters, name, CounterType::Error, "frag_fail"),
+            pbr_fallback: Counter::new(counters, name, CounterType::Pkts, "pbr_fallback"),
+            icmp_ratelimit: Counter::new(counters, name, CounterType::Pkts, "icmp_ratelimit"),
+        }
+    }
+}
+
+fn ipv4_addr(bytes: &[u8]) -> Ipv4Addr {
+    Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])
+}
+
+// FNV-1a, to hash the flows over the paths of a multipath route
+const FNV_BASIS: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+fn fnv(hash: u32, bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(hash, |h, b| (h ^ u32::from(*b)).wrapping_mul(FNV_PRIME))
+}
+
+// A hash of the 5-tuple of the packet - the addresses, the protocol and the TCP/UDP ports -
+// so that all the packets of a flow take the same path. Only the first fragment of a packet
+// has the ports, so the ports are left out for all the fragments to keep them together
+fn flow_hash(p: &BoxPkt) -> u32 {
+    let (iphdr, ihl) = p.get_l3();
+    let hash = fnv(FNV_BASIS, &iphdr[IPHDR_SADDR_OFF..IPHDR_DADDR_OFF + 4]);
+    let proto = iphdr[IPHDR_PROTO_OFF];
+    let hash = fnv(hash, &[proto]);
+    let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+    let first = p.data(0).map_or(0, |(d, _)| d.len());
+    if (proto == IP_PROTO_TCP || proto == IP_PROTO_UDP)
+        && frag & (IPHDR_MF | IPHDR_FRAG_MASK) == 0
+        && first >= ihl + 4
+    {
+        fnv(hash, &iphdr[ihl..ihl + 4])
+    } else {
+        hash
+    }
+}
+
+// Whether the table has a route that forwards packets to the address out of an interface
+fn reachable(table: &IPv4Table, addr: Ipv4Addr) -> bool {
+    let next = match table.longest_match(addr) {
+        Some((_, _, leaf)) => &leaf.next,
+        None => return false,
+    };
+    let next = match next {
+        Fwd::IPv4Leaf(resolved) => &resolved.next,
+        next => next,
+    };
+    matches!(next, Fwd::Adjacency(_) | Fwd::Multipath(_))
+}
+
+// The number of bytes of the original packet beyond its IP header that ICMP errors carry
+const ICMP_QUOTE_LEN: usize = 8;
+
+// The IP options that matter for fragmentation: the end of the options, the one byte no-op,
+// and the flag in the option type that says the option is copied into all the fragments
+const IPOPT_END: u8 = 0;
+const IPOPT_NOP: u8 = 1;
+const IPOPT_COPIED: u8 = 0x80;
+// Fragment offsets are in units of 8 bytes
+const FRAG_UNIT: usize = 8;
+
+// The ICMP errors a thread sends are rate limited (RFC 1812 section 4.3.2.8) with a token
+// bucket: up to ICMP_BURST errors at once, and ICMP_RATE errors a second after that
+const ICMP_RATE: u64 = 1000;
+const ICMP_BURST: u64 = 50;
+
+struct IcmpLimit {
+    tokens: u64,
+    // When the bucket was last refilled, in milliseconds
+    refilled: u64,
+}
+
+impl IcmpLimit {
+    fn new() -> IcmpLimit {
+        // The first refill fills the bucket
+        IcmpLimit {
+            tokens: 0,
+            refilled: 0,
+        }
+    }
+
+    // Take a token for an error if there is one
+    fn allow(&mut self, now: u64) -> bool {
+        let refill = now.saturating_sub(self.refilled) * ICMP_RATE / 1000;
+        if refill > 0 {
+            self.tokens = (self.tokens + refill).min(ICMP_BURST);
+            self.refilled = now;
+        }
+        if self.tokens == 0 {
+            return false;
+        }
+        self.tokens -= 1;
+        true
+    }
 }
 
 // The IPv4 Forwarding node: all it does is a route lookup the destinaton address in a
 // tree-bitmap data structure, find the 'adjacency' information that says where the
-// packet has to go out and send it to the Encap node for that output interface.
+// packet has to go out and send it to the Encap node for that output interface. The
+// forwarding node is the start of the ip4-output feature arc, packets going out of
+// interfaces with output features enabled go through those features before EncapMux.
+// The node decrements the TTL of the packets it forwards, packets whose TTL expires are
+// dropped and an ICMP time exceeded is sent back to the source of the packet. Packets to
+// R2's own addresses hit the /32 receive entries in the table and go to ip4-local instead.
+// Blackhole routes drop the packets silently, reject routes and destinations without a
+// route drop them with an ICMP destination unreachable back to the source.
+// A route can have more than one adjacency (ECMP), a hash of the flow picks one of them.
+// The control plane resolves routes with recursive nexthops to adjacencies, so a lookup is
+// all it takes for those routes too. Packets bigger than the MTU of the output interface
+// are fragmented here, before the output features, or dropped with an ICMP fragmentation
+// needed if they have the don't fragment bit set. Each VRF has a route table of its own,
+// the packets are routed using the table of the VRF of the interface they came in on.
+// Policy based routing (l3_ipv4_pbr, on the ip4-input arc) can pick another VRF for a
+// packet, or a nexthop to route the packet towards instead of its destination
 pub struct IPv4Fwd {
-    table: Arc<IPv4Table>,
+    // The route tables indexed by the VRF id, None for VRFs without routes yet
+    tables: Vec<Option<Arc<IPv4Table>>>,
+    output: FeatureArc,
+    // The interfaces indexed by ifindex, to find the address to send ICMP errors from and
+    // the MTU of the output interface
+    intfs: Vec<Option<Arc<Interface>>>,
     cnt: IPv4Cnt,
+    icmp_limit: IcmpLimit,
+    // The clock the ICMP rate limit goes by, the tests run the node on a clock of their own
+    time_msecs: fn() -> u64,
+    // The burst of packets being processed, and the packets from the burst going to EncapMux
+    burst: Vec<BoxPkt>,
+    encap: Vec<BoxPkt>,
 }
 
 impl IPv4Fwd {
-    pub fn new(table: Arc<IPv4Table>, counters: &mut Counters) -> IPv4Fwd {
-        let invalid_l3 = Counter::new(
-            counters,
-            names::L3_IPV4_FWD,
-            CounterType::Error,
-            "invalid_l3",
-        );
-        let no_route = Counter::new(counters, names::L3_IPV4_FWD, CounterType::Pkts, "no_route");
+    pub fn new(table: Arc<IPv4Table>, output: FeatureArc, counters: &mut Counters) -> IPv4Fwd {
         IPv4Fwd {
-            table,
-            cnt: IPv4Cnt {
-                no_route,
-                invalid_l3,
-            },
+            tables: vec![Some(table)],
+            output,
+            intfs: Vec::new(),
+            cnt: IPv4Cnt::new(counters),
+            icmp_limit: IcmpLimit::new(),
+            time_msecs,
+            burst: Vec::with_capacity(BURST_SIZE),
+            encap: Vec::with_capacity(BURST_SIZE),
         }
     }
 
@@ -65,63 +221,396 @@ impl IPv4Fwd {
             assert_eq!(*n as usize, v.len());
             v.push(next_name(*n));
         }
+        v.append(&mut self.output.next_names());
         v
     }
+
+    fn forward(&mut self, vectors: &mut Dispatch, mut p: BoxPkt) {
+        let (iphdr, _) = p.get_l3();
+        let daddr = ipv4_addr(&iphdr[IPHDR_DADDR_OFF..]);
+        let vrf = match p.pbr_vrf {
+            Some(vrf) => vrf,
+            None => self.intf(p.in_ifindex).map_or(DEFAULT_VRF, |intf| intf.vrf),
+        };
+        let table = self.tables.get(vrf).and_then(|t| t.as_ref());
+        // Policy based routing can send the packet towards a nexthop of its choice instead
+        // of the destination, as long as the nexthop is reachable. If not, the packet is
+        // routed to its destination like any other packet
+        let mut target = daddr;
+        if p.pbr_nhop != ZERO_IP {
+            if table.is_some_and(|t| reachable(t, p.pbr_nhop)) {
+                target = p.pbr_nhop;
+            } else {
+                vectors.trace(&p, || format!("pbr nhop {} unreachable", p.pbr_nhop));
+                self.cnt.pbr_fallback.incr();
+            }
+        }
+        let (prefix, mask, leaf) = match table.and_then(|t| t.longest_match(target)) {
+            Some(found) => found,
+            None => {
+                vectors.trace(&p, || format!("dst {} no route", daddr));
+                self.cnt.no_route.incr();
+                self.unreachable(vectors, &p, ICMP_NET_UNREACH);
+                return;
+            }
+        };
+        // A recursive route points to the leaf with the paths its nexthops resolved to
+        let next = match &leaf.next {
+            Fwd::IPv4Leaf(resolved) => &resolved.next,
+            next => next,
+        };
+        let adj = match next {
+            Fwd::Local(_) => {
+                vectors.trace(&p, || format!("dst {} local", daddr));
+                vectors.push(Next::L3Ipv4Local as usize, p);
+                return;
+            }
+            Fwd::Adjacency(adj) => adj,
+            Fwd::Multipath(mp) => mp.path(flow_hash(&p)),
+            Fwd::Blackhole => {
+                vectors.trace(&p, || {
+                    format!("dst {} route {}/{} blackhole", daddr, prefix, mask)
+                });
+                self.cnt.blackhole.incr();
+                return;
+            }
+            Fwd::Reject => {
+                vectors.trace(&p, || {
+                    format!("dst {} route {}/{} reject", daddr, prefix, mask)
+                });
+                self.cnt.reject.incr();
+                self.unreachable(vectors, &p, ICMP_HOST_UNREACH);
+                return;
+            }
+            _ => {
+                vectors.trace(&p, || format!("dst {} no route", daddr));
+                self.cnt.no_route.incr();
+                return;
+            }
+        };
+        let ttl = iphdr[IPHDR_TTL_OFF];
+        if ttl <= 1 {
+            vectors.trace(&p, || format!("ttl {} expired", ttl));
+            self.cnt.ttl_expired.incr();
+            let icmp = self.icmp_error(vectors.pool, &p, ICMP_TIME_EXCEEDED, 0, 0);
+            if let Some(icmp) = icmp {
+                vectors.trace(&icmp, || "icmp time exceeded".to_string());
+                self.forward(vectors, icmp);
+            }
+            return;
+        }
+        let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+        let mtu = self.mtu(adj.ifindex);
+        if p.len() > mtu && frag & IPHDR_DF != 0 {
+            vectors.trace(&p, || format!("len {} mtu {} DF set", p.len(), mtu));
+            self.cnt.frag_needed.incr();
+            let unreach = ICMP_DEST_UNREACH;
+            let icmp = self.icmp_error(vectors.pool, &p, unreach, ICMP_FRAG_NEEDED, mtu as u16);
+            if let Some(icmp) = icmp {
+                vectors.trace(&icmp, || "icmp fragmentation needed".to_string());
+                self.forward(vectors, icmp);
+            }
+            return;
+        }
+        ttl_decrement(&mut p);
+        vectors.trace(&p, || {
+            format!(
+                "dst {} route {}/{} nhop {} ifindex {}",
+                daddr, prefix, mask, adj.nhop, adj.ifindex
+            )
+        });
+        p.out_ifindex = adj.ifindex;
+        p.out_l3addr = adj.nhop;
+        if p.out_l3addr == ZERO_IP {
+            // destination (or the policy nexthop) is in connected subnet
+            p.out_l3addr = target;
+        }
+        if p.len() <= mtu {
+            self.output(vectors, p);
+            return;
+        }
+        let headroom = self.headroom(adj.ifindex);
+        match fragment(vectors.pool, &p, mtu, headroom) {
+            Some(frags) => {
+                let n = frags.len();
+                vectors.trace(&p, || {
+                    format!("len {} mtu {} fragments {}", p.len(), mtu, n)
+                });
+                self.cnt.fragmented.incr();
+                self.cnt.fragments.add(n as u64);
+                for f in frags {
+                    self.output(vectors, f);
+                }
+            }
+            None => {
+                vectors.trace(&p, || "fragmentation failed".to_string());
+                self.cnt.frag_fail.incr();
+            }
+        }
+    }
+
+    // Off to the output features if the output interface has any, to EncapMux otherwise
+    fn output(&mut self, vectors: &mut Dispatch, p: BoxPkt) {
+        if self.output.enabled(&p) {
+            let next = NEXT_NAMES.len() + self.output.next(&p, 0);
+            vectors.push(next, p);
+        } else {
+            self.encap.push(p);
+        }
+    }
+
+    fn intf(&self, ifindex: usize) -> Option<&Arc<Interface>> {
+        self.intfs.get(ifindex)?.as_ref()
+    }
+
+    fn mtu(&self, ifindex: usize) -> usize {
+        self.intf(ifindex).map_or(ETHER_MTU, |intf| intf.mtu)
+    }
+
+    fn headroom(&self, ifindex: usize) -> usize {
+        self.intf(ifindex).map_or(0, |intf| intf.headroom)
+    }
+
+    // Send a destination unreachable with the given code back to the source of the packet
+    fn unreachable(&mut self, vectors: &mut Dispatch, p: &BoxPkt, code: u8) {
+        if let Some(icmp) = self.icmp_error(vectors.pool, p, ICMP_DEST_UNREACH, code, 0) {
+            vectors.trace(&icmp, || format!("icmp unreachable code {}", code));
+            self.forward(vectors, icmp);
+        }
+    }
+
+    // Build an ICMP error of the given type and code for the packet, sent to the source of
+    // the packet from the address of the interface the packet came in on. The mtu is the
+    // next-hop MTU of a fragmentation needed, zero for other errors. There are no ICMP
+    // errors for ICMP errors (anything other than the ICMP queries), for fragments other
+    // than the first one, or for packets from addresses that are not unicast (RFC 1812
+    // section 4.3.2.7). Errors over the rate limit are not sent either, see IcmpLimit
+    fn icmp_error(
+        &mut self,
+        pool: &mut dyn PacketPool,
+        p: &BoxPkt,
+        icmp_type: u8,
+        code: u8,
+        mtu: u16,
+    ) -> Option<BoxPkt> {
+        let intf = self.intf(p.in_ifindex)?.clone();
+        if intf.ipv4_addr == ZERO_IP {
+            return None;
+        }
+        let (iphdr, _) = p.get_l3();
+        let saddr = ipv4_addr(&iphdr[IPHDR_SADDR_OFF..]);
+        if saddr.is_unspecified()
+            || saddr.is_broadcast()
+            || saddr.is_multicast()
+            || saddr.is_loopback()
+        {
+            return None;
+        }
+        let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+        if frag & IPHDR_FRAG_MASK != 0 {
+            return None;
+        }
+        let (data, len) = p.data(0)?;
+        let ihl = ((data[0] & 0xf) as usize) * 4;
+        if data[IPHDR_PROTO_OFF] == IP_PROTO_ICMP
+            && len > ihl + ICMP_TYPE_OFF
+            && !icmp_is_query(data[ihl + ICMP_TYPE_OFF])
+        {
+            return None;
+        }
+        if !self.icmp_limit.allow((self.time_msecs)()) {
+            self.cnt.icmp_ratelimit.incr();
+            return None;
+        }
+        let quote = &data[0..len.min(ihl + ICMP_QUOTE_LEN)];
+
+        let mut icmp = vec![0; ICMP_HDR_LEN];
+        icmp[ICMP_TYPE_OFF] = icmp_type;
+        icmp[ICMP_CODE_OFF] = code;
+        icmp[ICMP_MTU_OFF..ICMP_MTU_OFF + 2].copy_from_slice(&mtu.to_be_bytes());
+        icmp.extend_from_slice(quote);
+        let csum = ip_checksum(&icmp);
+        icmp[ICMP_CSUM_OFF..ICMP_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+        let mut hdr = vec![0; IPHDR_MIN_LEN];
+        hdr[0] = 0x45;
+        let totlen = (IPHDR_MIN_LEN + icmp.len()) as u16;
+        hdr[IPHDR_TOTLEN_OFF..IPHDR_TOTLEN_OFF + 2].copy_from_slice(&totlen.to_be_bytes());
+        hdr[IPHDR_TTL_OFF] = IP_DEFAULT_TTL;
+        hdr[IPHDR_PROTO_OFF] = IP_PROTO_ICMP;
+        hdr[IPHDR_SADDR_OFF..IPHDR_SADDR_OFF + 4].copy_from_slice(&intf.ipv4_addr.octets());
+        hdr[IPHDR_DADDR_OFF..IPHDR_DADDR_OFF + 4].copy_from_slice(&saddr.octets());
+        let csum = ip_checksum(&hdr);
+        hdr[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+        let mut pkt = pool.pkt(intf.headroom)?;
+        if !pkt.append(pool, &hdr) || !pkt.append(pool, &icmp) || !pkt.set_l3(IPHDR_MIN_LEN) {
+            return None;
+        }
+        pkt.in_ifindex = p.in_ifindex;
+        pkt.trace = p.trace;
+        Some(pkt)
+    }
+}
+
+// The options of the first fragment that go into the other fragments too, those with the
+// copied flag set (RFC 791), padded out to a multiple of 4 bytes
+fn frag_options(opts: &[u8]) -> Vec<u8> {
+    let mut copied = Vec::new();
+    let mut i = 0;
+    while i < opts.len() {
+        match opts[i] {
+            IPOPT_END => break,
+            IPOPT_NOP => i += 1,
+            opt => {
+                let len = opts.get(i + 1).map_or(0, |l| *l as usize);
+                if len < 2 || i + len > opts.len() {
+                    break;
+                }
+                if opt & IPOPT_COPIED != 0 {
+                    copied.extend_from_slice(&opts[i..i + len]);
+                }
+                i += len;
+            }
+        }
+    }
+    copied.resize((copied.len() + 3) & !3, IPOPT_END);
+    copied
+}
+
+// Split the packet into fragments of at most mtu bytes each. The first fragment has the
+// header of the packet with all its options, the others only the options that are copied.
+// The fragments of a packet that is itself a fragment are fragments of the original packet,
+// at their offsets in the original and with MF set on all but the very last one. None if
+// the packets could not be allocated
+fn fragment(
+    pool: &mut dyn PacketPool,
+    p: &BoxPkt,
+    mtu: usize,
+    headroom: usize,
+) -> Option<Vec<BoxPkt>> {
+    let (iphdr, ihl) = p.get_l3();
+    let first_hdr = iphdr[0..ihl].to_vec();
+    let mut hdr = iphdr[0..IPHDR_MIN_LEN].to_vec();
+    hdr.extend_from_slice(&frag_options(&iphdr[IPHDR_MIN_LEN..ihl]));
+    let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+    let frag_off = (frag & IPHDR_FRAG_MASK) as usize;
+    let more = frag & IPHDR_MF;
+
+    let mut frags = Vec::new();
+    let plen = p.len() - ihl;
+    let mut off = 0;
+    while off < plen {
+        let mut hdr = if off == 0 {
+            first_hdr.clone()
+        } else {
+            hdr.clone()
+        };
+        let hlen = hdr.len();
+        let room = mtu.checked_sub(hlen)? / FRAG_UNIT * FRAG_UNIT;
+        if room == 0 {
+            return None;
+        }
+        let len = room.min(plen - off);
+        let last = off + len == plen;
+        hdr[0] = 0x40 | (hlen / 4) as u8;
+        let totlen = (hlen + len) as u16;
+        hdr[IPHDR_TOTLEN_OFF..IPHDR_TOTLEN_OFF + 2].copy_from_slice(&totlen.to_be_bytes());
+        let mut field = (frag_off + off / FRAG_UNIT) as u16;
+        field |= if last { more } else { IPHDR_MF };
+        hdr[IPHDR_FRAG_OFF..IPHDR_FRAG_OFF + 2].copy_from_slice(&field.to_be_bytes());
+        hdr[IPHDR_CSUM_OFF] = 0;
+        hdr[IPHDR_CSUM_OFF + 1] = 0;
+        let csum = ip_checksum(&hdr);
+        hdr[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+        let mut f = pool.pkt(headroom)?;
+        if !f.append(pool, &hdr) || !f.append_pkt(pool, p, ihl + off, len) {
+            return None;
+        }
+        if !f.set_l3(hlen) {
+            return None;
+        }
+        f.in_ifindex = p.in_ifindex;
+        f.out_ifindex = p.out_ifindex;
+        f.out_l3addr = p.out_l3addr;
+        f.trace = p.trace;
+        frags.push(f);
+        off += len;
+    }
+    Some(frags)
+}
+
+// Decrement the TTL, and update the header checksum for the change in the TTL
+fn ttl_decrement(p: &mut BoxPkt) {
+    let (iphdr, _) = p.get_l3_mut();
+    let old = u16::from_be_bytes([iphdr[IPHDR_TTL_OFF], iphdr[IPHDR_PROTO_OFF]]);
+    iphdr[IPHDR_TTL_OFF] -= 1;
+    let new = u16::from_be_bytes([iphdr[IPHDR_TTL_OFF], iphdr[IPHDR_PROTO_OFF]]);
+    let csum = u16::from_be_bytes([iphdr[IPHDR_CSUM_OFF], iphdr[IPHDR_CSUM_OFF + 1]]);
+    let csum = ip_checksum_update(csum, old, new);
+    iphdr[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
 }
 
 impl Gclient<R2Msg> for IPv4Fwd {
     fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
-        let no_route = Counter::new(counters, &self.name(), CounterType::Pkts, "no_route");
-        let invalid_l3 = Counter::new(counters, &self.name(), CounterType::Error, "invalid_l3");
         Box::new(IPv4Fwd {
-            table: self.table.clone(),
-            cnt: IPv4Cnt {
-                no_route,
-                invalid_l3,
-            },
+            tables: self.tables.clone(),
+            output: self.output.clone(),
+            intfs: self.intfs.clone(),
+            cnt: IPv4Cnt::new(counters),
+            icmp_limit: IcmpLimit::new(),
+            time_msecs: self.time_msecs,
+            burst: Vec::with_capacity(BURST_SIZE),
+            encap: Vec::with_capacity(BURST_SIZE),
         })
     }
 
     fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
-        while let Some(mut p) = vectors.pop() {
-            let (iphdr, hdrlen) = p.get_l3();
-            if hdrlen < IPHDR_MIN_LEN {
-                self.cnt.invalid_l3.incr();
-                continue;
-            }
-            let daddr = Ipv4Addr::new(
-                iphdr[IPHDR_DADDR_OFF],
-                iphdr[IPHDR_DADDR_OFF + 1],
-                iphdr[IPHDR_DADDR_OFF + 2],
-                iphdr[IPHDR_DADDR_OFF + 3],
-            );
-            if let Some((_prefix, _mask, leaf)) = self.table.root.longest_match(daddr) {
-                match &leaf.next {
-                    Fwd::Adjacency(adj) => {
-                        p.out_ifindex = adj.ifindex;
-                        p.out_l3addr = adj.nhop;
-                        if p.out_l3addr == fwd::ZERO_IP {
-                            // destination is in connected subnet
-                            p.out_l3addr = daddr;
-                        }
-                        vectors.push(Next::EncapMux as usize, p);
-                    }
-                    _ => {
-                        let _ = self.cnt.no_route.incr();
-                    }
+        while vectors.pop_burst(&mut self.burst, BURST_SIZE) != 0 {
+            for p in self.burst.iter() {
+                p.prefetch();
+            }
+            // forward() needs self, so take the burst out of self while working on it
+            let mut burst = std::mem::take(&mut self.burst);
+            for p in burst.drain(..) {
+                let (_, hdrlen) = p.get_l3();
+                if hdrlen < IPHDR_MIN_LEN {
+                    vectors.trace(&p, || "invalid l3".to_string());
+                    self.cnt.invalid_l3.incr();
+                    continue;
                 }
-            } else {
-                self.cnt.no_route.incr();
+                self.forward(vectors, p);
             }
+            self.burst = burst;
+            vectors.push_burst(Next::EncapMux as usize, &mut self.encap);
         }
     }
 
-    fn control_msg(&mut self, _thread: usize, message: R2Msg) {
+    fn control_msg(&mut self, _thread: usize, message: R2Msg) -> bool {
         match message {
             R2Msg::IPv4TableAdd(table) => {
-                self.table = table.table;
+                if table.vrf >= self.tables.len() {
+                    self.tables.resize(table.vrf + 1, None);
+                }
+                self.tables[table.vrf] = Some(table.table);
+            }
+            R2Msg::FeatureEnable(feature) => {
+                self.output
+                    .enable(feature.ifindex, &feature.feature, feature.enable);
             }
-            _ => panic!("Unknown type"),
+            R2Msg::ModifyInterface(mod_intf) => {
+                let ifindex = mod_intf.intf.ifindex;
+                if ifindex >= self.intfs.len() {
+                    self.intfs.resize(ifindex + 1, None);
+                }
+                self.intfs[ifindex] = Some(mod_intf.intf);
+            }
+            _ => return false,
         }
+        true
     }
 }
+
+#[cfg(test)]
+mod test;
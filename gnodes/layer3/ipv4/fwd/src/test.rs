@@ -0,0 +1,384 @@
+use super::*;
+use fwd::adj::Adjacency;
+use fwd::harness::{ipv4_pkt, payload, Ipv4Hdr};
+use fwd::intf::ModifyInterfaceMsg;
+use fwd::ipv4::{IPv4Leaf, IPv4TableMsg};
+use fwd::ICMP_ECHO_REQUEST;
+use graph::harness::{self, pkt_bytes, Harness};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const IN_IFINDEX: usize = 1;
+const OUT_IFINDEX: usize = 2;
+const IN_ADDR: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 1);
+const SRC: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 2);
+const DST: Ipv4Addr = Ipv4Addr::new(20, 1, 1, 2);
+const NHOP: Ipv4Addr = Ipv4Addr::new(20, 1, 1, 1);
+
+// The clock of the fwd nodes in the tests, only the ICMP rate limit test moves it
+static CLOCK: AtomicU64 = AtomicU64::new(1_000);
+
+fn test_clock_msecs() -> u64 {
+    CLOCK.load(Ordering::Relaxed)
+}
+
+// The fwd node in a graph of its own, with the input interface connected to the subnet of
+// SRC and the output interface (with the given mtu) leading to DST through NHOP
+fn fwd_harness(counters: &mut Counters, mtu: usize) -> Harness<R2Msg> {
+    let output = FeatureArc::new(names::IP4_OUTPUT, names::L3_IPV4_FWD, names::ENCAPMUX, true);
+    let mut table = IPv4Table::new();
+    let adj = Adjacency::new(ZERO_IP, IN_IFINDEX);
+    let leaf = IPv4Leaf::new(Fwd::Adjacency(Arc::new(adj)));
+    table.add(Ipv4Addr::new(10, 1, 1, 0), 24, Arc::new(leaf));
+    let adj = Adjacency::new(NHOP, OUT_IFINDEX);
+    let leaf = IPv4Leaf::new(Fwd::Adjacency(Arc::new(adj)));
+    table.add(Ipv4Addr::new(20, 1, 1, 0), 24, Arc::new(leaf));
+
+    let mut node = IPv4Fwd::new(Arc::new(table), output, counters);
+    node.time_msecs = test_clock_msecs;
+    let mut harness = graph::harness!(node, counters);
+
+    let mut intf = Interface::new("in", IN_IFINDEX, vec![0; 6], 0);
+    intf.set_v4addr(IN_ADDR, 24);
+    let intf = Arc::new(intf);
+    assert!(harness.control_msg(R2Msg::ModifyInterface(ModifyInterfaceMsg { intf })));
+    let mut intf = Interface::new("out", OUT_IFINDEX, vec![0; 6], 0);
+    intf.set_v4addr(NHOP, 24);
+    intf.mtu = mtu;
+    let intf = Arc::new(intf);
+    assert!(harness.control_msg(R2Msg::ModifyInterface(ModifyInterfaceMsg { intf })));
+    harness
+}
+
+// An IPv4 packet from SRC to DST
+fn ipv4_hdr(proto: u8, ttl: u8, frag: u16) -> Ipv4Hdr {
+    Ipv4Hdr {
+        ttl,
+        frag,
+        ..Ipv4Hdr::new(SRC, DST, proto)
+    }
+}
+
+// An ICMP message of the given type with eight bytes of payload
+fn icmp_payload(icmp_type: u8) -> Vec<u8> {
+    let mut icmp = vec![0; ICMP_HDR_LEN + 8];
+    icmp[ICMP_TYPE_OFF] = icmp_type;
+    icmp
+}
+
+fn send(harness: &mut Harness<R2Msg>, data: &[u8]) -> Vec<(String, BoxPkt)> {
+    send_on(harness, data, IN_IFINDEX)
+}
+
+fn send_on(harness: &mut Harness<R2Msg>, data: &[u8], ifindex: usize) -> Vec<(String, BoxPkt)> {
+    let ihl = ((data[0] & 0xf) as usize) * 4;
+    let mut pkt = harness.pkt(data, ihl);
+    pkt.in_ifindex = ifindex;
+    harness.run(vec![pkt])
+}
+
+fn count(test: &str, ctype: CounterType, name: &str) -> u64 {
+    harness::count(test, names::L3_IPV4_FWD, ctype, name)
+}
+
+#[test]
+fn icmp_error_for_query() {
+    let mut counters = Counters::new("icmp_error_for_query").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+
+    // An echo request whose TTL expires gets a time exceeded back to its source
+    let pkt = ipv4_pkt(
+        &ipv4_hdr(IP_PROTO_ICMP, 1, 0),
+        &icmp_payload(ICMP_ECHO_REQUEST),
+    );
+    let out = send(&mut harness, &pkt);
+    assert_eq!(out.len(), 1);
+    let (next, icmp) = &out[0];
+    assert_eq!(next, names::ENCAPMUX);
+    assert_eq!(icmp.out_ifindex, IN_IFINDEX);
+    assert_eq!(icmp.out_l3addr, SRC);
+    let bytes = pkt_bytes(icmp);
+    assert_eq!(bytes[IPHDR_PROTO_OFF], IP_PROTO_ICMP);
+    assert_eq!(ipv4_addr(&bytes[IPHDR_SADDR_OFF..]), IN_ADDR);
+    assert_eq!(ipv4_addr(&bytes[IPHDR_DADDR_OFF..]), SRC);
+    assert_eq!(bytes[IPHDR_MIN_LEN + ICMP_TYPE_OFF], ICMP_TIME_EXCEEDED);
+    let expired = count("icmp_error_for_query", CounterType::Pkts, "ttl_expired");
+    assert_eq!(expired, 1);
+}
+
+#[test]
+fn no_icmp_error_for_error() {
+    let mut counters = Counters::new("no_icmp_error_for_error").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+
+    // Unreachable, source quench, redirect, time exceeded, parameter problem and an unknown
+    // type are all errors, their TTL expires silently
+    let errors = [ICMP_DEST_UNREACH, 4, 5, ICMP_TIME_EXCEEDED, 12, 200];
+    for icmp_type in errors.iter() {
+        let pkt = ipv4_pkt(&ipv4_hdr(IP_PROTO_ICMP, 1, 0), &icmp_payload(*icmp_type));
+        assert!(send(&mut harness, &pkt).is_empty());
+    }
+    let expired = count("no_icmp_error_for_error", CounterType::Pkts, "ttl_expired");
+    assert_eq!(expired, errors.len() as u64);
+}
+
+// The fragments the node sent out, each one as bytes
+fn fragments(out: &[(String, BoxPkt)]) -> Vec<Vec<u8>> {
+    out.iter()
+        .map(|(next, p)| {
+            assert_eq!(next, names::ENCAPMUX);
+            assert_eq!(p.out_ifindex, OUT_IFINDEX);
+            assert_eq!(p.out_l3addr, NHOP);
+            pkt_bytes(p)
+        })
+        .collect()
+}
+
+// The offset in bytes, the MF bit, the header length and the payload of a fragment
+fn frag_fields(frag: &[u8]) -> (usize, bool, usize, &[u8]) {
+    let ihl = ((frag[0] & 0xf) as usize) * 4;
+    assert_eq!(ip_checksum(&frag[0..ihl]), 0);
+    let totlen = u16::from_be_bytes([frag[IPHDR_TOTLEN_OFF], frag[IPHDR_TOTLEN_OFF + 1]]);
+    assert_eq!(totlen as usize, frag.len());
+    let field = u16::from_be_bytes([frag[IPHDR_FRAG_OFF], frag[IPHDR_FRAG_OFF + 1]]);
+    let off = (field & IPHDR_FRAG_MASK) as usize * FRAG_UNIT;
+    (off, field & IPHDR_MF != 0, ihl, &frag[ihl..])
+}
+
+#[test]
+fn fragment_offsets() {
+    let mut counters = Counters::new("fragment_offsets").unwrap();
+    let mut harness = fwd_harness(&mut counters, 576);
+
+    // 576 - 20 rounded down to a multiple of 8 is 552 bytes of payload per fragment
+    let data = payload(1400);
+    let out = send(
+        &mut harness,
+        &ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, 0), &data),
+    );
+    let frags = fragments(&out);
+    assert_eq!(frags.len(), 3);
+    let expect = [(0, true, 552), (552, true, 552), (1104, false, 296)];
+    for (frag, (off, mf, len)) in frags.iter().zip(expect.iter()) {
+        let (frag_off, frag_mf, ihl, data_part) = frag_fields(frag);
+        assert_eq!((frag_off, frag_mf, ihl), (*off, *mf, IPHDR_MIN_LEN));
+        assert_eq!(data_part, &data[*off..*off + *len]);
+        assert_eq!(frag[IPHDR_TTL_OFF], 63);
+    }
+    assert_eq!(
+        count("fragment_offsets", CounterType::Pkts, "fragmented"),
+        1
+    );
+    assert_eq!(count("fragment_offsets", CounterType::Pkts, "fragments"), 3);
+
+    // Fragmenting a fragment (at 1400, MF set) keeps the offsets in the original packet,
+    // and MF stays set on the last piece
+    let frag = IPHDR_MF | (1400 / FRAG_UNIT) as u16;
+    let out = send(
+        &mut harness,
+        &ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, frag), &data),
+    );
+    let frags = fragments(&out);
+    assert_eq!(frags.len(), 3);
+    let expect = [(1400, true), (1952, true), (2504, true)];
+    for (frag, (off, mf)) in frags.iter().zip(expect.iter()) {
+        let (frag_off, frag_mf, _, _) = frag_fields(frag);
+        assert_eq!((frag_off, frag_mf), (*off, *mf));
+    }
+}
+
+#[test]
+fn fragment_odd_payload() {
+    let mut counters = Counters::new("fragment_odd_payload").unwrap();
+    let mut harness = fwd_harness(&mut counters, 576);
+
+    // Only the last fragment can have a payload that is not a multiple of 8
+    let data = payload(1001);
+    let out = send(
+        &mut harness,
+        &ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, 0), &data),
+    );
+    let frags = fragments(&out);
+    assert_eq!(frags.len(), 2);
+    let (off, mf, _, first) = frag_fields(&frags[0]);
+    assert_eq!((off, mf, first.len()), (0, true, 552));
+    let (off, mf, _, last) = frag_fields(&frags[1]);
+    assert_eq!((off, mf, last.len()), (552, false, 449));
+    let mut whole = first.to_vec();
+    whole.extend_from_slice(last);
+    assert_eq!(whole, data);
+}
+
+#[test]
+fn fragment_options() {
+    let mut counters = Counters::new("fragment_options").unwrap();
+    let mut harness = fwd_harness(&mut counters, 576);
+
+    // A no-op, a record route (not copied) and a router alert (copied), then the end
+    let mut opts = vec![IPOPT_NOP, 7, 7, 4, 0, 0, 0, 0, 0x94, 4, 0, 0, IPOPT_END];
+    opts.resize(16, 0);
+    assert_eq!(frag_options(&opts), vec![0x94, 4, 0, 0]);
+
+    // The first fragment has all the options, the others only the router alert
+    let data = payload(1000);
+    let hdr = Ipv4Hdr {
+        opts: opts.clone(),
+        ..ipv4_hdr(IP_PROTO_UDP, 64, 0)
+    };
+    let out = send(&mut harness, &ipv4_pkt(&hdr, &data));
+    let frags = fragments(&out);
+    assert_eq!(frags.len(), 2);
+    let (off, _, ihl, first) = frag_fields(&frags[0]);
+    assert_eq!(ihl, IPHDR_MIN_LEN + 16);
+    assert_eq!(frags[0][IPHDR_MIN_LEN..ihl], opts[..]);
+    assert_eq!(first, &data[off..off + first.len()]);
+    let (off, mf, ihl, last) = frag_fields(&frags[1]);
+    assert_eq!(ihl, IPHDR_MIN_LEN + 4);
+    assert_eq!(frags[1][IPHDR_MIN_LEN..ihl], [0x94, 4, 0, 0]);
+    assert_eq!((off, mf), (first.len(), false));
+    assert_eq!(last, &data[off..]);
+
+    // Copied options are padded out to a multiple of 4 bytes
+    assert_eq!(frag_options(&[0x83, 3, 4]), vec![0x83, 3, 4, IPOPT_END]);
+}
+
+#[test]
+fn fragment_df() {
+    let mut counters = Counters::new("fragment_df").unwrap();
+    let mut harness = fwd_harness(&mut counters, 576);
+
+    // Too big with DF set, a fragmentation needed with the MTU goes back to the source
+    let pkt = ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, IPHDR_DF), &payload(1000));
+    let out = send(&mut harness, &pkt);
+    assert_eq!(out.len(), 1);
+    let (next, icmp) = &out[0];
+    assert_eq!(next, names::ENCAPMUX);
+    assert_eq!(icmp.out_ifindex, IN_IFINDEX);
+    assert_eq!(icmp.out_l3addr, SRC);
+    let bytes = pkt_bytes(icmp);
+    assert_eq!(ipv4_addr(&bytes[IPHDR_DADDR_OFF..]), SRC);
+    let icmp = &bytes[IPHDR_MIN_LEN..];
+    assert_eq!(icmp[ICMP_TYPE_OFF], ICMP_DEST_UNREACH);
+    assert_eq!(icmp[ICMP_CODE_OFF], ICMP_FRAG_NEEDED);
+    let mtu = u16::from_be_bytes([icmp[ICMP_MTU_OFF], icmp[ICMP_MTU_OFF + 1]]);
+    assert_eq!(mtu, 576);
+    assert_eq!(ip_checksum(icmp), 0);
+    // The error quotes the header and the first 8 bytes of the packet
+    assert_eq!(icmp[ICMP_HDR_LEN..], pkt[0..IPHDR_MIN_LEN + ICMP_QUOTE_LEN]);
+    assert_eq!(count("fragment_df", CounterType::Pkts, "frag_needed"), 1);
+    assert_eq!(count("fragment_df", CounterType::Pkts, "fragmented"), 0);
+}
+
+#[test]
+fn icmp_ratelimit() {
+    let mut counters = Counters::new("icmp_ratelimit").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+    let test = "icmp_ratelimit";
+
+    // A burst of errors goes out, the rest are suppressed till the bucket fills up again
+    let pkt = ipv4_pkt(
+        &ipv4_hdr(IP_PROTO_ICMP, 1, 0),
+        &icmp_payload(ICMP_ECHO_REQUEST),
+    );
+    let pkts = (0..ICMP_BURST + 1)
+        .map(|_| {
+            let mut p = harness.pkt(&pkt, IPHDR_MIN_LEN);
+            p.in_ifindex = IN_IFINDEX;
+            p
+        })
+        .collect();
+    let out = harness.run(pkts);
+    assert_eq!(out.len(), ICMP_BURST as usize);
+    assert_eq!(count(test, CounterType::Pkts, "icmp_ratelimit"), 1);
+    assert_eq!(
+        count(test, CounterType::Pkts, "ttl_expired"),
+        ICMP_BURST + 1
+    );
+
+    // Errors of all kinds count against the same bucket
+    let unreach = ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, IPHDR_DF), &vec![0; ETHER_MTU]);
+    assert!(send(&mut harness, &unreach).is_empty());
+    assert_eq!(count(test, CounterType::Pkts, "icmp_ratelimit"), 2);
+
+    // One more error a millisecond later
+    CLOCK.fetch_add(1000 / ICMP_RATE, Ordering::Relaxed);
+    assert_eq!(send(&mut harness, &pkt).len(), 1);
+    assert!(send(&mut harness, &pkt).is_empty());
+    assert_eq!(count(test, CounterType::Pkts, "icmp_ratelimit"), 3);
+}
+
+const VRF: usize = 1;
+const VRF_IFINDEX: usize = 3;
+const VRF_NHOP: Ipv4Addr = Ipv4Addr::new(30, 1, 1, 1);
+
+// Put the interface VRF_IFINDEX in VRF, and give VRF a table where the subnet of DST is
+// through VRF_NHOP on that same interface
+fn vrf_setup(harness: &mut Harness<R2Msg>) {
+    let mut intf = Interface::new("vrf", VRF_IFINDEX, vec![0; 6], 0);
+    intf.set_v4addr(Ipv4Addr::new(30, 1, 1, 2), 24);
+    intf.vrf = VRF;
+    let intf = Arc::new(intf);
+    assert!(harness.control_msg(R2Msg::ModifyInterface(ModifyInterfaceMsg { intf })));
+    let mut table = IPv4Table::new();
+    let adj = Adjacency::new(VRF_NHOP, VRF_IFINDEX);
+    let leaf = IPv4Leaf::new(Fwd::Adjacency(Arc::new(adj)));
+    table.add(Ipv4Addr::new(20, 1, 1, 0), 24, Arc::new(leaf));
+    let msg = IPv4TableMsg::new(VRF, Arc::new(table));
+    assert!(harness.control_msg(R2Msg::IPv4TableAdd(msg)));
+}
+
+#[test]
+fn vrf_ingress() {
+    let mut counters = Counters::new("fwd_vrf_ingress").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+    vrf_setup(&mut harness);
+
+    // The same prefix leads to a different adjacency in each VRF, the VRF of the interface
+    // the packet came in on decides which
+    let pkt = ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, 0), &[0; 8]);
+    let out = send(&mut harness, &pkt);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].1.out_ifindex, OUT_IFINDEX);
+    assert_eq!(out[0].1.out_l3addr, NHOP);
+    let out = send_on(&mut harness, &pkt, VRF_IFINDEX);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].1.out_ifindex, VRF_IFINDEX);
+    assert_eq!(out[0].1.out_l3addr, VRF_NHOP);
+
+    // The routes of the default VRF are not there in the other VRF, and neither is a route
+    // back to SRC for the unreachable about the packet
+    let hdr = Ipv4Hdr {
+        dst: Ipv4Addr::new(10, 1, 1, 3),
+        ..ipv4_hdr(IP_PROTO_UDP, 64, 0)
+    };
+    let pkt = ipv4_pkt(&hdr, &[0; 8]);
+    assert_eq!(send(&mut harness, &pkt)[0].1.out_ifindex, IN_IFINDEX);
+    assert!(send_on(&mut harness, &pkt, VRF_IFINDEX).is_empty());
+    assert_eq!(count("fwd_vrf_ingress", CounterType::Pkts, "no_route"), 2);
+}
+
+#[test]
+fn vrf_table_update() {
+    let mut counters = Counters::new("fwd_vrf_table_update").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+    vrf_setup(&mut harness);
+
+    // A new table for one VRF leaves the other VRFs alone
+    let mut table = IPv4Table::new();
+    let adj = Adjacency::new(VRF_NHOP, VRF_IFINDEX);
+    let leaf = IPv4Leaf::new(Fwd::Adjacency(Arc::new(adj)));
+    table.add(Ipv4Addr::new(40, 1, 1, 0), 24, Arc::new(leaf));
+    let msg = IPv4TableMsg::new(VRF, Arc::new(table));
+    assert!(harness.control_msg(R2Msg::IPv4TableAdd(msg)));
+    let pkt = ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 64, 0), &[0; 8]);
+    assert!(send_on(&mut harness, &pkt, VRF_IFINDEX).is_empty());
+    let out = send(&mut harness, &pkt);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].1.out_l3addr, NHOP);
+    let hdr = Ipv4Hdr {
+        dst: Ipv4Addr::new(40, 1, 1, 2),
+        ..ipv4_hdr(IP_PROTO_UDP, 64, 0)
+    };
+    let out = send_on(&mut harness, &ipv4_pkt(&hdr, &[0; 8]), VRF_IFINDEX);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].1.out_l3addr, VRF_NHOP);
+}
@@ -0,0 +1,153 @@
+use counters::{flavors::Counter, flavors::CounterType, Counters};
+use fwd::{ip_checksum, ip_checksum_update};
+use fwd::{ICMP_CSUM_OFF, ICMP_ECHO_REPLY, ICMP_ECHO_REQUEST, ICMP_HDR_LEN, ICMP_TYPE_OFF};
+use fwd::{IPHDR_CSUM_OFF, IPHDR_DADDR_OFF, IPHDR_FRAG_MASK, IPHDR_FRAG_OFF, IPHDR_MF};
+use fwd::{IPHDR_PROTO_OFF, IPHDR_SADDR_OFF, IPHDR_TTL_OFF, IP_DEFAULT_TTL, IP_PROTO_ICMP};
+use graph::Dispatch;
+use graph::Gclient;
+use log::Logger;
+use msg::R2Msg;
+use packet::BoxPkt;
+use std::sync::Arc;
+
+#[derive(Copy, Clone)]
+enum Next {
+    Drop = 0,
+    L3Ipv4Fwd,
+    L3Ipv4Reass,
+}
+
+const NEXT_NAMES: &[Next] = &[Next::Drop, Next::L3Ipv4Fwd, Next::L3Ipv4Reass];
+
+fn next_name(next: Next) -> String {
+    match next {
+        Next::Drop => names::DROP.to_string(),
+        Next::L3Ipv4Fwd => names::L3_IPV4_FWD.to_string(),
+        Next::L3Ipv4Reass => names::L3_IPV4_REASS.to_string(),
+    }
+}
+
+struct LocalCnt {
+    echo_reply: Counter,
+    bad_icmp: Counter,
+    icmp_unknown: Counter,
+    proto_unknown: Counter,
+    fragments: Counter,
+}
+
+impl LocalCnt {
+    fn new(counters: &mut Counters) -> LocalCnt {
+        let name = names::L3_IPV4_LOCAL;
+        LocalCnt {
+            echo_reply: Counter::new(counters, name, CounterType::Pkts, "echo_reply"),
+            bad_icmp: Counter::new(counters, name, CounterType::Error, "bad_icmp"),
+            icmp_unknown: Counter::new(counters, name, CounterType::Pkts, "icmp_unknown"),
+            proto_unknown: Counter::new(counters, name, CounterType::Pkts, "proto_unknown"),
+            fragments: Counter::new(counters, name, CounterType::Pkts, "fragments"),
+        }
+    }
+}
+
+// The ip4-local node gets the packets the forwarding node finds are addressed to R2 itself,
+// ie to one of the addresses of the interfaces. All R2 has to say to the outside world today
+// is to reply to pings - ICMP echo requests are turned around into echo replies in place and
+// sent back to the forwarding node, everything else is counted and dropped. Fragments go to
+// the reassembly node, which sends the whole packet back here once it has all the fragments
+pub struct IPv4Local {
+    cnt: LocalCnt,
+}
+
+impl IPv4Local {
+    pub fn new(counters: &mut Counters) -> IPv4Local {
+        IPv4Local {
+            cnt: LocalCnt::new(counters),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        names::L3_IPV4_LOCAL.to_string()
+    }
+
+    pub fn next_names(&self) -> Vec<String> {
+        let mut v = Vec::new();
+        for n in NEXT_NAMES {
+            assert_eq!(*n as usize, v.len());
+            v.push(next_name(*n));
+        }
+        v
+    }
+
+    fn icmp(&mut self, vectors: &mut Dispatch, mut p: BoxPkt) {
+        let (iphdr, ihl) = p.get_l3();
+        let first = p.data(0).map_or(0, |(d, _)| d.len());
+        if first < ihl + ICMP_HDR_LEN {
+            vectors.trace(&p, || "icmp header too short".to_string());
+            self.cnt.bad_icmp.incr();
+            return;
+        }
+        let icmp_type = iphdr[ihl + ICMP_TYPE_OFF];
+        if icmp_type != ICMP_ECHO_REQUEST {
+            vectors.trace(&p, || format!("icmp type {}", icmp_type));
+            self.cnt.icmp_unknown.incr();
+            return;
+        }
+        echo_reply(&mut p);
+        vectors.trace(&p, || "icmp echo reply".to_string());
+        self.cnt.echo_reply.incr();
+        vectors.push(Next::L3Ipv4Fwd as usize, p);
+    }
+}
+
+// Turn an echo request into a reply: swap the addresses and change the ICMP type. Only the
+// type changes in the ICMP message so its checksum is just updated for that, without going
+// over the data which can be spread across particles
+fn echo_reply(p: &mut BoxPkt) {
+    let (iphdr, ihl) = p.get_l3_mut();
+    for i in 0..4 {
+        iphdr.swap(IPHDR_SADDR_OFF + i, IPHDR_DADDR_OFF + i);
+    }
+    iphdr[IPHDR_TTL_OFF] = IP_DEFAULT_TTL;
+    iphdr[IPHDR_CSUM_OFF] = 0;
+    iphdr[IPHDR_CSUM_OFF + 1] = 0;
+    let csum = ip_checksum(&iphdr[0..ihl]);
+    iphdr[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+    let icmp = &mut iphdr[ihl..];
+    let old = u16::from_be_bytes([icmp[ICMP_TYPE_OFF], icmp[ICMP_TYPE_OFF + 1]]);
+    icmp[ICMP_TYPE_OFF] = ICMP_ECHO_REPLY;
+    let new = u16::from_be_bytes([icmp[ICMP_TYPE_OFF], icmp[ICMP_TYPE_OFF + 1]]);
+    let csum = u16::from_be_bytes([icmp[ICMP_CSUM_OFF], icmp[ICMP_CSUM_OFF + 1]]);
+    let csum = ip_checksum_update(csum, old, new);
+    icmp[ICMP_CSUM_OFF..ICMP_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+}
+
+impl Gclient<R2Msg> for IPv4Local {
+    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
+        Box::new(IPv4Local {
+            cnt: LocalCnt::new(counters),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        while let Some(p) = vectors.pop() {
+            let (iphdr, _) = p.get_l3();
+            let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+            if frag & (IPHDR_MF | IPHDR_FRAG_MASK) != 0 {
+                vectors.trace(&p, || "fragment".to_string());
+                self.cnt.fragments.incr();
+                vectors.push(Next::L3Ipv4Reass as usize, p);
+                continue;
+            }
+            let proto = iphdr[IPHDR_PROTO_OFF];
+            if proto == IP_PROTO_ICMP {
+                self.icmp(vectors, p);
+            } else {
+                vectors.trace(&p, || format!("proto {} not handled", proto));
+                self.cnt.proto_unknown.incr();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,90 @@
+use super::*;
+use fwd::harness::{ipv4_pkt, Ipv4Hdr};
+use fwd::{IPHDR_MIN_LEN, IP_PROTO_UDP};
+use graph::harness::{self, pkt_bytes, Harness};
+use std::net::Ipv4Addr;
+
+const SRC: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 2);
+const DST: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 1);
+
+fn local_harness(counters: &mut Counters) -> Harness<R2Msg> {
+    graph::harness!(IPv4Local::new(counters), counters)
+}
+
+// An IPv4 packet from SRC to DST
+fn ipv4_hdr(proto: u8, ttl: u8, frag: u16) -> Ipv4Hdr {
+    Ipv4Hdr {
+        ttl,
+        frag,
+        ..Ipv4Hdr::new(SRC, DST, proto)
+    }
+}
+
+// An ICMP echo request with some data and the right checksum
+fn echo_request() -> Vec<u8> {
+    let mut icmp = vec![0; ICMP_HDR_LEN];
+    icmp[ICMP_TYPE_OFF] = ICMP_ECHO_REQUEST;
+    // Identifier and sequence number
+    icmp[4..8].copy_from_slice(&[0x12, 0x34, 0x00, 0x01]);
+    icmp.extend((0..56).map(|i| i as u8));
+    let csum = ip_checksum(&icmp);
+    icmp[ICMP_CSUM_OFF..ICMP_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+    icmp
+}
+
+fn count(test: &str, name: &str) -> u64 {
+    harness::count(test, names::L3_IPV4_LOCAL, CounterType::Pkts, name)
+}
+
+#[test]
+fn echo() {
+    let mut counters = Counters::new("local_echo").unwrap();
+    let mut harness = local_harness(&mut counters);
+
+    let request = echo_request();
+    let pkt = ipv4_pkt(&ipv4_hdr(IP_PROTO_ICMP, 5, 0), &request);
+    let pkt = harness.pkt(&pkt, IPHDR_MIN_LEN);
+    let out = harness.run(vec![pkt]);
+    assert_eq!(out.len(), 1);
+    let (next, reply) = &out[0];
+    assert_eq!(next, names::L3_IPV4_FWD);
+
+    // Addresses swapped, TTL reset and both checksums right
+    let bytes = pkt_bytes(reply);
+    let (iphdr, icmp) = bytes.split_at(IPHDR_MIN_LEN);
+    assert_eq!(iphdr[IPHDR_SADDR_OFF..IPHDR_SADDR_OFF + 4], DST.octets());
+    assert_eq!(iphdr[IPHDR_DADDR_OFF..IPHDR_DADDR_OFF + 4], SRC.octets());
+    assert_eq!(iphdr[IPHDR_TTL_OFF], IP_DEFAULT_TTL);
+    assert_eq!(ip_checksum(iphdr), 0);
+    assert_eq!(icmp[ICMP_TYPE_OFF], ICMP_ECHO_REPLY);
+    assert_eq!(ip_checksum(icmp), 0);
+    // The rest of the message is echoed back as is
+    assert_eq!(icmp[4..], request[4..]);
+    assert_eq!(count("local_echo", "echo_reply"), 1);
+}
+
+#[test]
+fn fragments_to_reass() {
+    let mut counters = Counters::new("local_fragments").unwrap();
+    let mut harness = local_harness(&mut counters);
+
+    // The first fragment (MF set) and the last one (offset set) both go to reassembly
+    let request = echo_request();
+    let first = ipv4_pkt(&ipv4_hdr(IP_PROTO_ICMP, 5, IPHDR_MF), &request[0..32]);
+    let last = ipv4_pkt(&ipv4_hdr(IP_PROTO_ICMP, 5, 32 / 8), &request[32..]);
+    let first = harness.pkt(&first, IPHDR_MIN_LEN);
+    let last = harness.pkt(&last, IPHDR_MIN_LEN);
+    let out = harness.run(vec![first, last]);
+    assert_eq!(out.len(), 2);
+    for (next, _) in out.iter() {
+        assert_eq!(next, names::L3_IPV4_REASS);
+    }
+    assert_eq!(count("local_fragments", "fragments"), 2);
+    assert_eq!(count("local_fragments", "echo_reply"), 0);
+
+    // Other protocols are dropped
+    let udp = ipv4_pkt(&ipv4_hdr(IP_PROTO_UDP, 5, 0), &[0; 8]);
+    let udp = harness.pkt(&udp, IPHDR_MIN_LEN);
+    assert!(harness.run(vec![udp]).is_empty());
+    assert_eq!(count("local_fragments", "proto_unknown"), 1);
+}
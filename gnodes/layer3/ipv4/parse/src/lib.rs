@@ -1,9 +1,14 @@
 use counters::flavors::{Counter, CounterType};
 use counters::Counters;
-use fwd::IPHDR_MIN_LEN;
+use fwd::ip_checksum;
+use fwd::{IPHDR_DADDR_OFF, IPHDR_MIN_LEN, IPHDR_SADDR_OFF, IPHDR_TOTLEN_OFF};
+use graph::feature::FeatureArc;
 use graph::Dispatch;
 use graph::Gclient;
 use log::Logger;
+use msg::R2Msg;
+use packet::BoxPkt;
+use std::net::Ipv4Addr;
 use std::sync::Arc;
 
 #[derive(Copy, Clone)]
@@ -21,22 +26,59 @@ fn next_name(next: Next) -> String {
     }
 }
 
+// Why a packet failed the header checks
+enum Invalid {
+    Short,
+    Version,
+    Ihl,
+    Length,
+    Checksum,
+    MartianSrc,
+    MartianDst,
+}
+
+struct ParseCnt {
+    bad_pkt: Counter,
+    bad_version: Counter,
+    bad_ihl: Counter,
+    bad_len: Counter,
+    bad_csum: Counter,
+    martian_src: Counter,
+    martian_dst: Counter,
+}
+
+impl ParseCnt {
+    fn new(counters: &mut Counters) -> ParseCnt {
+        let name = names::L3_IPV4_PARSE;
+        ParseCnt {
+            bad_pkt: Counter::new(counters, name, CounterType::Error, "bad_pkt"),
+            bad_version: Counter::new(counters, name, CounterType::Error, "bad_version"),
+            bad_ihl: Counter::new(counters, name, CounterType::Error, "bad_ihl"),
+            bad_len: Counter::new(counters, name, CounterType::Error, "bad_len"),
+            bad_csum: Counter::new(counters, name, CounterType::Error, "bad_csum"),
+            martian_src: Counter::new(counters, name, CounterType::Error, "martian_src"),
+            martian_dst: Counter::new(counters, name, CounterType::Error, "martian_dst"),
+        }
+    }
+}
+
 // The parse node is assumed to get a layer3 packet as input, and its role is to redirect
 // the packet to the appropriate layer3 feature node (like v4, v6 or gre or mpls etc..).
-// All it handles today is ipv4
+// All it handles today is ipv4. The parse node is the start of the ip4-input feature arc,
+// packets on interfaces with input features enabled go through those features before
+// reaching the forwarding node. The parse node also makes sure that the nodes after it
+// see only sane ipv4 headers, see validate()
 pub struct IPv4Parse {
-    bad_pkt: Counter,
+    cnt: ParseCnt,
+    input: FeatureArc,
 }
 
 impl IPv4Parse {
-    pub fn new(counters: &mut Counters) -> IPv4Parse {
-        let bad_pkt = Counter::new(
-            counters,
-            names::L3_IPV4_PARSE,
-            CounterType::Error,
-            "bad_pkt",
-        );
-        IPv4Parse { bad_pkt }
+    pub fn new(input: FeatureArc, counters: &mut Counters) -> IPv4Parse {
+        IPv4Parse {
+            cnt: ParseCnt::new(counters),
+            input,
+        }
     }
 
     pub fn name(&self) -> String {
@@ -49,23 +91,117 @@ impl IPv4Parse {
             assert_eq!(*n as usize, v.len());
             v.push(next_name(*n));
         }
+        v.append(&mut self.input.next_names());
         v
     }
 }
 
-impl<T> Gclient<T> for IPv4Parse {
-    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<T>> {
-        let bad_pkt = Counter::new(counters, &self.name(), CounterType::Error, "bad_pkt");
-        Box::new(IPv4Parse { bad_pkt })
+fn ipv4_addr(bytes: &[u8]) -> Ipv4Addr {
+    Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])
+}
+
+// A source address no packet can come from: this network (0/8), loopback (127/8), multicast,
+// and the reserved class E addresses (240/4) including the limited broadcast
+fn martian_src(addr: Ipv4Addr) -> bool {
+    let first = addr.octets()[0];
+    first == 0 || addr.is_loopback() || addr.is_multicast() || first >= 240
+}
+
+// A destination address no packet can go to: this network (0/8), loopback (127/8) and the
+// reserved class E addresses (240/4), the limited broadcast is fine as a destination
+fn martian_dst(addr: Ipv4Addr) -> bool {
+    let first = addr.octets()[0];
+    first == 0 || addr.is_loopback() || (first >= 240 && !addr.is_broadcast())
+}
+
+// The checks on a received ipv4 header from RFC 1812 section 5.2.2: the packet is long
+// enough for a header, the version is 4, the header length (IHL) is at least the minimum
+// header length, the total length covers the header and is not more than the packet and
+// the header checksum is right. Packets with martian addresses (section 5.3.7) are invalid
+// too, see martian_src() and martian_dst(). The l3 header length is set to the IHL to
+// include options, and anything after the total length (like ethernet padding) is trimmed
+// off the packet. The whole header, with options, is expected to be in the first particle
+fn validate(p: &mut BoxPkt) -> Result<(), Invalid> {
+    if !p.set_l3(IPHDR_MIN_LEN) {
+        return Err(Invalid::Short);
+    }
+    let (iphdr, _) = p.get_l3();
+    if iphdr[0] >> 4 != 4 {
+        return Err(Invalid::Version);
+    }
+    let ihl = ((iphdr[0] & 0xf) as usize) * 4;
+    if ihl < IPHDR_MIN_LEN {
+        return Err(Invalid::Ihl);
+    }
+    let totlen = u16::from_be_bytes([iphdr[IPHDR_TOTLEN_OFF], iphdr[IPHDR_TOTLEN_OFF + 1]]);
+    let totlen = totlen as usize;
+    if totlen < ihl || totlen > p.len() {
+        return Err(Invalid::Length);
+    }
+    if !p.set_l3(ihl) {
+        return Err(Invalid::Short);
+    }
+    let (iphdr, _) = p.get_l3();
+    if ip_checksum(&iphdr[0..ihl]) != 0 {
+        return Err(Invalid::Checksum);
+    }
+    if martian_src(ipv4_addr(&iphdr[IPHDR_SADDR_OFF..])) {
+        return Err(Invalid::MartianSrc);
+    }
+    if martian_dst(ipv4_addr(&iphdr[IPHDR_DADDR_OFF..])) {
+        return Err(Invalid::MartianDst);
+    }
+    if p.len() > totlen {
+        p.truncate(totlen);
+    }
+    Ok(())
+}
+
+impl Gclient<R2Msg> for IPv4Parse {
+    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
+        Box::new(IPv4Parse {
+            cnt: ParseCnt::new(counters),
+            input: self.input.clone(),
+        })
     }
 
     fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
         while let Some(mut p) = vectors.pop() {
-            if p.set_l3(IPHDR_MIN_LEN) {
-                vectors.push(Next::L3Ipv4Fwd as usize, p);
-            } else {
-                self.bad_pkt.incr();
+            match validate(&mut p) {
+                Ok(()) => {
+                    if self.input.enabled(&p) {
+                        let next = NEXT_NAMES.len() + self.input.next(&p, 0);
+                        vectors.push(next, p);
+                    } else {
+                        vectors.push(Next::L3Ipv4Fwd as usize, p);
+                    }
+                }
+                Err(why) => {
+                    let (reason, cnt) = match why {
+                        Invalid::Short => ("bad packet", &mut self.cnt.bad_pkt),
+                        Invalid::Version => ("bad version", &mut self.cnt.bad_version),
+                        Invalid::Ihl => ("bad ihl", &mut self.cnt.bad_ihl),
+                        Invalid::Length => ("bad length", &mut self.cnt.bad_len),
+                        Invalid::Checksum => ("bad checksum", &mut self.cnt.bad_csum),
+                        Invalid::MartianSrc => ("martian source", &mut self.cnt.martian_src),
+                        Invalid::MartianDst => ("martian destination", &mut self.cnt.martian_dst),
+                    };
+                    vectors.trace(&p, || reason.to_string());
+                    cnt.incr();
+                }
             }
         }
     }
+
+    fn control_msg(&mut self, _thread: usize, message: R2Msg) -> bool {
+        if let R2Msg::FeatureEnable(feature) = message {
+            self.input
+                .enable(feature.ifindex, &feature.feature, feature.enable)
+        } else {
+            false
+        }
+    }
 }
+
+#[cfg(test)]
+mod test;
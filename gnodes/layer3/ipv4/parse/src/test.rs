@@ -0,0 +1,139 @@
+use super::*;
+use fwd::harness::{ipv4_checksum, ipv4_pkt, Ipv4Hdr};
+use fwd::{IPHDR_CSUM_OFF, IP_PROTO_UDP};
+use graph::harness::{self, Harness};
+
+const SRC: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 2);
+const DST: Ipv4Addr = Ipv4Addr::new(20, 1, 1, 2);
+
+fn parse_harness(counters: &mut Counters) -> Harness<R2Msg> {
+    let input = FeatureArc::new(
+        names::IP4_INPUT,
+        names::L3_IPV4_PARSE,
+        names::L3_IPV4_FWD,
+        false,
+    );
+    graph::harness!(IPv4Parse::new(input, counters), counters)
+}
+
+// A UDP IPv4 packet with 'payload' bytes of payload
+fn udp_pkt(src: Ipv4Addr, dst: Ipv4Addr, payload: usize) -> Vec<u8> {
+    ipv4_pkt(&Ipv4Hdr::new(src, dst, IP_PROTO_UDP), &vec![0xaa; payload])
+}
+
+fn count(test: &str, name: &str) -> u64 {
+    harness::count(test, names::L3_IPV4_PARSE, CounterType::Error, name)
+}
+
+// Run the packet through the parse node, it should be dropped with just the counter 'cntr'
+// going up
+fn dropped(test: &str, pkt: &[u8], cntr: &str) {
+    let mut counters = Counters::new(test).unwrap();
+    let mut harness = parse_harness(&mut counters);
+    let pkt = harness.pkt(pkt, 0);
+    assert!(harness.run(vec![pkt]).is_empty());
+    let all = [
+        "bad_pkt",
+        "bad_version",
+        "bad_ihl",
+        "bad_len",
+        "bad_csum",
+        "martian_src",
+        "martian_dst",
+    ];
+    for name in all.iter() {
+        let expect = if *name == cntr { 1 } else { 0 };
+        assert_eq!(count(test, name), expect, "counter {}", name);
+    }
+}
+
+#[test]
+fn valid() {
+    let mut counters = Counters::new("parse_valid").unwrap();
+    let mut harness = parse_harness(&mut counters);
+
+    // Options are part of the l3 header, and the ethernet padding is trimmed off
+    let hdr = Ipv4Hdr {
+        opts: vec![1, 1, 1, 0],
+        ..Ipv4Hdr::new(SRC, DST, IP_PROTO_UDP)
+    };
+    let mut pkt = ipv4_pkt(&hdr, &[0xaa; 8]);
+    pkt.extend_from_slice(&[0; 14]);
+    let p = harness.pkt(&pkt, 0);
+    let out = harness.run(vec![p]);
+    assert_eq!(out.len(), 1);
+    let (next, p) = &out[0];
+    assert_eq!(next, names::L3_IPV4_FWD);
+    assert_eq!(p.get_l3().1, 24);
+    assert_eq!(p.len(), 32);
+}
+
+#[test]
+fn short() {
+    dropped("parse_short", &udp_pkt(SRC, DST, 0)[0..16], "bad_pkt");
+}
+
+#[test]
+fn version() {
+    let mut pkt = udp_pkt(SRC, DST, 8);
+    pkt[0] = 0x65;
+    ipv4_checksum(&mut pkt);
+    dropped("parse_version", &pkt, "bad_version");
+}
+
+#[test]
+fn ihl() {
+    let mut pkt = udp_pkt(SRC, DST, 8);
+    pkt[0] = 0x44;
+    dropped("parse_ihl", &pkt, "bad_ihl");
+}
+
+#[test]
+fn total_length() {
+    // More than the packet, and less than the header
+    let mut pkt = udp_pkt(SRC, DST, 8);
+    pkt[IPHDR_TOTLEN_OFF..IPHDR_TOTLEN_OFF + 2].copy_from_slice(&29u16.to_be_bytes());
+    ipv4_checksum(&mut pkt);
+    dropped("parse_long", &pkt, "bad_len");
+    pkt[IPHDR_TOTLEN_OFF..IPHDR_TOTLEN_OFF + 2].copy_from_slice(&19u16.to_be_bytes());
+    ipv4_checksum(&mut pkt);
+    dropped("parse_short_len", &pkt, "bad_len");
+}
+
+#[test]
+fn bad_checksum() {
+    let mut pkt = udp_pkt(SRC, DST, 8);
+    pkt[IPHDR_CSUM_OFF] ^= 0xff;
+    dropped("parse_checksum", &pkt, "bad_csum");
+}
+
+#[test]
+fn martian_source() {
+    let srcs = [
+        Ipv4Addr::new(0, 1, 2, 3),
+        Ipv4Addr::new(127, 0, 0, 1),
+        Ipv4Addr::new(224, 0, 0, 5),
+        Ipv4Addr::new(240, 0, 0, 1),
+        Ipv4Addr::BROADCAST,
+    ];
+    for (i, src) in srcs.iter().enumerate() {
+        let test = format!("parse_martian_src{}", i);
+        dropped(&test, &udp_pkt(*src, DST, 8), "martian_src");
+    }
+}
+
+#[test]
+fn martian_destination() {
+    let dsts = [
+        Ipv4Addr::new(0, 1, 2, 3),
+        Ipv4Addr::new(127, 0, 0, 1),
+        Ipv4Addr::new(240, 0, 0, 1),
+    ];
+    for (i, dst) in dsts.iter().enumerate() {
+        let test = format!("parse_martian_dst{}", i);
+        dropped(&test, &udp_pkt(SRC, *dst, 8), "martian_dst");
+    }
+    // Multicast and the limited broadcast are fine as destinations
+    assert!(!martian_dst(Ipv4Addr::new(224, 0, 0, 5)));
+    assert!(!martian_dst(Ipv4Addr::BROADCAST));
+}
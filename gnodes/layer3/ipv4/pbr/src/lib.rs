@@ -0,0 +1,160 @@
+use counters::{flavors::Counter, flavors::CounterType, Counters};
+use fwd::pbr::{PbrKey, PbrRule};
+use fwd::{IPHDR_DADDR_OFF, IPHDR_FRAG_MASK, IPHDR_FRAG_OFF, IPHDR_MF, IPHDR_PROTO_OFF};
+use fwd::{IPHDR_SADDR_OFF, IPHDR_TOS_OFF, IP_PROTO_TCP, IP_PROTO_UDP};
+use graph::feature::FeatureArc;
+use graph::Dispatch;
+use graph::Gclient;
+use log::Logger;
+use msg::R2Msg;
+use packet::BoxPkt;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+#[derive(Copy, Clone)]
+enum Next {
+    Drop = 0,
+}
+
+const NEXT_NAMES: &[Next] = &[Next::Drop];
+
+fn next_name(next: Next) -> String {
+    match next {
+        Next::Drop => names::DROP.to_string(),
+    }
+}
+
+struct PbrCnt {
+    matched: Counter,
+    unmatched: Counter,
+}
+
+impl PbrCnt {
+    fn new(counters: &mut Counters) -> PbrCnt {
+        let name = names::L3_IPV4_PBR;
+        PbrCnt {
+            matched: Counter::new(counters, name, CounterType::Pkts, "matched"),
+            unmatched: Counter::new(counters, name, CounterType::Pkts, "unmatched"),
+        }
+    }
+}
+
+fn ipv4_addr(bytes: &[u8]) -> Ipv4Addr {
+    Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])
+}
+
+// The fields of the packet the rules match on. Only the first fragment of a packet has
+// the ports, the rest of the fragments match only rules that do not care about ports
+fn pbr_key(p: &BoxPkt) -> PbrKey {
+    let (iphdr, ihl) = p.get_l3();
+    let proto = iphdr[IPHDR_PROTO_OFF];
+    let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+    let first = p.data(0).map_or(0, |(d, _)| d.len());
+    let (sport, dport) = if (proto == IP_PROTO_TCP || proto == IP_PROTO_UDP)
+        && frag & (IPHDR_MF | IPHDR_FRAG_MASK) == 0
+        && first >= ihl + 4
+    {
+        let sport = u16::from_be_bytes([iphdr[ihl], iphdr[ihl + 1]]);
+        let dport = u16::from_be_bytes([iphdr[ihl + 2], iphdr[ihl + 3]]);
+        (Some(sport), Some(dport))
+    } else {
+        (None, None)
+    };
+    PbrKey {
+        saddr: ipv4_addr(&iphdr[IPHDR_SADDR_OFF..]),
+        daddr: ipv4_addr(&iphdr[IPHDR_DADDR_OFF..]),
+        proto,
+        sport,
+        dport,
+        dscp: iphdr[IPHDR_TOS_OFF] >> 2,
+        ifindex: p.in_ifindex,
+    }
+}
+
+// The policy based routing node, a feature on the ip4-input arc. The packets are matched
+// against the rules in the order of their sequence numbers, and the first rule that
+// matches decides how IPv4Fwd routes the packet - in the route table of the VRF of the
+// rule instead of the VRF of the input interface, and/or towards the nexthop of the rule
+// instead of the destination of the packet. The node only marks the packet (pbr_vrf and
+// pbr_nhop), the route lookup itself is left to IPv4Fwd. Packets that match no rule go
+// on unchanged. The rules are replaced as a whole by the control plane, RCU style like
+// the route tables
+pub struct IPv4Pbr {
+    rules: Arc<Vec<PbrRule>>,
+    input: FeatureArc,
+    // The position of this node on the ip4-input arc
+    pos: usize,
+    cnt: PbrCnt,
+}
+
+impl IPv4Pbr {
+    pub fn new(rules: Arc<Vec<PbrRule>>, input: FeatureArc, counters: &mut Counters) -> IPv4Pbr {
+        let pos = input.position(names::L3_IPV4_PBR).unwrap();
+        IPv4Pbr {
+            rules,
+            input,
+            pos,
+            cnt: PbrCnt::new(counters),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        names::L3_IPV4_PBR.to_string()
+    }
+
+    pub fn next_names(&self) -> Vec<String> {
+        let mut v = Vec::new();
+        for n in NEXT_NAMES {
+            assert_eq!(*n as usize, v.len());
+            v.push(next_name(*n));
+        }
+        v.append(&mut self.input.next_names());
+        v
+    }
+}
+
+impl Gclient<R2Msg> for IPv4Pbr {
+    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
+        Box::new(IPv4Pbr {
+            rules: self.rules.clone(),
+            input: self.input.clone(),
+            pos: self.pos,
+            cnt: PbrCnt::new(counters),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        while let Some(mut p) = vectors.pop() {
+            let key = pbr_key(&p);
+            if let Some(rule) = self.rules.iter().find(|r| r.matches(&key)) {
+                p.pbr_vrf = rule.vrf;
+                if let Some(nhop) = rule.nhop {
+                    p.pbr_nhop = nhop;
+                }
+                vectors.trace(&p, || format!("rule {}", rule.seq));
+                self.cnt.matched.incr();
+            } else {
+                self.cnt.unmatched.incr();
+            }
+            let next = NEXT_NAMES.len() + self.input.next(&p, self.pos);
+            vectors.push(next, p);
+        }
+    }
+
+    fn control_msg(&mut self, _thread: usize, message: R2Msg) -> bool {
+        match message {
+            R2Msg::PbrRules(rules) => {
+                self.rules = rules.rules;
+                true
+            }
+            R2Msg::FeatureEnable(feature) => {
+                self.input
+                    .enable(feature.ifindex, &feature.feature, feature.enable)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,166 @@
+use super::*;
+use fwd::harness::{ipv4_pkt, Ipv4Hdr};
+use fwd::pbr::PbrRulesMsg;
+use fwd::{IPHDR_MIN_LEN, IP_PROTO_ICMP};
+use graph::harness::{self, Harness};
+
+const IN_IFINDEX: usize = 1;
+const SRC: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 2);
+const DST: Ipv4Addr = Ipv4Addr::new(20, 1, 1, 2);
+const NHOP1: Ipv4Addr = Ipv4Addr::new(30, 1, 1, 1);
+const NHOP2: Ipv4Addr = Ipv4Addr::new(40, 1, 1, 1);
+const ZERO: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
+
+fn pbr_harness(counters: &mut Counters, rules: Vec<PbrRule>) -> Harness<R2Msg> {
+    let mut input = FeatureArc::new(
+        names::IP4_INPUT,
+        names::L3_IPV4_PARSE,
+        names::L3_IPV4_FWD,
+        false,
+    );
+    input.register(names::L3_IPV4_PBR);
+    graph::harness!(IPv4Pbr::new(Arc::new(rules), input, counters), counters)
+}
+
+// A rule that matches everything, with just the sequence number and the actions filled in
+fn rule(seq: u32, vrf: Option<usize>, nhop: Option<Ipv4Addr>) -> PbrRule {
+    PbrRule {
+        seq,
+        src: (ZERO, 0),
+        dst: (ZERO, 0),
+        proto: None,
+        sport: None,
+        dport: None,
+        dscp: None,
+        ifindex: None,
+        vrf,
+        nhop,
+    }
+}
+
+// An IPv4 packet with the ports of a TCP/UDP header right after the IP header
+fn ipv4_ports(proto: u8, dscp: u8, frag: u16, sport: u16, dport: u16) -> Vec<u8> {
+    let hdr = Ipv4Hdr {
+        tos: dscp << 2,
+        frag,
+        ..Ipv4Hdr::new(SRC, DST, proto)
+    };
+    let mut ports = sport.to_be_bytes().to_vec();
+    ports.extend_from_slice(&dport.to_be_bytes());
+    ports.resize(8, 0);
+    ipv4_pkt(&hdr, &ports)
+}
+
+// Run the packet through the node, it always goes on to IPv4Fwd, return the marks on it
+fn send(harness: &mut Harness<R2Msg>, pkt: &[u8]) -> (Option<usize>, Ipv4Addr) {
+    let mut p = harness.pkt(pkt, IPHDR_MIN_LEN);
+    p.in_ifindex = IN_IFINDEX;
+    let mut out = harness.run(vec![p]);
+    assert_eq!(out.len(), 1);
+    let (next, p) = out.pop().unwrap();
+    assert_eq!(next, names::L3_IPV4_FWD);
+    (p.pbr_vrf, p.pbr_nhop)
+}
+
+fn count(test: &str, name: &str) -> u64 {
+    harness::count(test, names::L3_IPV4_PBR, CounterType::Pkts, name)
+}
+
+#[test]
+fn match_order() {
+    let mut counters = Counters::new("pbr_match_order").unwrap();
+    // Both rules match, the one with the lower sequence number wins
+    let mut first = rule(10, None, Some(NHOP1));
+    first.proto = Some(IP_PROTO_UDP);
+    let rules = vec![first, rule(20, Some(1), Some(NHOP2))];
+    let mut harness = pbr_harness(&mut counters, rules);
+    let udp = ipv4_ports(IP_PROTO_UDP, 0, 0, 1000, 2000);
+    assert_eq!(send(&mut harness, &udp), (None, NHOP1));
+    let tcp = ipv4_ports(IP_PROTO_TCP, 0, 0, 1000, 2000);
+    assert_eq!(send(&mut harness, &tcp), (Some(1), NHOP2));
+
+    // The rules are replaced as a whole, a packet matching none of them is left alone
+    let mut only = rule(10, Some(2), None);
+    only.proto = Some(IP_PROTO_TCP);
+    let msg = PbrRulesMsg::new(Arc::new(vec![only]));
+    assert!(harness.control_msg(R2Msg::PbrRules(msg)));
+    assert_eq!(send(&mut harness, &udp), (None, ZERO));
+    assert_eq!(send(&mut harness, &tcp), (Some(2), ZERO));
+    assert_eq!(count("pbr_match_order", "matched"), 3);
+    assert_eq!(count("pbr_match_order", "unmatched"), 1);
+}
+
+#[test]
+fn port_ranges() {
+    let mut counters = Counters::new("pbr_port_ranges").unwrap();
+    let mut r = rule(10, None, Some(NHOP1));
+    r.sport = Some((1000, 1010));
+    r.dport = Some((53, 53));
+    let mut harness = pbr_harness(&mut counters, vec![r]);
+
+    // Both ends of the range are included
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 0, 1000, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, NHOP1));
+    let pkt = ipv4_ports(IP_PROTO_TCP, 0, 0, 1010, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, NHOP1));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 0, 999, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 0, 1011, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 0, 1005, 54);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+
+    // No ports in packets other than TCP/UDP, or in fragments after the first
+    let pkt = ipv4_ports(IP_PROTO_ICMP, 0, 0, 1000, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 1, 1000, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, IPHDR_MF, 1000, 53);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+    assert_eq!(count("pbr_port_ranges", "matched"), 2);
+    assert_eq!(count("pbr_port_ranges", "unmatched"), 6);
+}
+
+#[test]
+fn dscp() {
+    let mut counters = Counters::new("pbr_dscp").unwrap();
+    let mut ef = rule(10, Some(3), None);
+    ef.dscp = Some(46);
+    let mut af = rule(20, Some(4), Some(NHOP2));
+    af.dscp = Some(10);
+    let mut harness = pbr_harness(&mut counters, vec![ef, af]);
+
+    let pkt = ipv4_ports(IP_PROTO_UDP, 46, 0, 1000, 2000);
+    assert_eq!(send(&mut harness, &pkt), (Some(3), ZERO));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 10, 0, 1000, 2000);
+    assert_eq!(send(&mut harness, &pkt), (Some(4), NHOP2));
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 0, 1000, 2000);
+    assert_eq!(send(&mut harness, &pkt), (None, ZERO));
+}
+
+#[test]
+fn actions() {
+    let mut counters = Counters::new("pbr_actions").unwrap();
+    // Just a vrf, just a nexthop, and both - matching on the prefixes and input interface
+    let mut vrf = rule(10, Some(5), None);
+    vrf.ifindex = Some(IN_IFINDEX + 1);
+    let mut nhop = rule(20, None, Some(NHOP1));
+    nhop.src = (Ipv4Addr::new(10, 1, 0, 0), 16);
+    nhop.dst = (Ipv4Addr::new(20, 1, 2, 0), 24);
+    let mut both = rule(30, Some(6), Some(NHOP2));
+    both.src = (Ipv4Addr::new(10, 0, 0, 0), 8);
+    let mut harness = pbr_harness(&mut counters, vec![vrf, nhop, both]);
+    let pkt = ipv4_ports(IP_PROTO_UDP, 0, 0, 1000, 2000);
+    assert_eq!(send(&mut harness, &pkt), (Some(6), NHOP2));
+
+    let mut nhop = rule(20, None, Some(NHOP1));
+    nhop.dst = (Ipv4Addr::new(20, 1, 1, 0), 24);
+    let mut vrf = rule(10, Some(5), None);
+    vrf.ifindex = Some(IN_IFINDEX);
+    let msg = PbrRulesMsg::new(Arc::new(vec![nhop.clone()]));
+    assert!(harness.control_msg(R2Msg::PbrRules(msg)));
+    assert_eq!(send(&mut harness, &pkt), (None, NHOP1));
+    let msg = PbrRulesMsg::new(Arc::new(vec![vrf, nhop]));
+    assert!(harness.control_msg(R2Msg::PbrRules(msg)));
+    assert_eq!(send(&mut harness, &pkt), (Some(5), ZERO));
+}
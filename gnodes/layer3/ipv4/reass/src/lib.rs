@@ -0,0 +1,356 @@
+use common::time_msecs;
+use counters::{flavors::Counter, flavors::CounterType, Counters};
+use fwd::ip_checksum;
+use fwd::{IPHDR_CSUM_OFF, IPHDR_DADDR_OFF, IPHDR_FRAG_MASK, IPHDR_FRAG_OFF, IPHDR_MF};
+use fwd::{IPHDR_ID_OFF, IPHDR_MIN_LEN, IPHDR_PROTO_OFF, IPHDR_SADDR_OFF, IPHDR_TOTLEN_OFF};
+use graph::Dispatch;
+use graph::Gclient;
+use log::Logger;
+use msg::R2Msg;
+use packet::BoxPkt;
+use packet::PacketPool;
+use std::collections::{HashMap, VecDeque};
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+#[derive(Copy, Clone)]
+enum Next {
+    Drop = 0,
+    L3Ipv4Local,
+}
+
+const NEXT_NAMES: &[Next] = &[Next::Drop, Next::L3Ipv4Local];
+
+fn next_name(next: Next) -> String {
+    match next {
+        Next::Drop => names::DROP.to_string(),
+        Next::L3Ipv4Local => names::L3_IPV4_LOCAL.to_string(),
+    }
+}
+
+// How long the fragments of a packet are held waiting for the rest of the fragments
+const REASS_TIMEOUT_MSECS: u64 = 30_000;
+// The fragments are packets from the thread's packet pool, so a thread holds on to at most
+// these many fragments (and bytes in them) and reassembles at most these many packets at
+// a time - enough for a few packets of the largest size, and a small part of the pool
+const REASS_MAX_FRAGS: usize = 64;
+const REASS_MAX_BYTES: usize = 96 * 1024;
+const REASS_MAX_PKTS: usize = 16;
+// Fragment offsets are in units of 8 bytes
+const FRAG_UNIT: usize = 8;
+const IP_MAX_LEN: usize = 65535;
+
+struct ReassCnt {
+    reassembled: Counter,
+    timeouts: Counter,
+    overlaps: Counter,
+    duplicates: Counter,
+    bad_frag: Counter,
+    no_mem: Counter,
+    no_buf: Counter,
+}
+
+impl ReassCnt {
+    fn new(counters: &mut Counters) -> ReassCnt {
+        let name = names::L3_IPV4_REASS;
+        ReassCnt {
+            reassembled: Counter::new(counters, name, CounterType::Pkts, "reassembled"),
+            timeouts: Counter::new(counters, name, CounterType::Pkts, "timeouts"),
+            overlaps: Counter::new(counters, name, CounterType::Error, "overlaps"),
+            duplicates: Counter::new(counters, name, CounterType::Pkts, "duplicates"),
+            bad_frag: Counter::new(counters, name, CounterType::Error, "bad_frag"),
+            no_mem: Counter::new(counters, name, CounterType::Error, "no_mem"),
+            no_buf: Counter::new(counters, name, CounterType::Error, "no_buf"),
+        }
+    }
+}
+
+// The fragments of a packet have the same source, destination, id and protocol (RFC 791)
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+struct ReassKey {
+    saddr: Ipv4Addr,
+    daddr: Ipv4Addr,
+    id: u16,
+    proto: u8,
+}
+
+// A fragment with the range of bytes of the original payload it carries
+struct Frag {
+    start: usize,
+    end: usize,
+    pkt: BoxPkt,
+}
+
+// A packet being reassembled
+struct Reass {
+    // Sorted by offset, and they never overlap
+    frags: Vec<Frag>,
+    // The payload length of the original packet, known once the last fragment is in
+    total: Option<usize>,
+    // The payload bytes received so far, and the length of the fragments holding them
+    received: usize,
+    bytes: usize,
+    expires: u64,
+}
+
+// Why a fragment was not added to its packet
+enum Reject {
+    // An exact copy of a fragment we have, the packet is fine
+    Duplicate,
+    // Overlaps the fragments we have, the whole packet is suspect
+    Overlap,
+    // Goes beyond the end of the packet, or the end of the packet moved
+    Bad,
+}
+
+impl Reass {
+    fn new(expires: u64) -> Reass {
+        Reass {
+            frags: Vec::new(),
+            total: None,
+            received: 0,
+            bytes: 0,
+            expires,
+        }
+    }
+
+    fn add(&mut self, frag: Frag, more: bool) -> Result<(), Reject> {
+        let (start, end) = (frag.start, frag.end);
+        if let Some(total) = self.total {
+            if end > total || (!more && end != total) {
+                return Err(Reject::Bad);
+            }
+        } else if !more && self.frags.last().is_some_and(|f| f.end > end) {
+            return Err(Reject::Bad);
+        }
+        let at = self.frags.partition_point(|f| f.start < start);
+        if let Some(next) = self.frags.get(at) {
+            if next.start == start && next.end == end {
+                return Err(Reject::Duplicate);
+            }
+            if next.start < end {
+                return Err(Reject::Overlap);
+            }
+        }
+        if at > 0 && self.frags[at - 1].end > start {
+            return Err(Reject::Overlap);
+        }
+        if !more {
+            self.total = Some(end);
+        }
+        self.received += end - start;
+        self.bytes += frag.pkt.len();
+        self.frags.insert(at, frag);
+        Ok(())
+    }
+
+    fn complete(&self) -> bool {
+        self.total == Some(self.received)
+    }
+
+    // The original packet - the header of the first fragment, without the fragment offset
+    // and the more fragments flag, followed by the payloads of all the fragments in order
+    fn packet(&self, pool: &mut dyn PacketPool) -> Option<BoxPkt> {
+        let first = &self.frags.first()?.pkt;
+        let (iphdr, ihl) = first.get_l3();
+        let mut hdr = iphdr[0..ihl].to_vec();
+        let totlen = (ihl + self.total?) as u16;
+        hdr[IPHDR_TOTLEN_OFF..IPHDR_TOTLEN_OFF + 2].copy_from_slice(&totlen.to_be_bytes());
+        let frag = u16::from_be_bytes([hdr[IPHDR_FRAG_OFF], hdr[IPHDR_FRAG_OFF + 1]]);
+        let frag = frag & !(IPHDR_MF | IPHDR_FRAG_MASK);
+        hdr[IPHDR_FRAG_OFF..IPHDR_FRAG_OFF + 2].copy_from_slice(&frag.to_be_bytes());
+        hdr[IPHDR_CSUM_OFF] = 0;
+        hdr[IPHDR_CSUM_OFF + 1] = 0;
+        let csum = ip_checksum(&hdr);
+        hdr[IPHDR_CSUM_OFF..IPHDR_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+        let mut pkt = pool.pkt(first.headroom())?;
+        if !pkt.append(pool, &hdr) {
+            return None;
+        }
+        for f in self.frags.iter() {
+            let (_, ihl) = f.pkt.get_l3();
+            if !pkt.append_pkt(pool, &f.pkt, ihl, f.end - f.start) {
+                return None;
+            }
+        }
+        if !pkt.set_l3(ihl) {
+            return None;
+        }
+        pkt.in_ifindex = first.in_ifindex;
+        pkt.trace = first.trace;
+        Some(pkt)
+    }
+}
+
+// The ip4-local node sends the fragments of packets addressed to R2 here, and the packets
+// are reassembled and sent back to ip4-local once all their fragments are in. Each thread
+// reassembles the fragments it gets, so the fragments of a packet have to come in on the
+// interfaces of one thread - which is the case if they come in on the same interface.
+// Fragments that overlap (other than exact duplicates) get the whole packet dropped, and
+// packets that do not complete within REASS_TIMEOUT_MSECS are dropped. The node asks the
+// graph to be woken up when the oldest packet being reassembled times out
+pub struct IPv4Reass {
+    cnt: ReassCnt,
+    pkts: HashMap<ReassKey, Reass>,
+    // The packets being reassembled in the order they expire, which is the order they
+    // started in. Packets that completed or were dropped are skipped when they come up
+    expiry: VecDeque<(u64, ReassKey)>,
+    // The fragments held across all the packets, and the bytes in them
+    frags: usize,
+    bytes: usize,
+    // The clock the timeouts go by, the tests run the node on a clock of their own
+    time_msecs: fn() -> u64,
+}
+
+impl IPv4Reass {
+    pub fn new(counters: &mut Counters) -> IPv4Reass {
+        IPv4Reass {
+            cnt: ReassCnt::new(counters),
+            pkts: HashMap::new(),
+            expiry: VecDeque::new(),
+            frags: 0,
+            bytes: 0,
+            time_msecs,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        names::L3_IPV4_REASS.to_string()
+    }
+
+    pub fn next_names(&self) -> Vec<String> {
+        let mut v = Vec::new();
+        for n in NEXT_NAMES {
+            assert_eq!(*n as usize, v.len());
+            v.push(next_name(*n));
+        }
+        v
+    }
+
+    fn remove(&mut self, key: &ReassKey) -> Option<Reass> {
+        let reass = self.pkts.remove(key)?;
+        self.frags -= reass.frags.len();
+        self.bytes -= reass.bytes;
+        Some(reass)
+    }
+
+    fn reassemble(&mut self, vectors: &mut Dispatch, p: BoxPkt, now: u64) {
+        let (iphdr, ihl) = p.get_l3();
+        let key = ReassKey {
+            saddr: ipv4_addr(&iphdr[IPHDR_SADDR_OFF..]),
+            daddr: ipv4_addr(&iphdr[IPHDR_DADDR_OFF..]),
+            id: u16::from_be_bytes([iphdr[IPHDR_ID_OFF], iphdr[IPHDR_ID_OFF + 1]]),
+            proto: iphdr[IPHDR_PROTO_OFF],
+        };
+        let frag = u16::from_be_bytes([iphdr[IPHDR_FRAG_OFF], iphdr[IPHDR_FRAG_OFF + 1]]);
+        let more = frag & IPHDR_MF != 0;
+        let start = (frag & IPHDR_FRAG_MASK) as usize * FRAG_UNIT;
+        let end = start + p.len() - ihl;
+        // All but the last fragment carry a multiple of 8 bytes
+        if end == start || (more && (end - start) & (FRAG_UNIT - 1) != 0) || ihl + end > IP_MAX_LEN
+        {
+            vectors.trace(&p, || format!("bad fragment offset {} end {}", start, end));
+            self.cnt.bad_frag.incr();
+            return;
+        }
+        let len = p.len();
+        if self.frags >= REASS_MAX_FRAGS
+            || self.bytes + len > REASS_MAX_BYTES
+            || (!self.pkts.contains_key(&key) && self.pkts.len() >= REASS_MAX_PKTS)
+        {
+            vectors.trace(&p, || "reassembly memory full".to_string());
+            self.cnt.no_mem.incr();
+            return;
+        }
+        vectors.trace(&p, || {
+            format!("fragment id {} offset {} end {}", key.id, start, end)
+        });
+        let expiry = &mut self.expiry;
+        let reass = self.pkts.entry(key).or_insert_with(|| {
+            let expires = now + REASS_TIMEOUT_MSECS;
+            expiry.push_back((expires, key));
+            Reass::new(expires)
+        });
+        let frag = Frag { start, end, pkt: p };
+        match reass.add(frag, more) {
+            Ok(()) => {
+                self.frags += 1;
+                self.bytes += len;
+            }
+            Err(Reject::Duplicate) => {
+                self.cnt.duplicates.incr();
+                return;
+            }
+            Err(why) => {
+                match why {
+                    Reject::Overlap => self.cnt.overlaps.incr(),
+                    _ => self.cnt.bad_frag.incr(),
+                };
+                self.remove(&key);
+                return;
+            }
+        }
+        if !reass.complete() {
+            return;
+        }
+        let reass = self.remove(&key).unwrap();
+        match reass.packet(vectors.pool) {
+            Some(pkt) => {
+                vectors.trace(&pkt, || format!("reassembled len {}", pkt.len()));
+                self.cnt.reassembled.incr();
+                vectors.push(Next::L3Ipv4Local as usize, pkt);
+            }
+            None => {
+                self.cnt.no_buf.incr();
+            }
+        }
+    }
+
+    // Drop the packets that did not complete in time, and ask to be woken up when the next
+    // one is due to expire
+    fn expire(&mut self, vectors: &mut Dispatch, now: u64) {
+        while let Some((expires, key)) = self.expiry.front().copied() {
+            if expires > now {
+                vectors.wakeup(((expires - now) * 1_000_000) as usize);
+                break;
+            }
+            self.expiry.pop_front();
+            if self.pkts.get(&key).is_some_and(|r| r.expires == expires) {
+                self.remove(&key);
+                self.cnt.timeouts.incr();
+            }
+        }
+    }
+}
+
+fn ipv4_addr(bytes: &[u8]) -> Ipv4Addr {
+    Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])
+}
+
+impl Gclient<R2Msg> for IPv4Reass {
+    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
+        Box::new(IPv4Reass::new(counters))
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        let mut now = None;
+        while let Some(p) = vectors.pop() {
+            let t = *now.get_or_insert_with(self.time_msecs);
+            let (_, hdrlen) = p.get_l3();
+            if hdrlen < IPHDR_MIN_LEN {
+                vectors.trace(&p, || "invalid l3".to_string());
+                self.cnt.bad_frag.incr();
+                continue;
+            }
+            self.reassemble(vectors, p, t);
+        }
+        if !self.expiry.is_empty() {
+            let t = now.unwrap_or_else(self.time_msecs);
+            self.expire(vectors, t);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,197 @@
+use super::*;
+use fwd::harness::{ipv4_pkt, payload, Ipv4Hdr};
+use fwd::IP_PROTO_UDP;
+use graph::harness::{self, pkt_bytes, Harness};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const SRC: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 2);
+const DST: Ipv4Addr = Ipv4Addr::new(10, 1, 1, 1);
+
+// The clock of the reassembly node in the timeout test
+static CLOCK: AtomicU64 = AtomicU64::new(1_000);
+
+fn test_clock_msecs() -> u64 {
+    CLOCK.load(Ordering::Relaxed)
+}
+
+fn reass_harness(counters: &mut Counters, clock: bool) -> Harness<R2Msg> {
+    let mut node = IPv4Reass::new(counters);
+    if clock {
+        node.time_msecs = test_clock_msecs;
+    }
+    graph::harness!(node, counters)
+}
+
+// A fragment of the packet with the given id, with the payload at the byte offset 'off'
+fn frag(id: u16, off: usize, more: bool, payload: &[u8]) -> Vec<u8> {
+    let mut frag = (off / FRAG_UNIT) as u16;
+    if more {
+        frag |= IPHDR_MF;
+    }
+    let hdr = Ipv4Hdr {
+        id,
+        frag,
+        ..Ipv4Hdr::new(SRC, DST, IP_PROTO_UDP)
+    };
+    ipv4_pkt(&hdr, payload)
+}
+
+fn send(harness: &mut Harness<R2Msg>, frags: &[Vec<u8>]) -> Vec<(String, BoxPkt)> {
+    let pkts = frags
+        .iter()
+        .map(|f| harness.pkt(f, IPHDR_MIN_LEN))
+        .collect();
+    harness.run(pkts)
+}
+
+fn count(test: &str, ctype: CounterType, name: &str) -> u64 {
+    harness::count(test, names::L3_IPV4_REASS, ctype, name)
+}
+
+#[test]
+fn out_of_order() {
+    let mut counters = Counters::new("reass_out_of_order").unwrap();
+    let mut harness = reass_harness(&mut counters, false);
+
+    // The last fragment first, then the first one and then the middle one
+    let data = payload(100);
+    let frags = [
+        frag(1, 96, false, &data[96..]),
+        frag(1, 0, true, &data[0..48]),
+        frag(1, 48, true, &data[48..96]),
+    ];
+    assert!(send(&mut harness, &frags[0..2]).is_empty());
+    let out = send(&mut harness, &frags[2..]);
+    assert_eq!(out.len(), 1);
+    let (next, pkt) = &out[0];
+    assert_eq!(next, names::L3_IPV4_LOCAL);
+    let bytes = pkt_bytes(pkt);
+    let (iphdr, rest) = bytes.split_at(IPHDR_MIN_LEN);
+    assert_eq!(ip_checksum(iphdr), 0);
+    let totlen = u16::from_be_bytes([iphdr[IPHDR_TOTLEN_OFF], iphdr[IPHDR_TOTLEN_OFF + 1]]);
+    assert_eq!(totlen as usize, IPHDR_MIN_LEN + data.len());
+    assert_eq!(iphdr[IPHDR_FRAG_OFF..IPHDR_FRAG_OFF + 2], [0, 0]);
+    assert_eq!(rest, &data[..]);
+    let test = "reass_out_of_order";
+    assert_eq!(count(test, CounterType::Pkts, "reassembled"), 1);
+}
+
+#[test]
+fn overlap() {
+    let mut counters = Counters::new("reass_overlap").unwrap();
+    let mut harness = reass_harness(&mut counters, false);
+    let test = "reass_overlap";
+
+    // An exact duplicate is dropped, and the packet still completes
+    let data = payload(32);
+    let frags = [
+        frag(1, 0, true, &data[0..16]),
+        frag(1, 0, true, &data[0..16]),
+        frag(1, 16, false, &data[16..]),
+    ];
+    assert_eq!(send(&mut harness, &frags).len(), 1);
+    assert_eq!(count(test, CounterType::Pkts, "duplicates"), 1);
+
+    // A fragment that overlaps another one drops the whole packet, the rest of the packet
+    // starts a new one which never completes
+    let frags = [
+        frag(2, 0, true, &data[0..16]),
+        frag(2, 8, true, &data[8..24]),
+        frag(2, 16, false, &data[16..]),
+    ];
+    assert!(send(&mut harness, &frags).is_empty());
+    assert_eq!(count(test, CounterType::Error, "overlaps"), 1);
+    assert_eq!(count(test, CounterType::Pkts, "reassembled"), 1);
+}
+
+#[test]
+fn timeout() {
+    let mut counters = Counters::new("reass_timeout").unwrap();
+    let mut harness = reass_harness(&mut counters, true);
+    let test = "reass_timeout";
+    let data = payload(32);
+
+    // Packet 1 is still there just before the timeout
+    let start = test_clock_msecs();
+    assert!(send(&mut harness, &[frag(1, 0, true, &data[0..16])]).is_empty());
+    CLOCK.store(start + REASS_TIMEOUT_MSECS - 1, Ordering::Relaxed);
+    assert!(send(&mut harness, &[frag(2, 0, true, &data[0..16])]).is_empty());
+    assert_eq!(count(test, CounterType::Pkts, "timeouts"), 0);
+
+    // And gone at the timeout, its last fragment does not complete it
+    CLOCK.store(start + REASS_TIMEOUT_MSECS, Ordering::Relaxed);
+    assert!(send(&mut harness, &[frag(3, 0, true, &data[0..16])]).is_empty());
+    assert_eq!(count(test, CounterType::Pkts, "timeouts"), 1);
+    assert!(send(&mut harness, &[frag(1, 16, false, &data[16..])]).is_empty());
+
+    // Packet 2 completes in time
+    let out = send(&mut harness, &[frag(2, 16, false, &data[16..])]);
+    assert_eq!(out.len(), 1);
+    assert_eq!(count(test, CounterType::Pkts, "reassembled"), 1);
+}
+
+#[test]
+fn max_pkts() {
+    let mut counters = Counters::new("reass_max_pkts").unwrap();
+    let mut harness = reass_harness(&mut counters, false);
+    let test = "reass_max_pkts";
+    let data = payload(16);
+
+    // One more packet than the limit, the last one is dropped
+    let frags: Vec<Vec<u8>> = (0..=REASS_MAX_PKTS as u16)
+        .map(|id| frag(id, 0, true, &data[0..8]))
+        .collect();
+    assert!(send(&mut harness, &frags).is_empty());
+    assert_eq!(count(test, CounterType::Error, "no_mem"), 1);
+
+    // The packets already in can still complete
+    let out = send(&mut harness, &[frag(0, 8, false, &data[8..])]);
+    assert_eq!(out.len(), 1);
+}
+
+#[test]
+fn max_frags() {
+    let mut counters = Counters::new("reass_max_frags").unwrap();
+    let mut harness = reass_harness(&mut counters, false);
+    let test = "reass_max_frags";
+    let data = payload(8);
+
+    // Two packets with half the fragments each, then one fragment too many
+    let half = REASS_MAX_FRAGS / 2;
+    let mut frags = Vec::new();
+    for id in 0..2 {
+        for i in 0..half {
+            frags.push(frag(id, i * FRAG_UNIT, true, &data));
+        }
+    }
+    assert!(send(&mut harness, &frags).is_empty());
+    assert_eq!(count(test, CounterType::Error, "no_mem"), 0);
+    let last = frag(0, half * FRAG_UNIT, false, &data);
+    assert!(send(&mut harness, &[last]).is_empty());
+    assert_eq!(count(test, CounterType::Error, "no_mem"), 1);
+}
+
+#[test]
+fn max_bytes() {
+    let mut counters = Counters::new("reass_max_bytes").unwrap();
+    let mut harness = reass_harness(&mut counters, false);
+    let test = "reass_max_bytes";
+
+    // Big fragments across two packets, up to just below the byte limit
+    let data = payload(8000);
+    let fit = REASS_MAX_BYTES / (IPHDR_MIN_LEN + data.len());
+    let mut frags = Vec::new();
+    for i in 0..fit {
+        let (id, off) = if i < fit / 2 {
+            (0, i)
+        } else {
+            (1, i - fit / 2)
+        };
+        frags.push(frag(id, off * data.len(), true, &data));
+    }
+    assert!(send(&mut harness, &frags).is_empty());
+    assert_eq!(count(test, CounterType::Error, "no_mem"), 0);
+    let last = frag(1, (fit - fit / 2) * data.len(), false, &data);
+    assert!(send(&mut harness, &[last]).is_empty());
+    assert_eq!(count(test, CounterType::Error, "no_mem"), 1);
+}
@@ -0,0 +1,317 @@
+use counters::{flavors::Counter, flavors::CounterType, Counters};
+use fwd::intf::Interface;
+use fwd::{icmp6_checksum, ipv6::IPv6Table, ipv6_addr, Fwd, ETHER_MTU, IP_DEFAULT_TTL};
+use fwd::{ICMP6_DEST_UNREACH, ICMP6_NO_ROUTE, ICMP6_REJECT_ROUTE};
+use fwd::{ICMP6_INFO_MIN, ICMP6_MTU_OFF, ICMP6_PKT_TOO_BIG, ICMP6_TIME_EXCEEDED, IP6_MIN_MTU};
+use fwd::{ICMP_CODE_OFF, ICMP_CSUM_OFF, ICMP_HDR_LEN, ICMP_TYPE_OFF, IP_PROTO_ICMP6};
+use fwd::{IP6HDR_DADDR_OFF, IP6HDR_HLIM_OFF, IP6HDR_LEN, IP6HDR_SADDR_OFF, ZERO_IP6};
+use fwd::{IP6HDR_NEXTHDR_OFF, IP6HDR_PLEN_OFF};
+use graph::Dispatch;
+use graph::Gclient;
+use graph::BURST_SIZE;
+use log::Logger;
+use msg::R2Msg;
+use packet::BoxPkt;
+use packet::PacketPool;
+use std::net::Ipv6Addr;
+use std::sync::Arc;
+
+#[derive(Copy, Clone)]
+enum Next {
+    Drop = 0,
+    EncapMux,
+}
+
+const NEXT_NAMES: &[Next] = &[Next::Drop, Next::EncapMux];
+
+fn next_name(next: Next) -> String {
+    match next {
+        Next::Drop => names::DROP.to_string(),
+        Next::EncapMux => names::ENCAPMUX.to_string(),
+    }
+}
+
+struct IPv6Cnt {
+    no_route: Counter,
+    blackhole: Counter,
+    reject: Counter,
+    invalid_l3: Counter,
+    hlim_expired: Counter,
+    too_big: Counter,
+    link_local: Counter,
+    local: Counter,
+}
+
+impl IPv6Cnt {
+    fn new(counters: &mut Counters) -> IPv6Cnt {
+        let name = names::L3_IPV6_FWD;
+        IPv6Cnt {
+            no_route: Counter::new(counters, name, CounterType::Pkts, "no_route"),
+            blackhole: Counter::new(counters, name, CounterType::Pkts, "blackhole"),
+            reject: Counter::new(counters, name, CounterType::Pkts, "reject"),
+            invalid_l3: Counter::new(counters, name, CounterType::Error, "invalid_l3"),
+            hlim_expired: Counter::new(counters, name, CounterType::Pkts, "hlim_expired"),
+            too_big: Counter::new(counters, name, CounterType::Pkts, "too_big"),
+            link_local: Counter::new(counters, name, CounterType::Pkts, "link_local"),
+            local: Counter::new(counters, name, CounterType::Pkts, "local"),
+        }
+    }
+}
+
+// fe80::/10, packets from or to these addresses never leave the link they are on
+fn is_link_local(addr: &Ipv6Addr) -> bool {
+    addr.segments()[0] & 0xffc0 == 0xfe80
+}
+
+// The IPv6 Forwarding node: a route lookup of the destination address in the ipv6 table,
+// and the packet goes to EncapMux with the output interface and nexthop from the adjacency,
+// just like IPv4Fwd. The hop limit is decremented on the way, packets whose hop limit runs
+// out are dropped with an ICMPv6 time exceeded back to the source. Routers do not fragment
+// ipv6 packets, packets bigger than the MTU of the output interface are dropped with an
+// ICMPv6 packet too big. Packets to or from link local addresses are not forwarded, and
+// there is nothing in R2 yet to terminate ipv6 packets addressed to R2 itself, those are
+// dropped too. Blackhole routes drop the packets silently, reject routes and destinations
+// without a route drop them with an ICMPv6 destination unreachable back to the source
+pub struct IPv6Fwd {
+    table: Arc<IPv6Table>,
+    // The interfaces indexed by ifindex, to find the address to send ICMPv6 errors from and
+    // the MTU of the output interface
+    intfs: Vec<Option<Arc<Interface>>>,
+    cnt: IPv6Cnt,
+    // The burst of packets being processed, and the packets from the burst going to EncapMux
+    burst: Vec<BoxPkt>,
+    encap: Vec<BoxPkt>,
+}
+
+impl IPv6Fwd {
+    pub fn new(table: Arc<IPv6Table>, counters: &mut Counters) -> IPv6Fwd {
+        IPv6Fwd {
+            table,
+            intfs: Vec::new(),
+            cnt: IPv6Cnt::new(counters),
+            burst: Vec::with_capacity(BURST_SIZE),
+            encap: Vec::with_capacity(BURST_SIZE),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        names::L3_IPV6_FWD.to_string()
+    }
+
+    pub fn next_names(&self) -> Vec<String> {
+        let mut v = Vec::new();
+        for n in NEXT_NAMES {
+            assert_eq!(*n as usize, v.len());
+            v.push(next_name(*n));
+        }
+        v
+    }
+
+    fn forward(&mut self, vectors: &mut Dispatch, mut p: BoxPkt) {
+        let (iphdr, _) = p.get_l3();
+        let daddr = ipv6_addr(&iphdr[IP6HDR_DADDR_OFF..]);
+        let saddr = ipv6_addr(&iphdr[IP6HDR_SADDR_OFF..]);
+        if is_link_local(&daddr) || is_link_local(&saddr) {
+            vectors.trace(&p, || format!("src {} dst {} link local", saddr, daddr));
+            self.cnt.link_local.incr();
+            return;
+        }
+        if let Some((prefix, mask, leaf)) = self.table.longest_match(daddr) {
+            match &leaf.next {
+                Fwd::Local(_) => {
+                    vectors.trace(&p, || format!("dst {} local", daddr));
+                    self.cnt.local.incr();
+                }
+                Fwd::Adjacency6(adj) => {
+                    let hlim = iphdr[IP6HDR_HLIM_OFF];
+                    if hlim <= 1 {
+                        vectors.trace(&p, || format!("hop limit {} expired", hlim));
+                        self.cnt.hlim_expired.incr();
+                        let icmp = self.icmp6_error(vectors.pool, &p, ICMP6_TIME_EXCEEDED, 0, 0);
+                        if let Some(icmp) = icmp {
+                            vectors.trace(&icmp, || "icmp6 time exceeded".to_string());
+                            self.forward(vectors, icmp);
+                        }
+                        return;
+                    }
+                    let mtu = self.mtu(adj.ifindex);
+                    if p.len() > mtu {
+                        vectors.trace(&p, || format!("len {} mtu {} too big", p.len(), mtu));
+                        self.cnt.too_big.incr();
+                        let too_big = ICMP6_PKT_TOO_BIG;
+                        let icmp = self.icmp6_error(vectors.pool, &p, too_big, 0, mtu as u32);
+                        if let Some(icmp) = icmp {
+                            vectors.trace(&icmp, || "icmp6 packet too big".to_string());
+                            self.forward(vectors, icmp);
+                        }
+                        return;
+                    }
+                    let (iphdr, _) = p.get_l3_mut();
+                    iphdr[IP6HDR_HLIM_OFF] -= 1;
+                    vectors.trace(&p, || {
+                        format!(
+                            "dst {} route {}/{} nhop {} ifindex {}",
+                            daddr, prefix, mask, adj.nhop, adj.ifindex
+                        )
+                    });
+                    p.out_ifindex = adj.ifindex;
+                    p.out_l3addr6 = adj.nhop;
+                    if p.out_l3addr6 == ZERO_IP6 {
+                        // destination is in connected subnet
+                        p.out_l3addr6 = daddr;
+                    }
+                    self.encap.push(p);
+                }
+                Fwd::Blackhole => {
+                    vectors.trace(&p, || {
+                        format!("dst {} route {}/{} blackhole", daddr, prefix, mask)
+                    });
+                    self.cnt.blackhole.incr();
+                }
+                Fwd::Reject => {
+                    vectors.trace(&p, || {
+                        format!("dst {} route {}/{} reject", daddr, prefix, mask)
+                    });
+                    self.cnt.reject.incr();
+                    self.unreachable(vectors, &p, ICMP6_REJECT_ROUTE);
+                }
+                _ => {
+                    vectors.trace(&p, || format!("dst {} no route", daddr));
+                    self.cnt.no_route.incr();
+                }
+            }
+        } else {
+            vectors.trace(&p, || format!("dst {} no route", daddr));
+            self.cnt.no_route.incr();
+            self.unreachable(vectors, &p, ICMP6_NO_ROUTE);
+        }
+    }
+
+    // Send a destination unreachable with the given code back to the source of the packet
+    fn unreachable(&mut self, vectors: &mut Dispatch, p: &BoxPkt, code: u8) {
+        if let Some(icmp) = self.icmp6_error(vectors.pool, p, ICMP6_DEST_UNREACH, code, 0) {
+            vectors.trace(&icmp, || format!("icmp6 unreachable code {}", code));
+            self.forward(vectors, icmp);
+        }
+    }
+
+    fn intf(&self, ifindex: usize) -> Option<&Arc<Interface>> {
+        self.intfs.get(ifindex)?.as_ref()
+    }
+
+    fn mtu(&self, ifindex: usize) -> usize {
+        self.intf(ifindex).map_or(ETHER_MTU, |intf| intf.mtu)
+    }
+
+    // Build an ICMPv6 error of the given type and code for the packet, sent to the source of
+    // the packet from the address of the interface the packet came in on. The mtu is the MTU
+    // of a packet too big, zero for other errors. The error carries as much of the packet as
+    // fits in the minimum IPv6 MTU. There are no errors for ICMPv6 errors, or for packets from
+    // the unspecified or multicast addresses (RFC 4443 section 2.4)
+    fn icmp6_error(
+        &self,
+        pool: &mut dyn PacketPool,
+        p: &BoxPkt,
+        icmp_type: u8,
+        code: u8,
+        mtu: u32,
+    ) -> Option<BoxPkt> {
+        let intf = self.intf(p.in_ifindex)?;
+        if intf.ipv6_addr == ZERO_IP6 {
+            return None;
+        }
+        let (iphdr, _) = p.get_l3();
+        let saddr = ipv6_addr(&iphdr[IP6HDR_SADDR_OFF..]);
+        if saddr.is_unspecified() || saddr.is_multicast() {
+            return None;
+        }
+        let (data, len) = p.data(0)?;
+        if data[IP6HDR_NEXTHDR_OFF] == IP_PROTO_ICMP6
+            && len > IP6HDR_LEN + ICMP_TYPE_OFF
+            && data[IP6HDR_LEN + ICMP_TYPE_OFF] < ICMP6_INFO_MIN
+        {
+            return None;
+        }
+
+        let quote_len = p.len().min(IP6_MIN_MTU - IP6HDR_LEN - ICMP_HDR_LEN);
+        let mut icmp = vec![0; ICMP_HDR_LEN];
+        icmp[ICMP_TYPE_OFF] = icmp_type;
+        icmp[ICMP_CODE_OFF] = code;
+        icmp[ICMP6_MTU_OFF..ICMP6_MTU_OFF + 4].copy_from_slice(&mtu.to_be_bytes());
+        while icmp.len() < ICMP_HDR_LEN + quote_len {
+            let (data, len) = p.data(icmp.len() - ICMP_HDR_LEN)?;
+            let len = len.min(ICMP_HDR_LEN + quote_len - icmp.len());
+            icmp.extend_from_slice(&data[0..len]);
+        }
+        let csum = icmp6_checksum(&intf.ipv6_addr, &saddr, &icmp);
+        icmp[ICMP_CSUM_OFF..ICMP_CSUM_OFF + 2].copy_from_slice(&csum.to_be_bytes());
+
+        let mut hdr = vec![0; IP6HDR_LEN];
+        hdr[0] = 0x60;
+        let plen = icmp.len() as u16;
+        hdr[IP6HDR_PLEN_OFF..IP6HDR_PLEN_OFF + 2].copy_from_slice(&plen.to_be_bytes());
+        hdr[IP6HDR_NEXTHDR_OFF] = IP_PROTO_ICMP6;
+        hdr[IP6HDR_HLIM_OFF] = IP_DEFAULT_TTL;
+        hdr[IP6HDR_SADDR_OFF..IP6HDR_SADDR_OFF + 16].copy_from_slice(&intf.ipv6_addr.octets());
+        hdr[IP6HDR_DADDR_OFF..IP6HDR_DADDR_OFF + 16].copy_from_slice(&saddr.octets());
+
+        let mut pkt = pool.pkt(intf.headroom)?;
+        if !pkt.append(pool, &hdr) || !pkt.append(pool, &icmp) || !pkt.set_l3(IP6HDR_LEN) {
+            return None;
+        }
+        pkt.in_ifindex = p.in_ifindex;
+        pkt.trace = p.trace;
+        Some(pkt)
+    }
+}
+
+impl Gclient<R2Msg> for IPv6Fwd {
+    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
+        Box::new(IPv6Fwd {
+            table: self.table.clone(),
+            intfs: self.intfs.clone(),
+            cnt: IPv6Cnt::new(counters),
+            burst: Vec::with_capacity(BURST_SIZE),
+            encap: Vec::with_capacity(BURST_SIZE),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        while vectors.pop_burst(&mut self.burst, BURST_SIZE) != 0 {
+            for p in self.burst.iter() {
+                p.prefetch();
+            }
+            // forward() needs self, so take the burst out of self while working on it
+            let mut burst = std::mem::take(&mut self.burst);
+            for p in burst.drain(..) {
+                let (_, hdrlen) = p.get_l3();
+                if hdrlen < IP6HDR_LEN {
+                    vectors.trace(&p, || "invalid l3".to_string());
+                    self.cnt.invalid_l3.incr();
+                    continue;
+                }
+                self.forward(vectors, p);
+            }
+            self.burst = burst;
+            vectors.push_burst(Next::EncapMux as usize, &mut self.encap);
+        }
+    }
+
+    fn control_msg(&mut self, _thread: usize, message: R2Msg) -> bool {
+        match message {
+            R2Msg::IPv6TableAdd(table) => self.table = table.table,
+            R2Msg::ModifyInterface(mod_intf) => {
+                let ifindex = mod_intf.intf.ifindex;
+                if ifindex >= self.intfs.len() {
+                    self.intfs.resize(ifindex + 1, None);
+                }
+                self.intfs[ifindex] = Some(mod_intf.intf);
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,180 @@
+use super::*;
+use fwd::adj::Adjacency6;
+use fwd::harness::ipv6_pkt;
+use fwd::intf::ModifyInterfaceMsg;
+use fwd::ipv6::{IPv6Leaf, IPv6TableMsg};
+use fwd::IP_PROTO_UDP;
+use graph::harness::{self, pkt_bytes, Harness};
+
+const IN_IFINDEX: usize = 1;
+const OUT_IFINDEX: usize = 2;
+const IN_ADDR: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 1);
+const SRC: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 2);
+const DST: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 2, 0, 0, 0, 0, 2);
+const NHOP: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 2, 0, 0, 0, 0, 1);
+const REJECT: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 3, 0, 0, 0, 0, 0);
+
+// The fwd node in a graph of its own, with the input interface connected to the subnet of
+// SRC and the output interface (with the given mtu) leading to DST through NHOP, and a
+// reject route to REJECT/64
+fn fwd_harness(counters: &mut Counters, mtu: usize) -> Harness<R2Msg> {
+    let mut table = IPv6Table::new();
+    let adj = Adjacency6::new(ZERO_IP6, IN_IFINDEX);
+    let leaf = IPv6Leaf::new(Fwd::Adjacency6(Arc::new(adj)));
+    table.add(
+        Ipv6Addr::new(0x2001, 0xdb8, 1, 0, 0, 0, 0, 0),
+        64,
+        Arc::new(leaf),
+    );
+    let adj = Adjacency6::new(NHOP, OUT_IFINDEX);
+    let leaf = IPv6Leaf::new(Fwd::Adjacency6(Arc::new(adj)));
+    table.add(
+        Ipv6Addr::new(0x2001, 0xdb8, 2, 0, 0, 0, 0, 0),
+        64,
+        Arc::new(leaf),
+    );
+    table.add(REJECT, 64, Arc::new(IPv6Leaf::new(Fwd::Reject)));
+    let table = Arc::new(table);
+
+    let mut harness = graph::harness!(IPv6Fwd::new(table.clone(), counters), counters);
+    let msg = IPv6TableMsg::new(table);
+    assert!(harness.control_msg(R2Msg::IPv6TableAdd(msg)));
+
+    let mut intf = Interface::new("in", IN_IFINDEX, vec![0; 6], 0);
+    intf.set_v6addr(IN_ADDR, 64);
+    let intf = Arc::new(intf);
+    assert!(harness.control_msg(R2Msg::ModifyInterface(ModifyInterfaceMsg { intf })));
+    let mut intf = Interface::new("out", OUT_IFINDEX, vec![0; 6], 0);
+    intf.set_v6addr(NHOP, 64);
+    intf.mtu = mtu;
+    let intf = Arc::new(intf);
+    assert!(harness.control_msg(R2Msg::ModifyInterface(ModifyInterfaceMsg { intf })));
+    harness
+}
+
+fn send(harness: &mut Harness<R2Msg>, data: &[u8]) -> Vec<(String, BoxPkt)> {
+    let mut pkt = harness.pkt(data, IP6HDR_LEN);
+    pkt.in_ifindex = IN_IFINDEX;
+    harness.run(vec![pkt])
+}
+
+fn count(test: &str, name: &str) -> u64 {
+    harness::count(test, names::L3_IPV6_FWD, CounterType::Pkts, name)
+}
+
+// Check that the packet is an ICMPv6 error of the given type from IN_ADDR back to SRC, and
+// return the ICMPv6 message
+fn icmp6_error(out: &[(String, BoxPkt)], icmp_type: u8) -> Vec<u8> {
+    assert_eq!(out.len(), 1);
+    let (next, icmp) = &out[0];
+    assert_eq!(next, names::ENCAPMUX);
+    assert_eq!(icmp.out_ifindex, IN_IFINDEX);
+    assert_eq!(icmp.out_l3addr6, SRC);
+    let bytes = pkt_bytes(icmp);
+    assert!(bytes.len() <= IP6_MIN_MTU);
+    assert_eq!(bytes[IP6HDR_NEXTHDR_OFF], IP_PROTO_ICMP6);
+    assert_eq!(ipv6_addr(&bytes[IP6HDR_SADDR_OFF..]), IN_ADDR);
+    assert_eq!(ipv6_addr(&bytes[IP6HDR_DADDR_OFF..]), SRC);
+    let plen = u16::from_be_bytes([bytes[IP6HDR_PLEN_OFF], bytes[IP6HDR_PLEN_OFF + 1]]);
+    assert_eq!(plen as usize, bytes.len() - IP6HDR_LEN);
+    let msg = bytes[IP6HDR_LEN..].to_vec();
+    assert_eq!(msg[ICMP_TYPE_OFF], icmp_type);
+    assert_eq!(icmp6_checksum(&IN_ADDR, &SRC, &msg), 0);
+    msg
+}
+
+#[test]
+fn forward() {
+    let mut counters = Counters::new("ipv6_forward").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+
+    let out = send(&mut harness, &ipv6_pkt(SRC, DST, IP_PROTO_UDP, 2, &[0; 64]));
+    assert_eq!(out.len(), 1);
+    let (next, p) = &out[0];
+    assert_eq!(next, names::ENCAPMUX);
+    assert_eq!(p.out_ifindex, OUT_IFINDEX);
+    assert_eq!(p.out_l3addr6, NHOP);
+    assert_eq!(pkt_bytes(p)[IP6HDR_HLIM_OFF], 1);
+}
+
+#[test]
+fn hop_limit_expired() {
+    let mut counters = Counters::new("ipv6_hlim_expired").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+
+    let pkt = ipv6_pkt(SRC, DST, IP_PROTO_UDP, 1, &[0; 64]);
+    let out = send(&mut harness, &pkt);
+    let msg = icmp6_error(&out, ICMP6_TIME_EXCEEDED);
+    // The whole packet fits in the error
+    assert_eq!(msg[ICMP_HDR_LEN..], pkt[..]);
+    assert_eq!(count("ipv6_hlim_expired", "hlim_expired"), 1);
+
+    // No error about an error
+    let mut unreach = vec![0; ICMP_HDR_LEN];
+    unreach[ICMP_TYPE_OFF] = ICMP6_DEST_UNREACH;
+    let pkt = ipv6_pkt(SRC, DST, IP_PROTO_ICMP6, 1, &unreach);
+    assert!(send(&mut harness, &pkt).is_empty());
+    assert_eq!(count("ipv6_hlim_expired", "hlim_expired"), 2);
+}
+
+#[test]
+fn packet_too_big() {
+    let mut counters = Counters::new("ipv6_too_big").unwrap();
+    let mut harness = fwd_harness(&mut counters, IP6_MIN_MTU);
+
+    // A packet that just fits goes out
+    let fits = ipv6_pkt(
+        SRC,
+        DST,
+        IP_PROTO_UDP,
+        64,
+        &vec![0; IP6_MIN_MTU - IP6HDR_LEN],
+    );
+    let out = send(&mut harness, &fits);
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].1.out_ifindex, OUT_IFINDEX);
+
+    // One byte more does not, the error has the MTU and as much of the packet as fits
+    let big = ipv6_pkt(
+        SRC,
+        DST,
+        IP_PROTO_UDP,
+        64,
+        &vec![0; IP6_MIN_MTU - IP6HDR_LEN + 1],
+    );
+    let out = send(&mut harness, &big);
+    let msg = icmp6_error(&out, ICMP6_PKT_TOO_BIG);
+    let mtu = &msg[ICMP6_MTU_OFF..ICMP6_MTU_OFF + 4];
+    assert_eq!(
+        u32::from_be_bytes([mtu[0], mtu[1], mtu[2], mtu[3]]),
+        IP6_MIN_MTU as u32
+    );
+    assert_eq!(msg.len(), IP6_MIN_MTU - IP6HDR_LEN);
+    assert_eq!(msg[ICMP_HDR_LEN..], big[0..msg.len() - ICMP_HDR_LEN]);
+    assert_eq!(count("ipv6_too_big", "too_big"), 1);
+}
+
+#[test]
+fn unreachable() {
+    let mut counters = Counters::new("ipv6_unreachable").unwrap();
+    let mut harness = fwd_harness(&mut counters, ETHER_MTU);
+
+    // A reject route, and no route at all
+    let dsts = [
+        (REJECT, ICMP6_REJECT_ROUTE),
+        (
+            Ipv6Addr::new(0x2001, 0xdb9, 0, 0, 0, 0, 0, 1),
+            ICMP6_NO_ROUTE,
+        ),
+    ];
+    for (dst, code) in dsts.iter() {
+        let mut pkt = ipv6_pkt(SRC, DST, IP_PROTO_UDP, 64, &[0; 64]);
+        pkt[IP6HDR_DADDR_OFF..IP6HDR_DADDR_OFF + 16].copy_from_slice(&dst.octets());
+        let out = send(&mut harness, &pkt);
+        let msg = icmp6_error(&out, ICMP6_DEST_UNREACH);
+        assert_eq!(msg[ICMP_CODE_OFF], *code);
+        assert_eq!(msg[ICMP_HDR_LEN..], pkt[..]);
+    }
+    assert_eq!(count("ipv6_unreachable", "reject"), 1);
+    assert_eq!(count("ipv6_unreachable", "no_route"), 1);
+}
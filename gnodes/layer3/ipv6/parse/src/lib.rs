@@ -0,0 +1,126 @@
+use counters::flavors::{Counter, CounterType};
+use counters::Counters;
+use fwd::{IP6HDR_LEN, IP6HDR_PLEN_OFF};
+use graph::Dispatch;
+use graph::Gclient;
+use log::Logger;
+use msg::R2Msg;
+use packet::BoxPkt;
+use std::sync::Arc;
+
+#[derive(Copy, Clone)]
+enum Next {
+    Drop = 0,
+    L3Ipv6Fwd,
+}
+
+const NEXT_NAMES: &[Next] = &[Next::Drop, Next::L3Ipv6Fwd];
+
+fn next_name(next: Next) -> String {
+    match next {
+        Next::Drop => names::DROP.to_string(),
+        Next::L3Ipv6Fwd => names::L3_IPV6_FWD.to_string(),
+    }
+}
+
+// Why a packet failed the header checks
+enum Invalid {
+    Short,
+    Version,
+    Length,
+}
+
+struct ParseCnt {
+    bad_pkt: Counter,
+    bad_version: Counter,
+    bad_len: Counter,
+}
+
+impl ParseCnt {
+    fn new(counters: &mut Counters) -> ParseCnt {
+        let name = names::L3_IPV6_PARSE;
+        ParseCnt {
+            bad_pkt: Counter::new(counters, name, CounterType::Error, "bad_pkt"),
+            bad_version: Counter::new(counters, name, CounterType::Error, "bad_version"),
+            bad_len: Counter::new(counters, name, CounterType::Error, "bad_len"),
+        }
+    }
+}
+
+// The ipv6 counterpart of the ipv4 parse node, it gets the ipv6 packets from the ethernet
+// decap node, makes sure the ipv6 header is sane and hands the packet to the ipv6
+// forwarding node. The extension headers are left alone, they are part of the payload as
+// far as forwarding is concerned
+pub struct IPv6Parse {
+    cnt: ParseCnt,
+}
+
+impl IPv6Parse {
+    pub fn new(counters: &mut Counters) -> IPv6Parse {
+        IPv6Parse {
+            cnt: ParseCnt::new(counters),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        names::L3_IPV6_PARSE.to_string()
+    }
+
+    pub fn next_names(&self) -> Vec<String> {
+        let mut v = Vec::new();
+        for n in NEXT_NAMES {
+            assert_eq!(*n as usize, v.len());
+            v.push(next_name(*n));
+        }
+        v
+    }
+}
+
+// The packet is long enough for the fixed ipv6 header, the version is 6 and the payload
+// length is not more than what the packet has. Anything after the payload (like ethernet
+// padding) is trimmed off the packet. There is no header checksum in ipv6
+fn validate(p: &mut BoxPkt) -> Result<(), Invalid> {
+    if !p.set_l3(IP6HDR_LEN) {
+        return Err(Invalid::Short);
+    }
+    let (iphdr, _) = p.get_l3();
+    if iphdr[0] >> 4 != 6 {
+        return Err(Invalid::Version);
+    }
+    let plen = u16::from_be_bytes([iphdr[IP6HDR_PLEN_OFF], iphdr[IP6HDR_PLEN_OFF + 1]]);
+    let totlen = IP6HDR_LEN + plen as usize;
+    if totlen > p.len() {
+        return Err(Invalid::Length);
+    }
+    if p.len() > totlen {
+        p.truncate(totlen);
+    }
+    Ok(())
+}
+
+impl Gclient<R2Msg> for IPv6Parse {
+    fn clone(&self, counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<R2Msg>> {
+        Box::new(IPv6Parse {
+            cnt: ParseCnt::new(counters),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        while let Some(mut p) = vectors.pop() {
+            match validate(&mut p) {
+                Ok(()) => {
+                    vectors.push(Next::L3Ipv6Fwd as usize, p);
+                }
+                Err(why) => {
+                    let (reason, cnt) = match why {
+                        Invalid::Short => ("bad packet", &mut self.cnt.bad_pkt),
+                        Invalid::Version => ("bad version", &mut self.cnt.bad_version),
+                        Invalid::Length => ("bad length", &mut self.cnt.bad_len),
+                    };
+                    vectors.trace(&p, || reason.to_string());
+                    cnt.incr();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use packet::BoxPkt;
+
+/// The maximum number of features that can be registered on one arc
+pub const MAX_FEATURES: usize = 64;
+
+/// A feature arc is an ordered list of optional feature nodes (like ACL, NAT, policers) that
+/// packets can be sent through between two nodes in the graph - the 'start' and the 'end' of
+/// the arc. The features are registered on the arc when the graph is created, and then they
+/// can be enabled/disabled per interface. The start node and every feature node on the arc
+/// carries a copy of the arc, and the next names of those nodes include the arc's next_names().
+/// Packets on an interface without any features enabled go from the start node to the end
+/// node just like they would without an arc. Packets on an interface with features enabled
+/// go from the start node to the first enabled feature, then to the next enabled feature and
+/// so on, the last enabled feature sends the packet to the end node. The arc either looks at
+/// the input interface of the packet (like ip4-input) or the output interface (like ip4-output)
+#[derive(Clone)]
+pub struct FeatureArc {
+    name: String,
+    start: String,
+    end: String,
+    output: bool,
+    features: Vec<String>,
+    // A bitmap of features enabled on each interface, indexed by ifindex
+    enabled: Vec<u64>,
+}
+
+impl FeatureArc {
+    pub fn new(name: &str, start: &str, end: &str, output: bool) -> FeatureArc {
+        FeatureArc {
+            name: name.to_string(),
+            start: start.to_string(),
+            end: end.to_string(),
+            output,
+            features: Vec::new(),
+            enabled: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a feature node to the end of the arc. The features have to be registered before
+    /// the start node and the feature nodes of the arc are created
+    pub fn register(&mut self, feature: &str) -> bool {
+        if self.features.len() >= MAX_FEATURES || self.position(feature).is_some() {
+            return false;
+        }
+        self.features.push(feature.to_string());
+        true
+    }
+
+    /// The names of the nodes that have a copy of the arc - the start node and all features
+    pub fn nodes(&self) -> Vec<String> {
+        let mut v = vec![self.start.clone()];
+        v.extend(self.features.iter().cloned());
+        v
+    }
+
+    /// The next names a node on the arc has to add to its own next names: all the features
+    /// followed by the end node of the arc
+    pub fn next_names(&self) -> Vec<String> {
+        let mut v = self.features.clone();
+        v.push(self.end.clone());
+        v
+    }
+
+    /// The position on the arc right after the given feature node, to be passed to next()
+    /// by the feature node. The start node of the arc is at position zero
+    pub fn position(&self, feature: &str) -> Option<usize> {
+        self.features
+            .iter()
+            .position(|f| f == feature)
+            .map(|pos| pos + 1)
+    }
+
+    /// Enable or disable a feature on an interface, returns false if the feature is not
+    /// registered on the arc
+    pub fn enable(&mut self, ifindex: usize, feature: &str, enable: bool) -> bool {
+        let bit = match self.position(feature) {
+            Some(pos) => 1 << (pos - 1),
+            None => return false,
+        };
+        if ifindex >= self.enabled.len() {
+            self.enabled.resize(ifindex + 1, 0);
+        }
+        if enable {
+            self.enabled[ifindex] |= bit;
+        } else {
+            self.enabled[ifindex] &= !bit;
+        }
+        true
+    }
+
+    /// The names of the features enabled on an interface, in the arc order
+    pub fn features(&self, ifindex: usize) -> Vec<String> {
+        let bits = self.bits(ifindex);
+        self.features
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| bits & (1 << i) != 0)
+            .map(|(_, f)| f.clone())
+            .collect()
+    }
+
+    fn bits(&self, ifindex: usize) -> u64 {
+        if ifindex < self.enabled.len() {
+            self.enabled[ifindex]
+        } else {
+            0
+        }
+    }
+
+    fn ifindex(&self, pkt: &BoxPkt) -> usize {
+        if self.output {
+            pkt.out_ifindex
+        } else {
+            pkt.in_ifindex
+        }
+    }
+
+    /// Are there any features enabled on the interface of this packet
+    pub fn enabled(&self, pkt: &BoxPkt) -> bool {
+        self.bits(self.ifindex(pkt)) != 0
+    }
+
+    /// Where does the packet go from position 'pos' on the arc, the return value is an
+    /// index into next_names() - either the next enabled feature, or the end node
+    pub fn next(&self, pkt: &BoxPkt, pos: usize) -> usize {
+        let bits = self
+            .bits(self.ifindex(pkt))
+            .checked_shr(pos as u32)
+            .unwrap_or(0);
+        if bits == 0 {
+            self.features.len()
+        } else {
+            pos + bits.trailing_zeros() as usize
+        }
+    }
+}
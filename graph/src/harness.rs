@@ -0,0 +1,197 @@
+use super::*;
+use counters::flavors::CounterRO;
+use counters::CountersRO;
+use packet::PktsHeap;
+use std::sync::Mutex;
+
+const HARNESS_INPUT: &str = "harness_input";
+const NUM_PKTS: usize = 256;
+const NUM_PART: usize = 512;
+const PARTICLE_SZ: usize = 2048;
+
+/// A graph with just one node in it (other than the drop node), for the unit tests of the
+/// node. The packets handed to run() are queued to the node and the graph is run till the
+/// node is done with them, and the packets the node queued to its next nodes are handed
+/// back along with the names of those next nodes. Packets the node drops go to the drop
+/// node and are freed like in any other graph. The harness has a packet pool of its own
+/// for the tests to build packets from, the node allocates from the graph's pool
+pub struct Harness<T> {
+    graph: Graph<T>,
+    name: String,
+    pool: Box<dyn PacketPool>,
+    queue: Arc<ArrayQueue<BoxPkt>>,
+    input: Arc<Mutex<Vec<BoxPkt>>>,
+    output: Arc<Mutex<Vec<(String, BoxPkt)>>>,
+}
+
+fn harness_pool(
+    name: &str,
+    counters: &mut Counters,
+) -> (Box<dyn PacketPool>, Arc<ArrayQueue<BoxPkt>>) {
+    let queue = Arc::new(ArrayQueue::new(NUM_PKTS));
+    let pool = PktsHeap::new(
+        name,
+        queue.clone(),
+        counters,
+        NUM_PKTS,
+        NUM_PART,
+        PARTICLE_SZ,
+    );
+    (Box::new(pool), queue)
+}
+
+fn harness_init(name: &str, next_names: Vec<String>, counters: &mut Counters) -> GnodeInit {
+    GnodeInit {
+        name: name.to_string(),
+        next_names,
+        cntrs: GnodeCntrs::new(name, counters),
+        perf: Perf::new(name, counters),
+        depth: VEC_SIZE,
+    }
+}
+
+impl<T> Harness<T> {
+    /// Put the node 'node' with the name 'name' and the next nodes 'next_names' in a graph
+    pub fn new(
+        node: Box<dyn Gclient<T>>,
+        name: &str,
+        next_names: Vec<String>,
+        counters: &mut Counters,
+    ) -> Harness<T> {
+        let (pool, queue) = harness_pool("HARNESS_GRAPH", counters);
+        let mut graph = Graph::new(0, pool, queue, counters);
+        let input = Arc::new(Mutex::new(Vec::new()));
+        let output = Arc::new(Mutex::new(Vec::new()));
+
+        // The input node goes in first, so that the packets it queues to the node under
+        // test are dispatched in the same run of the graph
+        let init = harness_init(HARNESS_INPUT, vec![name.to_string()], counters);
+        let inject = Box::new(Inject {
+            input: input.clone(),
+        });
+        graph.add(inject, init);
+        let init = harness_init(name, next_names.clone(), counters);
+        graph.add(node, init);
+        for next in next_names.iter() {
+            if next == names::DROP || next == name {
+                continue;
+            }
+            let init = harness_init(next, vec![], counters);
+            let capture = Box::new(Capture {
+                name: next.clone(),
+                output: output.clone(),
+            });
+            graph.add(capture, init);
+        }
+        graph.finalize();
+
+        let (pool, queue) = harness_pool("HARNESS_PKTS", counters);
+        Harness {
+            graph,
+            name: name.to_string(),
+            pool,
+            queue,
+            input,
+            output,
+        }
+    }
+
+    /// A packet with 'data' in it, the data starts with an l3 header of 'l3_len' bytes
+    pub fn pkt(&mut self, data: &[u8], l3_len: usize) -> BoxPkt {
+        while let Ok(p) = self.queue.pop() {
+            self.pool.free(p);
+        }
+        let mut pkt = self.pool.pkt(0).unwrap();
+        assert!(pkt.append(&mut *self.pool, data));
+        assert!(pkt.set_l3(l3_len));
+        pkt
+    }
+
+    /// Send a control message to the node
+    pub fn control_msg(&mut self, message: T) -> bool {
+        let name = self.name.clone();
+        self.graph.control_msg(&name, message)
+    }
+
+    /// Run the packets through the node, returns the packets the node queued to its next
+    /// nodes (other than drop) in the order they were queued to each next node
+    pub fn run(&mut self, pkts: Vec<BoxPkt>) -> Vec<(String, BoxPkt)> {
+        self.input.lock().unwrap().extend(pkts);
+        loop {
+            let (work, wakeup) = self.graph.run();
+            if !work || wakeup != 0 {
+                break;
+            }
+        }
+        self.output.lock().unwrap().drain(..).collect()
+    }
+}
+
+/// The Harness for a node that has the usual name() and next_names() methods
+#[macro_export]
+macro_rules! harness {
+    ($node:expr, $counters:expr) => {{
+        let node = $node;
+        let name = node.name();
+        let next_names = node.next_names();
+        $crate::harness::Harness::new(Box::new(node), &name, next_names, $counters)
+    }};
+}
+
+/// All the data in the packet, as one buffer
+pub fn pkt_bytes(p: &BoxPkt) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut i = 0;
+    while let Some((data, len)) = p.data(i) {
+        bytes.extend_from_slice(&data[0..len]);
+        i += len;
+    }
+    bytes
+}
+
+/// The value of the counter 'name' of the node, in the counters named 'test'
+pub fn count(test: &str, node: &str, ctype: CounterType, name: &str) -> u64 {
+    let ro = CountersRO::new(test).unwrap();
+    let cntr = CounterRO::search(&ro, node, ctype, name).unwrap();
+    cntr.read(0)
+}
+
+// Queues the packets handed to the harness to the node under test
+struct Inject {
+    input: Arc<Mutex<Vec<BoxPkt>>>,
+}
+
+impl<T> Gclient<T> for Inject {
+    fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<T>> {
+        Box::new(Inject {
+            input: self.input.clone(),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        for p in self.input.lock().unwrap().drain(..) {
+            vectors.push(0, p);
+        }
+    }
+}
+
+// Stands in for a next node of the node under test, and collects the packets queued to it
+struct Capture {
+    name: String,
+    output: Arc<Mutex<Vec<(String, BoxPkt)>>>,
+}
+
+impl<T> Gclient<T> for Capture {
+    fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<T>> {
+        Box::new(Capture {
+            name: self.name.clone(),
+            output: self.output.clone(),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        while let Some(p) = vectors.pop() {
+            self.output.lock().unwrap().push((self.name.clone(), p));
+        }
+    }
+}
@@ -5,21 +5,35 @@ use log::Logger;
 use packet::BoxPkt;
 use packet::PacketPool;
 use perf::Perf;
+use std::cmp::min;
 use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::sync::Arc;
+use trace::Trace;
+
+pub mod feature;
+#[cfg(any(test, feature = "test-harness"))]
+pub mod harness;
+pub mod trace;
 
 // We preallocate space for these many graph nodes, of course it can grow beyond that,
 // but the goal is as much as possible to pre-allocate space
 const GRAPH_INIT_SZ: usize = 1024;
-/// The size of the packet queue to each graph node. Beyond this, packets to that node
-/// will get dropped
+/// The default size of the packet queue to each graph node. Beyond this, packets to that
+/// node will get dropped. Nodes can choose a different size via GnodeInit::depth
 pub const VEC_SIZE: usize = 256;
+/// Nodes processing packets in bursts (Dispatch::pop_burst) pick up these many packets at
+/// a time, a burst is small enough that the node can prefetch all the packets in the burst
+/// before it starts working on them, and they all stay in the cache till the node is done
+pub const BURST_SIZE: usize = 32;
 
 pub trait Driver: Sync {
     fn fd(&self) -> Option<i32>;
     fn sendmsg(&mut self, pool: &mut dyn PacketPool, pkt: BoxPkt) -> usize;
     fn recvmsg(&mut self, pool: &mut dyn PacketPool, headroom: usize) -> Option<BoxPkt>;
+    /// Release the resources like counters that the driver allocated, called when the
+    /// interface is deleted by the thread that owns the counters, like Gclient::free()
+    fn free(&self, _counters: &mut Counters) {}
 }
 
 /// Every graph node feature/client needs to implement these methods/APIs
@@ -34,18 +48,28 @@ pub trait Gclient<T>: Send {
     /// other graph nodes
     fn dispatch(&mut self, _thread: usize, _vectors: &mut Dispatch);
     /// This API is called when a node gets a message from control plane, like for example
-    /// to modify the nodes forwarding tables etc..
-    fn control_msg(&mut self, _thread: usize, _message: T) {}
+    /// to modify the nodes forwarding tables etc.. Returns false if the node does not know
+    /// what to do with the message, which is counted as an error against the node
+    fn control_msg(&mut self, _thread: usize, _message: T) -> bool {
+        false
+    }
+    /// This API is called when the node is removed from the graph, the node is expected to
+    /// release resources like counters that it allocated. This is called by the thread that
+    /// owns the counters (usually the control thread), not by the forwarding thread
+    fn free(&self, _counters: &mut Counters) {}
 }
 
 /// This structure provides methods to get packets queued up for a node, and for
 /// the node to queue up packets to other nodes
 pub struct Dispatch<'d> {
     node: usize,
+    name: &'d str,
     pub pool: &'d mut dyn PacketPool,
     vectors: &'d mut Vec<VecDeque<BoxPkt>>,
+    depths: &'d Vec<usize>,
     counters: &'d mut Vec<GnodeCntrs>,
     nodes: &'d Vec<usize>,
+    trace: &'d Trace,
     work: bool,
     wakeup: usize,
 }
@@ -53,13 +77,52 @@ pub struct Dispatch<'d> {
 impl Dispatch<'_> {
     /// Get one of the packets queued up for a node
     pub fn pop(&mut self) -> Option<BoxPkt> {
-        self.vectors[self.node].pop_front()
+        let pkt = self.vectors[self.node].pop_front();
+        if let Some(ref p) = pkt {
+            if p.trace != 0 {
+                self.trace.record(p.trace, self.name);
+            }
+        }
+        pkt
+    }
+
+    /// Get upto 'max' of the packets queued up for a node, the packets are appended to
+    /// 'burst'. Returns the number of packets added to 'burst'
+    pub fn pop_burst(&mut self, burst: &mut Vec<BoxPkt>, max: usize) -> usize {
+        let vector = &mut self.vectors[self.node];
+        let cnt = min(max, vector.len());
+        for p in vector.drain(0..cnt) {
+            if p.trace != 0 {
+                self.trace.record(p.trace, self.name);
+            }
+            burst.push(p);
+        }
+        cnt
     }
 
-    /// Queue one packet to another node
+    /// Start tracing the packet if packet trace is enabled for the packet's input interface.
+    /// This is called by the nodes that bring packets into the graph, like interface nodes
+    pub fn trace_start(&mut self, pkt: &mut BoxPkt) {
+        let id = self.trace.start(pkt.in_ifindex);
+        if id != 0 {
+            pkt.trace = id;
+            self.trace.record(id, self.name);
+        }
+    }
+
+    /// Add a summary of what the node did with the packet to the packet's trace. The summary
+    /// is generated by calling f(), which is called only if the packet is being traced
+    pub fn trace<F: FnOnce() -> String>(&self, pkt: &BoxPkt, f: F) {
+        if pkt.trace != 0 {
+            self.trace.summary(pkt.trace, self.name, f());
+        }
+    }
+
+    /// Queue one packet to another node. If the other node's queue is full, the packet
+    /// is dropped and counted as a GraphDrop of the other node
     pub fn push(&mut self, node: usize, pkt: BoxPkt) -> bool {
         let node = self.nodes[node];
-        if self.vectors[node].capacity() >= 1 {
+        if self.vectors[node].len() < self.depths[node] {
             self.vectors[node].push_back(pkt);
             if node <= self.node {
                 self.work = true;
@@ -73,6 +136,32 @@ impl Dispatch<'_> {
         }
     }
 
+    /// Queue all the packets in 'burst' to another node, 'burst' is left empty. The packets
+    /// that dont fit in the other node's queue are dropped and counted as GraphDrops of the
+    /// other node. Returns the number of packets queued
+    pub fn push_burst(&mut self, node: usize, burst: &mut Vec<BoxPkt>) -> usize {
+        if burst.is_empty() {
+            return 0;
+        }
+        let node = self.nodes[node];
+        let room = self.depths[node].saturating_sub(self.vectors[node].len());
+        let cnt = min(room, burst.len());
+        let drops = burst.len() - cnt;
+        self.vectors[node].extend(burst.drain(0..cnt));
+        burst.clear();
+        if cnt != 0 {
+            if node <= self.node {
+                self.work = true;
+                self.wakeup = 0;
+            }
+            self.counters[node].enqed.add(cnt as u64);
+        }
+        if drops != 0 {
+            self.counters[node].drops.add(drops as u64);
+        }
+        cnt
+    }
+
     /// Specify the time when this node has work again/needs to be scheduled again
     /// wakeup of zero means it has work right now, non zero wakeup indicates time
     /// in nanoseconds from now when the node has work
@@ -98,6 +187,9 @@ pub struct GnodeInit {
     /// A set of generic counters that tracks the node's enqueue/dequeue/drops etc..
     pub cntrs: GnodeCntrs,
     pub perf: Perf,
+    /// The maximum number of packets that can be queued up to the node, packets beyond
+    /// that are dropped. VEC_SIZE is a good default
+    pub depth: usize,
 }
 
 impl GnodeInit {
@@ -107,23 +199,64 @@ impl GnodeInit {
             next_names: self.next_names.clone(),
             cntrs: GnodeCntrs::new(&self.name, counters),
             perf: Perf::new(&self.name, counters),
+            depth: self.depth,
         }
     }
+
+    /// Release the generic counters and perf counters of a node
+    pub fn free(&self, counters: &mut Counters) {
+        self.cntrs.free(counters);
+        self.perf.free(counters);
+    }
 }
 
 pub struct GnodeCntrs {
     enqed: Counter,
     drops: Counter,
+    unknown_msg: Counter,
 }
 
 impl GnodeCntrs {
     pub fn new(name: &str, counters: &mut Counters) -> GnodeCntrs {
         let enqed = Counter::new(counters, name, CounterType::Pkts, "GraphEnq");
         let drops = Counter::new(counters, name, CounterType::Error, "GraphDrop");
-        GnodeCntrs { enqed, drops }
+        let unknown_msg = Counter::new(counters, name, CounterType::Error, "GraphUnknownMsg");
+        GnodeCntrs {
+            enqed,
+            drops,
+            unknown_msg,
+        }
+    }
+
+    pub fn free(&self, counters: &mut Counters) {
+        self.enqed.free(counters);
+        self.drops.free(counters);
+        self.unknown_msg.free(counters);
     }
 }
 
+/// A snapshot of a graph node, its edges and its statistics - used to display the graph
+pub struct GnodeInfo {
+    pub name: String,
+    /// Index of the node in the graph
+    pub index: usize,
+    /// Names of all the nodes this node has edges to
+    pub next_names: Vec<String>,
+    /// Node indices that next_names resolved to, zero (drop) if the node doesnt exist
+    pub next_nodes: Vec<usize>,
+    /// Number of packets queued up for the node
+    pub depth: usize,
+    /// Number of packets enqueued to the node
+    pub enqed: u64,
+    /// Number of packets dropped because the node's queue was full
+    pub drops: u64,
+    /// Average number of cycles the node spends in each dispatch
+    pub perf_avg: u64,
+    /// Average number of cycles the node spends per packet queued to it, counting only the
+    /// dispatches that had packets to work on
+    pub perf_pkt: u64,
+}
+
 // The Gnode structure holds the exact node feature/client object and some metadata
 // associated with the client
 struct Gnode<T> {
@@ -168,6 +301,8 @@ pub struct Graph<T> {
     perf: Vec<Perf>,
     // A per node packet queue, to hold packets from other nodes to this node
     vectors: Vec<VecDeque<BoxPkt>>,
+    // The maximum number of packets each node's queue can hold
+    depths: Vec<usize>,
     // Generic enq/deq/drop counters per node
     counters: Vec<GnodeCntrs>,
     // Each graph node has an index which is an offset into the nodes Vec in this structure.
@@ -177,6 +312,8 @@ pub struct Graph<T> {
     pool: Box<dyn PacketPool>,
     // Freed packets are queued here
     queue: Arc<ArrayQueue<BoxPkt>>,
+    // Packet trace, shared with the control thread
+    trace: Arc<Trace>,
 }
 
 impl<T> Graph<T> {
@@ -193,16 +330,19 @@ impl<T> Graph<T> {
             nodes: Vec::with_capacity(GRAPH_INIT_SZ),
             perf: Vec::with_capacity(GRAPH_INIT_SZ),
             vectors: Vec::with_capacity(GRAPH_INIT_SZ),
+            depths: Vec::with_capacity(GRAPH_INIT_SZ),
             counters: Vec::with_capacity(GRAPH_INIT_SZ),
             indices: HashMap::with_capacity(GRAPH_INIT_SZ),
             pool,
             queue,
+            trace: Arc::new(Trace::new(thread)),
         };
         let init = GnodeInit {
             name: names::DROP.to_string(),
             next_names: vec![],
             cntrs: GnodeCntrs::new(names::DROP, counters),
             perf: Perf::new(names::DROP, counters),
+            depth: VEC_SIZE,
         };
         let count = Counter::new(counters, names::DROP, CounterType::Pkts, "count");
         g.add(Box::new(DropNode { count }), init);
@@ -223,10 +363,10 @@ impl<T> Graph<T> {
         let mut perf = Vec::with_capacity(GRAPH_INIT_SZ);
         let mut vectors = Vec::with_capacity(GRAPH_INIT_SZ);
         let mut cntrs = Vec::with_capacity(GRAPH_INIT_SZ);
-        for n in self.nodes.iter() {
+        for (n, depth) in self.nodes.iter().zip(self.depths.iter()) {
             nodes.push(n.clone(counters, log.clone()));
             perf.push(Perf::new(&n.name, counters));
-            vectors.push(VecDeque::with_capacity(VEC_SIZE));
+            vectors.push(VecDeque::with_capacity(*depth));
             cntrs.push(GnodeCntrs::new(&n.name, counters));
         }
         Graph {
@@ -234,13 +374,21 @@ impl<T> Graph<T> {
             nodes,
             perf,
             vectors,
+            depths: self.depths.clone(),
             counters: cntrs,
             indices: self.indices.clone(),
             pool,
             queue,
+            trace: Arc::new(Trace::new(thread)),
         }
     }
 
+    /// Use the given packet trace for this graph, the trace is usually shared with the
+    /// control thread so that it can enable tracing and display the traced packets
+    pub fn set_trace(&mut self, trace: Arc<Trace>) {
+        self.trace = trace;
+    }
+
     /// Add a new feature/client node to the graph.
     pub fn add(&mut self, client: Box<dyn Gclient<T>>, init: GnodeInit) {
         let index = self.index(&init.name);
@@ -251,12 +399,66 @@ impl<T> Graph<T> {
         self.nodes
             .push(Gnode::new(client, init.name.clone(), init.next_names));
         self.perf.push(init.perf);
-        self.vectors.push(VecDeque::with_capacity(VEC_SIZE));
+        self.vectors.push(VecDeque::with_capacity(init.depth));
+        self.depths.push(init.depth);
         self.counters.push(init.cntrs);
         let index = self.nodes.len() - 1; // 0 based index
         self.indices.insert(init.name, index);
     }
 
+    /// Remove a feature/client node from the graph. The node is handed back to the caller
+    /// along with its init parameters, so that the caller can release the resources held
+    /// by the node (like counters). Any packets queued up for the node are dropped, and
+    /// nodes that had this node as their next node will send packets to the drop node.
+    pub fn del(&mut self, name: &str) -> Option<(Box<dyn Gclient<T>>, GnodeInit)> {
+        let index = self.index(name);
+        if index == 0 {
+            return None; // Unknown node, and the drop node itself is never deleted
+        }
+        self.indices.remove(name);
+        // The last node in the graph gets moved into the slot of the deleted node
+        let node = self.nodes.swap_remove(index);
+        let perf = self.perf.swap_remove(index);
+        let cntrs = self.counters.swap_remove(index);
+        self.vectors.swap_remove(index);
+        let depth = self.depths.swap_remove(index);
+        if index < self.nodes.len() {
+            self.indices.insert(self.nodes[index].name.clone(), index);
+        }
+        // Node indices have changed, so resolve the next_names of every node again
+        self.finalize();
+        let init = GnodeInit {
+            name: node.name,
+            next_names: node.next_names,
+            cntrs,
+            perf,
+            depth,
+        };
+        Some((node.client, init))
+    }
+
+    /// Add a next node to an existing node, returns the index of the new edge - the index
+    /// the node uses to push packets to the next node. If the node already has the next node,
+    /// the index of the existing edge is returned. The next node need not be in the graph yet,
+    /// packets on the edge go to the drop node till the next node is added. Returns None if
+    /// the node itself is not in the graph
+    pub fn add_edge(&mut self, name: &str, next: &str) -> Option<usize> {
+        let index = self.index(name);
+        if index == 0 {
+            return None;
+        }
+        let next_index = self.index(next);
+        let node = &mut self.nodes[index];
+        if let Some(edge) = node.next_names.iter().position(|n| n == next) {
+            return Some(edge);
+        }
+        node.next_names.push(next.to_string());
+        let edge = node.next_names.len() - 1;
+        node.next_nodes.resize(edge + 1, 0);
+        node.next_nodes[edge] = next_index;
+        Some(edge)
+    }
+
     fn index(&self, name: &str) -> usize {
         if let Some(&index) = self.indices.get(name) {
             index
@@ -297,18 +499,22 @@ impl<T> Graph<T> {
         for n in 0..self.nodes.len() {
             let node = &mut self.nodes[n];
             let client = &mut node.client;
+            let pkts = self.vectors[n].len();
             let mut d = Dispatch {
                 node: n,
+                name: &node.name,
                 pool: &mut *self.pool,
                 vectors: &mut self.vectors,
+                depths: &self.depths,
                 counters: &mut self.counters,
                 nodes: &node.next_nodes,
+                trace: &self.trace,
                 work: false,
                 wakeup: usize::MAX,
             };
             self.perf[n].start();
             client.dispatch(self.thread, &mut d);
-            self.perf[n].stop();
+            self.perf[n].stop(pkts);
             // Does client have more work pending, and when does it need to do that work ?
             if d.work {
                 work = true;
@@ -320,13 +526,36 @@ impl<T> Graph<T> {
         (work, nsecs)
     }
 
+    /// Get a snapshot of all the nodes in the graph, in the order of their indices
+    pub fn info(&self) -> Vec<GnodeInfo> {
+        let mut info = Vec::with_capacity(self.nodes.len());
+        for (index, node) in self.nodes.iter().enumerate() {
+            info.push(GnodeInfo {
+                name: node.name.clone(),
+                index,
+                next_names: node.next_names.clone(),
+                next_nodes: node.next_nodes.clone(),
+                depth: self.vectors[index].len(),
+                enqed: self.counters[index].enqed.get(),
+                drops: self.counters[index].drops.get(),
+                perf_avg: self.perf[index].get_avg(),
+                perf_pkt: self.perf[index].get_avg_pkt(),
+            });
+        }
+        info
+    }
+
+    /// Hand over a control message to the named node. Returns false if there is no such node
+    /// or if the node did not understand the message
     pub fn control_msg(&mut self, name: &str, message: T) -> bool {
         let index = self.index(name);
         if index == 0 {
             false
-        } else {
-            self.nodes[index].client.control_msg(self.thread, message);
+        } else if self.nodes[index].client.control_msg(self.thread, message) {
             true
+        } else {
+            self.counters[index].unknown_msg.incr();
+            false
         }
     }
 }
@@ -346,6 +575,10 @@ impl<T> Gclient<T> for DropNode {
             self.count.incr();
         }
     }
+
+    fn free(&self, counters: &mut Counters) {
+        self.count.free(counters);
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@ use packet::{PacketPool, PktsHeap};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
+use trace::TRACE_THREAD_SHIFT;
 
 const NUM_PKTS: usize = 10;
 const NUM_PART: usize = 20;
@@ -99,7 +100,10 @@ impl RxNode {
     }
 }
 
-struct TestMsg {}
+// Messages to test nodes, the nodes pretend to understand only the known ones
+struct TestMsg {
+    known: bool,
+}
 
 impl Gclient<TestMsg> for RxNode {
     fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<TestMsg>> {
@@ -114,7 +118,8 @@ impl Gclient<TestMsg> for RxNode {
         if self.affinity.is_some() && (self.affinity != Some(thread)) {
             return;
         }
-        let pkt = new_pkt(vectors.pool, self.count);
+        let mut pkt = new_pkt(vectors.pool, self.count);
+        vectors.trace_start(&mut pkt);
         vectors.push(Next::PRINT as usize, pkt);
         self.count += 1;
         self.total_count.fetch_add(1, Ordering::Relaxed);
@@ -205,13 +210,101 @@ impl Gclient<TestMsg> for PrintNode {
     fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
         while let Some(mut pkt) = vectors.pop() {
             validate_pkt(&mut pkt, self.count);
+            vectors.trace(&pkt, || format!("seq {}", self.count));
             self.count += 1;
             self.total_count.fetch_add(1, Ordering::Relaxed);
             vectors.push(Next::TX as usize, pkt);
         }
     }
+
+    fn control_msg(&mut self, _thread: usize, message: TestMsg) -> bool {
+        message.known
+    }
+}
+
+// Brings in a burst of packets every time its scheduled, the packet sequence numbers are
+// advanced only by the packets that made it into the next node's queue
+struct BurstRxNode {
+    count: usize,
+    total_count: Arc<AtomicUsize>,
+}
+
+impl BurstRxNode {
+    fn new() -> BurstRxNode {
+        BurstRxNode {
+            count: 0,
+            total_count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn name(&self) -> String {
+        "RX".to_string()
+    }
+}
+
+impl Gclient<TestMsg> for BurstRxNode {
+    fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<TestMsg>> {
+        Box::new(BurstRxNode {
+            count: 0,
+            total_count: self.total_count.clone(),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        let mut burst = Vec::new();
+        for i in 0..BURST_PKTS {
+            burst.push(new_pkt(vectors.pool, self.count + i));
+        }
+        let queued = vectors.push_burst(Next::PRINT as usize, &mut burst);
+        assert!(burst.is_empty());
+        self.count += queued;
+        self.total_count.fetch_add(queued, Ordering::Relaxed);
+    }
+}
+
+// Same as the PrintNode, but works on bursts of packets
+struct BurstPrintNode {
+    count: usize,
+    total_count: Arc<AtomicUsize>,
+}
+
+impl BurstPrintNode {
+    fn new() -> BurstPrintNode {
+        BurstPrintNode {
+            count: 0,
+            total_count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn name(&self) -> String {
+        "PRINT".to_string()
+    }
+}
+
+impl Gclient<TestMsg> for BurstPrintNode {
+    fn clone(&self, _counters: &mut Counters, _log: Arc<Logger>) -> Box<dyn Gclient<TestMsg>> {
+        Box::new(BurstPrintNode {
+            count: 0,
+            total_count: self.total_count.clone(),
+        })
+    }
+
+    fn dispatch(&mut self, _thread: usize, vectors: &mut Dispatch) {
+        let mut burst = Vec::new();
+        while vectors.pop_burst(&mut burst, 2) != 0 {
+            assert!(burst.len() <= 2);
+            for pkt in burst.iter_mut() {
+                validate_pkt(pkt, self.count);
+                self.count += 1;
+                self.total_count.fetch_add(1, Ordering::Relaxed);
+            }
+            vectors.push_burst(Next::TX as usize, &mut burst);
+        }
+    }
 }
 
+const BURST_PKTS: usize = 4;
+
 #[test]
 // Single thread: rx, print, tx all runs in the same thread. Note that each node has
 // Rc + atomic self.total_count because once a node is cloned, its non-shared self.count
@@ -233,6 +326,7 @@ fn single_thread() {
         next_names: rx.next_names(0),
         cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
         perf: Perf::new(&rx.name(), &mut counters),
+        depth: VEC_SIZE,
     };
     graph.add(rx.clone(&mut counters, log.clone()), init);
 
@@ -241,6 +335,7 @@ fn single_thread() {
         next_names: tx.next_names(),
         cntrs: GnodeCntrs::new(&tx.name(), &mut counters),
         perf: Perf::new(&tx.name(), &mut counters),
+        depth: VEC_SIZE,
     };
     graph.add(tx.clone(&mut counters, log.clone()), init);
 
@@ -249,6 +344,7 @@ fn single_thread() {
         next_names: print.next_names(),
         cntrs: GnodeCntrs::new(&print.name(), &mut counters),
         perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
     };
     graph.add(print.clone(&mut counters, log), init);
 
@@ -265,6 +361,20 @@ fn single_thread() {
     assert_eq!(rcnt, test_count);
     assert_eq!(tcnt, rcnt - 1);
     assert_eq!(pcnt, rcnt);
+
+    // TX runs before PRINT, so the last packet from PRINT is still queued up for TX
+    let info = graph.info();
+    assert_eq!(info.len(), 4);
+    assert_eq!(info[0].name, names::DROP);
+    assert_eq!(info[1].name, rx.name());
+    // The RX node has a next name "RX0" that doesnt exist, that resolves to the drop node
+    assert_eq!(info[1].next_nodes, vec![0, 3, 2]);
+    assert_eq!(info[2].name, tx.name());
+    assert_eq!(info[2].enqed, test_count as u64);
+    assert_eq!(info[2].depth, 1);
+    assert_eq!(info[3].name, print.name());
+    assert_eq!(info[3].enqed, test_count as u64);
+    assert_eq!(info[3].depth, 0);
 }
 
 #[test]
@@ -292,6 +402,7 @@ fn multi_thread() {
             next_names: rx.next_names(0),
             cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
             perf: Perf::new(&rx.name(), &mut counters),
+            depth: VEC_SIZE,
         };
         graph.add(rx.clone(&mut counters, log.clone()), init);
         rx_vec.push(rx);
@@ -302,6 +413,7 @@ fn multi_thread() {
         next_names: tx.next_names(),
         cntrs: GnodeCntrs::new(&tx.name(), &mut counters),
         perf: Perf::new(&tx.name(), &mut counters),
+        depth: VEC_SIZE,
     };
     graph.add(tx.clone(&mut counters, log.clone()), init);
 
@@ -310,6 +422,7 @@ fn multi_thread() {
         next_names: print.next_names(),
         cntrs: GnodeCntrs::new(&print.name(), &mut counters),
         perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
     };
     graph.add(print.clone(&mut counters, log.clone()), init);
 
@@ -345,3 +458,407 @@ fn multi_thread() {
     assert_eq!(tcnt, rcnt - 1);
     assert_eq!(pcnt, rcnt);
 }
+
+#[test]
+// Delete a node from the graph, the nodes that had the deleted node as their next node
+// should now be sending packets to the drop node. And adding the node back should restore
+// the packet path
+fn node_delete() {
+    let mut counters = match Counters::new("r2_graph_delete") {
+        Ok(c) => c,
+        Err(errno) => panic!("Unable to create counters, errno {}", errno),
+    };
+    let log = Arc::new(Logger::new("r2_logs", 32, 1000).unwrap());
+    let (pool, queue) = packet_pool("node_delete");
+    let mut graph = Graph::new(0, pool, queue, &mut counters);
+    let rx = Box::new(RxNode::new(Some(0)));
+    let tx = Box::new(TxNode::new());
+    let print = Box::new(PrintNode::new());
+
+    let init = GnodeInit {
+        name: rx.name(),
+        next_names: rx.next_names(0),
+        cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
+        perf: Perf::new(&rx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(rx.clone(&mut counters, log.clone()), init);
+
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: print.next_names(),
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(print.clone(&mut counters, log.clone()), init);
+
+    let init = GnodeInit {
+        name: tx.name(),
+        next_names: tx.next_names(),
+        cntrs: GnodeCntrs::new(&tx.name(), &mut counters),
+        perf: Perf::new(&tx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(tx.clone(&mut counters, log.clone()), init);
+
+    graph.finalize();
+
+    // The drop node and unknown nodes cant be deleted
+    assert!(graph.del(names::DROP).is_none());
+    assert!(graph.del("UNKNOWN").is_none());
+
+    let (node, init) = graph.del(&print.name()).unwrap();
+    assert_eq!(init.name, print.name());
+    node.free(&mut counters);
+    init.free(&mut counters);
+    assert!(graph.del(&print.name()).is_none());
+
+    let test_count = 10;
+    for _ in 0..test_count {
+        graph.run();
+    }
+    assert_eq!(rx.total_count.load(Ordering::Relaxed), test_count);
+    assert_eq!(print.total_count.load(Ordering::Relaxed), 0);
+    assert_eq!(tx.total_count.load(Ordering::Relaxed), 0);
+
+    // The rx node starts its sequence numbers afresh, so does the new print node
+    let (node, init) = graph.del(&rx.name()).unwrap();
+    node.free(&mut counters);
+    init.free(&mut counters);
+    let rx = Box::new(RxNode::new(Some(0)));
+    let init = GnodeInit {
+        name: rx.name(),
+        next_names: rx.next_names(0),
+        cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
+        perf: Perf::new(&rx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(rx.clone(&mut counters, log.clone()), init);
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: print.next_names(),
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(print.clone(&mut counters, log), init);
+    graph.finalize();
+
+    for _ in 0..test_count {
+        graph.run();
+    }
+    let rcnt = rx.total_count.load(Ordering::Relaxed);
+    assert_eq!(rcnt, test_count);
+    assert_eq!(print.total_count.load(Ordering::Relaxed), rcnt);
+    assert!(tx.total_count.load(Ordering::Relaxed) >= rcnt - 1);
+}
+
+#[test]
+// Trace a couple of packets, each traced packet should have a record from every node
+// it visited, in the order it visited them, and untraced packets should have none
+fn packet_trace() {
+    let mut counters = match Counters::new("r2_graph_trace") {
+        Ok(c) => c,
+        Err(errno) => panic!("Unable to create counters, errno {}", errno),
+    };
+    let log = Arc::new(Logger::new("r2_logs", 32, 1000).unwrap());
+    let (pool, queue) = packet_pool("packet_trace");
+    let mut graph = Graph::new(0, pool, queue, &mut counters);
+    let trace = Arc::new(Trace::new(0));
+    graph.set_trace(trace.clone());
+    let rx = Box::new(RxNode::new(Some(0)));
+    let tx = Box::new(TxNode::new());
+    let print = Box::new(PrintNode::new());
+
+    let init = GnodeInit {
+        name: rx.name(),
+        next_names: rx.next_names(0),
+        cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
+        perf: Perf::new(&rx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(rx.clone(&mut counters, log.clone()), init);
+
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: print.next_names(),
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(print.clone(&mut counters, log.clone()), init);
+
+    let init = GnodeInit {
+        name: tx.name(),
+        next_names: tx.next_names(),
+        cntrs: GnodeCntrs::new(&tx.name(), &mut counters),
+        perf: Perf::new(&tx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(tx.clone(&mut counters, log), init);
+
+    graph.finalize();
+
+    // The test packets all have an in_ifindex of zero
+    trace.enable(0, 2);
+    for _ in 0..5 {
+        graph.run();
+    }
+
+    let mut records = Vec::new();
+    trace.walk(|r| records.push((r.id, r.node.clone(), r.summary.clone())));
+    assert_eq!(records.len(), 6);
+    for id in 1..3 {
+        let pkt: Vec<&(usize, String, String)> = records.iter().filter(|r| r.0 == id).collect();
+        assert_eq!(pkt.len(), 3);
+        assert_eq!(pkt[0].1, rx.name());
+        assert_eq!(pkt[1].1, print.name());
+        assert_eq!(pkt[1].2, format!("seq {}", id - 1));
+        assert_eq!(pkt[2].1, tx.name());
+    }
+
+    trace.clear();
+    graph.run();
+    let mut count = 0;
+    trace.walk(|_| count += 1);
+    assert_eq!(count, 0);
+}
+
+#[test]
+// Packets traced on different threads should never get the same trace id, the records a
+// packet leaves on another thread after a handoff are matched up using the id
+fn trace_ids_unique() {
+    let t0 = Trace::new(0);
+    let t1 = Trace::new(1);
+    t0.enable(0, 2);
+    t1.enable(0, 2);
+    let ids = [t0.start(0), t0.start(0), t1.start(0), t1.start(0)];
+    assert_eq!(ids[0], 1);
+    assert_eq!(ids[1], 2);
+    assert_eq!(ids[2], (1 << TRACE_THREAD_SHIFT) | 1);
+    assert_eq!(ids[3], (1 << TRACE_THREAD_SHIFT) | 2);
+    assert_eq!(t0.start(0), 0);
+}
+
+#[test]
+// A node whose queue is full should have packets to it dropped and counted as drops
+fn queue_full() {
+    let mut counters = match Counters::new("r2_graph_qfull") {
+        Ok(c) => c,
+        Err(errno) => panic!("Unable to create counters, errno {}", errno),
+    };
+    let log = Arc::new(Logger::new("r2_logs", 32, 1000).unwrap());
+    let (pool, queue) = packet_pool("queue_full");
+    let mut graph = Graph::new(0, pool, queue, &mut counters);
+    let rx = Box::new(RxNode::new(Some(0)));
+    let print = Box::new(PrintNode::new());
+
+    let init = GnodeInit {
+        name: rx.name(),
+        next_names: rx.next_names(0),
+        cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
+        perf: Perf::new(&rx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(rx.clone(&mut counters, log.clone()), init);
+
+    // The print node cant queue up any packets at all
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: print.next_names(),
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: 0,
+    };
+    graph.add(print.clone(&mut counters, log), init);
+
+    graph.finalize();
+
+    let test_count = 10;
+    for _ in 0..test_count {
+        graph.run();
+    }
+    assert_eq!(rx.total_count.load(Ordering::Relaxed), test_count);
+    assert_eq!(print.total_count.load(Ordering::Relaxed), 0);
+    let info = graph.info();
+    assert_eq!(info[2].name, print.name());
+    assert_eq!(info[2].enqed, 0);
+    assert_eq!(info[2].drops, test_count as u64);
+}
+
+#[test]
+// Packets pushed and popped in bursts, the bursts that dont fit in a queue are tail dropped
+fn burst() {
+    let mut counters = match Counters::new("r2_graph_burst") {
+        Ok(c) => c,
+        Err(errno) => panic!("Unable to create counters, errno {}", errno),
+    };
+    let log = Arc::new(Logger::new("r2_logs", 32, 1000).unwrap());
+    let (pool, queue) = packet_pool("burst");
+    let mut graph = Graph::new(0, pool, queue, &mut counters);
+    let rx = Box::new(BurstRxNode::new());
+    let print = Box::new(BurstPrintNode::new());
+    let tx = Box::new(TxNode::new());
+
+    let init = GnodeInit {
+        name: rx.name(),
+        next_names: tx.next_names(),
+        cntrs: GnodeCntrs::new(&rx.name(), &mut counters),
+        perf: Perf::new(&rx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(rx.clone(&mut counters, log.clone()), init);
+
+    // The print node can queue up only three of the four packets in each burst
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: tx.next_names(),
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: BURST_PKTS - 1,
+    };
+    graph.add(print.clone(&mut counters, log.clone()), init);
+
+    let init = GnodeInit {
+        name: tx.name(),
+        next_names: tx.next_names(),
+        cntrs: GnodeCntrs::new(&tx.name(), &mut counters),
+        perf: Perf::new(&tx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(tx.clone(&mut counters, log), init);
+
+    graph.finalize();
+
+    let test_count = 10;
+    for _ in 0..test_count {
+        graph.run();
+    }
+    let queued = test_count * (BURST_PKTS - 1);
+    assert_eq!(rx.total_count.load(Ordering::Relaxed), queued);
+    assert_eq!(print.total_count.load(Ordering::Relaxed), queued);
+    assert_eq!(tx.total_count.load(Ordering::Relaxed), queued);
+    let info = graph.info();
+    assert_eq!(info[2].name, print.name());
+    assert_eq!(info[2].enqed, queued as u64);
+    assert_eq!(info[2].drops, test_count as u64);
+    assert_eq!(info[3].name, tx.name());
+    assert_eq!(info[3].enqed, queued as u64);
+    assert_eq!(info[3].drops, 0);
+}
+
+#[test]
+// Packets should go through only the features enabled on their interface, in arc order
+fn feature_arc() {
+    let (mut pool, _queue) = packet_pool("feature_arc");
+    let mut arc = feature::FeatureArc::new("arc", "START", "END", false);
+    assert!(arc.register("F1"));
+    assert!(arc.register("F2"));
+    assert!(arc.register("F3"));
+    assert!(!arc.register("F2"));
+    assert_eq!(arc.nodes(), vec!["START", "F1", "F2", "F3"]);
+    assert_eq!(arc.next_names(), vec!["F1", "F2", "F3", "END"]);
+
+    assert!(arc.enable(1, "F1", true));
+    assert!(arc.enable(1, "F3", true));
+    assert!(!arc.enable(1, "F4", true));
+    assert_eq!(arc.features(1), vec!["F1", "F3"]);
+
+    let mut pkt = new_pkt(&mut *pool, 0);
+    pkt.in_ifindex = 1;
+    assert!(arc.enabled(&pkt));
+    assert_eq!(arc.next(&pkt, 0), 0);
+    assert_eq!(arc.next(&pkt, arc.position("F1").unwrap()), 2);
+    assert_eq!(arc.next(&pkt, arc.position("F3").unwrap()), 3);
+
+    // No features on this interface, straight to the end of the arc
+    pkt.in_ifindex = 2;
+    assert!(!arc.enabled(&pkt));
+    assert_eq!(arc.next(&pkt, 0), 3);
+
+    pkt.in_ifindex = 1;
+    assert!(arc.enable(1, "F1", false));
+    assert_eq!(arc.next(&pkt, 0), 2);
+    assert!(arc.enable(1, "F3", false));
+    assert!(!arc.enabled(&pkt));
+}
+
+#[test]
+// Edges added at runtime should resolve to the next node, once the next node is in the graph
+fn edge_add() {
+    let mut counters = match Counters::new("r2_graph_edge") {
+        Ok(c) => c,
+        Err(errno) => panic!("Unable to create counters, errno {}", errno),
+    };
+    let log = Arc::new(Logger::new("r2_logs", 32, 1000).unwrap());
+    let (pool, queue) = packet_pool("edge_add");
+    let mut graph = Graph::new(0, pool, queue, &mut counters);
+    let print = Box::new(PrintNode::new());
+    let tx = Box::new(TxNode::new());
+
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: vec![names::DROP.to_string()],
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(print.clone(&mut counters, log.clone()), init);
+    graph.finalize();
+
+    assert_eq!(graph.add_edge("NOSUCHNODE", &tx.name()), None);
+    // TX is not in the graph yet, so the edge goes to the drop node
+    assert_eq!(graph.add_edge(&print.name(), &tx.name()), Some(1));
+    let info = graph.info();
+    assert_eq!(info[1].next_names, vec![names::DROP, "TX"]);
+    assert_eq!(info[1].next_nodes, vec![0, 0]);
+
+    let init = GnodeInit {
+        name: tx.name(),
+        next_names: tx.next_names(),
+        cntrs: GnodeCntrs::new(&tx.name(), &mut counters),
+        perf: Perf::new(&tx.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(tx.clone(&mut counters, log), init);
+    graph.finalize();
+    let info = graph.info();
+    assert_eq!(info[1].next_nodes, vec![0, 2]);
+
+    // Adding an edge that already exists gives back the same edge, an edge to an
+    // existing node resolves right away
+    assert_eq!(graph.add_edge(&print.name(), &tx.name()), Some(1));
+    assert_eq!(graph.add_edge(&print.name(), &print.name()), Some(2));
+    let info = graph.info();
+    assert_eq!(info[1].next_nodes, vec![0, 2, 1]);
+}
+
+#[test]
+fn control_msg() {
+    let mut counters = match Counters::new("r2_graph_msg") {
+        Ok(c) => c,
+        Err(errno) => panic!("Unable to create counters, errno {}", errno),
+    };
+    let log = Arc::new(Logger::new("r2_logs", 32, 1000).unwrap());
+    let (pool, queue) = packet_pool("control_msg");
+    let mut graph = Graph::new(0, pool, queue, &mut counters);
+    let print = Box::new(PrintNode::new());
+    let init = GnodeInit {
+        name: print.name(),
+        next_names: print.next_names(),
+        cntrs: GnodeCntrs::new(&print.name(), &mut counters),
+        perf: Perf::new(&print.name(), &mut counters),
+        depth: VEC_SIZE,
+    };
+    graph.add(print.clone(&mut counters, log), init);
+    graph.finalize();
+
+    assert!(graph.control_msg(&print.name(), TestMsg { known: true }));
+    assert_eq!(graph.counters[1].unknown_msg.get(), 0);
+    // Messages the node does not understand are counted against the node
+    assert!(!graph.control_msg(&print.name(), TestMsg { known: false }));
+    assert_eq!(graph.counters[1].unknown_msg.get(), 1);
+    assert!(!graph.control_msg("NOSUCHNODE", TestMsg { known: true }));
+    assert_eq!(graph.counters[1].unknown_msg.get(), 1);
+}
@@ -0,0 +1,120 @@
+use common::time_nsecs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// We dont want a forgotten trace to keep eating memory, so we stop recording once we have
+// these many records, the trace has to be cleared to start recording again
+const TRACE_MAX_RECORDS: usize = 4096;
+
+/// The trace id of a packet has the thread that started tracing the packet in the upper
+/// bits, so that the ids are unique across threads and a packet handed off to another
+/// thread keeps its id there
+pub const TRACE_THREAD_SHIFT: usize = 48;
+
+/// One record is added for each node a traced packet visits
+pub struct TraceRecord {
+    /// The trace id of the packet, every traced packet gets an id unique across threads
+    pub id: usize,
+    /// Name of the graph node the packet visited
+    pub node: String,
+    /// Time in nanoseconds when the node got the packet
+    pub time: u64,
+    /// Whatever the node has to say about what it did with the packet
+    pub summary: String,
+}
+
+/// The packet trace is enabled for a given number of packets coming in on an interface,
+/// the interface node marks the packets for tracing as they are received and from there
+/// on every node that the packet visits adds a record. Like the Logger, there is one
+/// Trace per forwarding thread and it is shared with the control plane thread which
+/// enables/clears/displays the trace. The fields are atomic so that the forwarding thread
+/// can check if tracing is on without any locks, the records are protected by a lock, but
+/// that lock is taken only for the packets being traced - and tracing is for debugging
+/// and is expected to be turned on only for a handful of packets.
+pub struct Trace {
+    /// The ifindex on which packets have to be traced
+    ifindex: AtomicUsize,
+    /// Number of packets that are still to be traced
+    count: AtomicUsize,
+    /// The trace id to give the next traced packet, zero means not traced
+    next_id: AtomicUsize,
+    /// The trace records
+    records: Mutex<Vec<TraceRecord>>,
+}
+
+impl Default for Trace {
+    fn default() -> Self {
+        Trace::new(0)
+    }
+}
+
+impl Trace {
+    /// The trace of forwarding thread 'thread'
+    pub fn new(thread: usize) -> Self {
+        Trace {
+            ifindex: AtomicUsize::new(0),
+            count: AtomicUsize::new(0),
+            next_id: AtomicUsize::new((thread << TRACE_THREAD_SHIFT) | 1),
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Trace the next 'count' packets coming in on ifindex
+    pub fn enable(&self, ifindex: usize, count: usize) {
+        self.ifindex.store(ifindex, Ordering::Relaxed);
+        self.count.store(count, Ordering::Relaxed);
+    }
+
+    /// Stop tracing and clear all the trace records
+    pub fn clear(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.records.lock().unwrap().clear();
+    }
+
+    /// Returns a trace id for the packet if the packet has to be traced, zero otherwise
+    pub fn start(&self, ifindex: usize) -> usize {
+        let count = self.count.load(Ordering::Relaxed);
+        if count == 0 || ifindex != self.ifindex.load(Ordering::Relaxed) {
+            return 0;
+        }
+        self.count.store(count - 1, Ordering::Relaxed);
+        let id = self.next_id.load(Ordering::Relaxed);
+        self.next_id.store(id + 1, Ordering::Relaxed);
+        id
+    }
+
+    /// Add a record saying that the packet with trace id 'id' visited node 'node'
+    pub fn record(&self, id: usize, node: &str) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() < TRACE_MAX_RECORDS {
+            records.push(TraceRecord {
+                id,
+                node: node.to_string(),
+                time: time_nsecs(),
+                summary: String::new(),
+            });
+        }
+    }
+
+    /// Add the summary to the latest record of packet 'id' at node 'node'
+    pub fn summary(&self, id: usize, node: &str, summary: String) {
+        let mut records = self.records.lock().unwrap();
+        for r in records.iter_mut().rev() {
+            if r.id == id && r.node == node {
+                if !r.summary.is_empty() {
+                    r.summary.push_str(", ");
+                }
+                r.summary.push_str(&summary);
+                return;
+            }
+        }
+    }
+
+    /// Call f() on each of the trace records, in the order they were recorded
+    pub fn walk<F: FnMut(&TraceRecord)>(&self, mut f: F) {
+        let records = self.records.lock().unwrap();
+        for r in records.iter() {
+            f(r);
+        }
+    }
+}
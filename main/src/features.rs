@@ -0,0 +1,85 @@
+use super::*;
+use graph::feature::FeatureArc;
+use msg::FeatureEnableMsg;
+
+// The feature arcs in the system. The control thread keeps the master copy of each arc
+// which knows about all the features registered and the features enabled per interface,
+// and the nodes on the arc have their own copies which are kept in sync via messages
+pub struct FeatureCtx {
+    arcs: HashMap<String, FeatureArc>,
+}
+
+impl FeatureCtx {
+    pub fn new() -> FeatureCtx {
+        let mut arcs = HashMap::new();
+        let mut input = FeatureArc::new(
+            names::IP4_INPUT,
+            names::L3_IPV4_PARSE,
+            names::L3_IPV4_FWD,
+            false,
+        );
+        // Policy based routing is the one feature R2 itself has on the arcs
+        input.register(names::L3_IPV4_PBR);
+        arcs.insert(input.name().to_string(), input);
+        let output = FeatureArc::new(names::IP4_OUTPUT, names::L3_IPV4_FWD, names::ENCAPMUX, true);
+        arcs.insert(output.name().to_string(), output);
+        FeatureCtx { arcs }
+    }
+
+    // Feature nodes register themselves on an arc before the nodes on the arc are created,
+    // and then add themselves to the graph with their next names including the arc's
+    pub fn register(&mut self, arc: &str, feature: &str) -> bool {
+        if let Some(a) = self.arcs.get_mut(arc) {
+            a.register(feature)
+        } else {
+            false
+        }
+    }
+
+    pub fn arcs(&self) -> &HashMap<String, FeatureArc> {
+        &self.arcs
+    }
+
+    pub fn arc(&self, arc: &str) -> &FeatureArc {
+        &self.arcs[arc]
+    }
+}
+
+pub fn feature_enable(
+    r2: &mut R2,
+    arc: &str,
+    feature: &str,
+    ifindex: usize,
+    enable: bool,
+) -> Result<(), String> {
+    let a = match r2.features.arcs.get_mut(arc) {
+        Some(a) => a,
+        None => return Err(format!("Unknown feature arc {}", arc)),
+    };
+    if !a.enable(ifindex, feature, enable) {
+        return Err(format!("Feature {} not on arc {}", feature, arc));
+    }
+    let msg = FeatureEnableMsg {
+        arc: arc.to_string(),
+        feature: feature.to_string(),
+        ifindex,
+        enable,
+        nodes: a.nodes(),
+    };
+    r2.broadcast(R2Msg::FeatureEnable(msg));
+    Ok(())
+}
+
+// An interface is going away, disable all its features so that an interface that reuses
+// the ifindex later does not end up with them
+pub fn features_del_ifindex(r2: &mut R2, ifindex: usize) {
+    let mut enabled = Vec::new();
+    for (name, arc) in r2.features.arcs.iter() {
+        for feature in arc.features(ifindex) {
+            enabled.push((name.clone(), feature));
+        }
+    }
+    for (arc, feature) in enabled {
+        let _ = feature_enable(r2, &arc, &feature, ifindex, false);
+    }
+}
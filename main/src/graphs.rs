@@ -0,0 +1,92 @@
+use super::*;
+use apis_graph::{GnodeApi, GraphApi, GraphErr, GraphSyncHandler};
+use graph::GnodeInfo;
+use msg::GraphInfoMsg;
+
+pub struct GraphApis {
+    r2: Arc<Mutex<R2>>,
+}
+
+impl GraphApis {
+    pub fn new(r2: Arc<Mutex<R2>>) -> GraphApis {
+        GraphApis { r2 }
+    }
+}
+
+// The graph is owned by each forwarding thread, so we ask the threads to send us a snapshot
+// of the graph. If thread is None, the snapshot is collected from all threads
+fn graph_info(r2: &mut R2, thread: Option<usize>) -> Vec<(usize, Vec<GnodeInfo>)> {
+    let (sender, receiver) = channel();
+    let msg = R2Msg::GraphInfo(GraphInfoMsg { reply: sender });
+    r2.request(msg, receiver, thread)
+}
+
+fn gnode_api(n: &GnodeInfo) -> GnodeApi {
+    GnodeApi {
+        name: Some(n.name.clone()),
+        index: Some(n.index as i32),
+        next_names: Some(n.next_names.clone()),
+        next_nodes: Some(n.next_nodes.iter().map(|i| *i as i32).collect()),
+        depth: Some(n.depth as i64),
+        enqed: Some(n.enqed as i64),
+        drops: Some(n.drops as i64),
+        perf_avg: Some(n.perf_avg as i64),
+        perf_pkt: Some(n.perf_pkt as i64),
+    }
+}
+
+// Render the graph in Graphviz DOT format. Every node has the drop node as a next node,
+// and nodes can have next names that might not exist (yet), all of those resolve to the
+// drop node - so we leave out the edges to the drop node to keep the picture sane
+fn graph_dot(thread: usize, nodes: &[GnodeInfo]) -> String {
+    let mut s = format!("digraph \"r2-{}\" {{\n", thread);
+    for n in nodes.iter() {
+        s.push_str(&format!(
+            "    \"{}\" [shape=box, label=\"{}\\nenq {} drop {} depth {}\\ncycles {} per pkt {}\"];\n",
+            n.name, n.name, n.enqed, n.drops, n.depth, n.perf_avg, n.perf_pkt
+        ));
+    }
+    for n in nodes.iter() {
+        for next in n.next_nodes.iter() {
+            if *next != 0 {
+                s.push_str(&format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    n.name, nodes[*next].name
+                ));
+            }
+        }
+    }
+    s.push_str("}\n");
+    s
+}
+
+impl GraphSyncHandler for GraphApis {
+    fn handle_show(&self) -> thrift::Result<Vec<GraphApi>> {
+        let mut r2 = self.r2.lock().unwrap();
+        let graphs = graph_info(&mut r2, None);
+        let mut ret = Vec::new();
+        for (thread, nodes) in graphs.iter() {
+            ret.push(GraphApi {
+                thread: Some(*thread as i32),
+                nodes: Some(nodes.iter().map(gnode_api).collect()),
+            });
+        }
+        Ok(ret)
+    }
+
+    fn handle_dot(&self, thread: i32) -> thrift::Result<String> {
+        let mut r2 = self.r2.lock().unwrap();
+        if thread < 0 || thread as usize >= r2.threads.len() {
+            return Err(From::from(GraphErr::new(format!("Bad thread {}", thread))));
+        }
+        let graphs = graph_info(&mut r2, Some(thread as usize));
+        if let Some((thread, nodes)) = graphs.first() {
+            Ok(graph_dot(*thread, nodes))
+        } else {
+            Err(From::from(GraphErr::new(format!(
+                "No reply from thread {}",
+                thread
+            ))))
+        }
+    }
+}
@@ -1,20 +1,24 @@
 use super::*;
-use crate::ipv4::add_route;
-use crate::ipv4::del_route;
+use crate::features::{feature_enable, features_del_ifindex};
+use crate::ipv4::del_routes_ifindex;
+use crate::ipv4::{update_routes, RouteOp};
+use crate::ipv6::{del_routes6_ifindex, update_routes6, Route6Op};
+use crate::pbr::del_rules_ifindex;
 use apis_interface::{CurvesApi, InterfaceErr, InterfaceSyncHandler};
 use dpdk::DpdkHw;
 use fwd::intf::Interface;
 use fwd::intf::ModifyInterfaceMsg;
-use fwd::ZERO_IP;
+use fwd::{IP_MIN_MTU, ZERO_IP, ZERO_IP6};
 use interface::IfNode;
 use l2_eth_decap::EthDecap;
 use l2_eth_encap::EthEncap;
-use msg::EpollAddMsg;
-use msg::{ClassAddMsg, GnodeAddMsg};
+use msg::{ClassAddMsg, EdgeAddMsg, EdgeDelMsg, GnodeAddMsg, GnodeDelMsg};
 use msg::{Curves, Sc};
+use msg::{EpollAddMsg, EpollDelMsg};
+use msg::{NodeQuery, NodeQueryMsg, NodeReply};
+use names::{l2_eth_decap, l2_eth_encap};
 use perf::Perf;
 use socket::RawSock;
-use std::net::Ipv4Addr;
 
 pub struct InterfaceApis {
     r2: Arc<Mutex<R2>>,
@@ -34,6 +38,8 @@ pub struct IfdCtx {
     name2idx: HashMap<String, usize>,
     idx2name: HashMap<usize, String>,
     interfaces: HashMap<String, Arc<Interface>>,
+    // The thread doing I/O for an interface and the descriptor it polls on, indexed by ifindex
+    io: HashMap<usize, (usize, Option<i32>)>,
 }
 
 impl IfdCtx {
@@ -43,13 +49,30 @@ impl IfdCtx {
             name2idx: HashMap::new(),
             idx2name: HashMap::new(),
             interfaces: HashMap::new(),
+            io: HashMap::new(),
         }
     }
 
-    fn add(&mut self, ifname: &str, ifindex: usize, interface: Arc<Interface>) {
+    pub fn add(
+        &mut self,
+        ifname: &str,
+        ifindex: usize,
+        interface: Arc<Interface>,
+        thread: usize,
+        fd: Option<i32>,
+    ) {
         self.interfaces.insert(ifname.to_string(), interface);
         self.name2idx.insert(ifname.to_string(), ifindex);
         self.idx2name.insert(ifindex, ifname.to_string());
+        self.io.insert(ifindex, (thread, fd));
+    }
+
+    fn del(&mut self, ifname: &str) {
+        self.interfaces.remove(ifname);
+        if let Some(ifindex) = self.name2idx.remove(ifname) {
+            self.idx2name.remove(&ifindex);
+            self.io.remove(&ifindex);
+        }
     }
 
     pub fn get(&self, ifname: &str) -> Option<&Arc<Interface>> {
@@ -116,6 +139,7 @@ fn create_eth_nodes(r2: &mut R2, intf: Arc<Interface>) {
         next_names: decap.next_names(),
         cntrs: GnodeCntrs::new(&decap.name(), &mut r2.counters),
         perf: Perf::new(&decap.name(), &mut r2.counters),
+        depth: VEC_SIZE,
     };
     let msg = GnodeAddMsg {
         node: Box::new(decap),
@@ -124,12 +148,15 @@ fn create_eth_nodes(r2: &mut R2, intf: Arc<Interface>) {
     let msg = R2Msg::GnodeAdd(msg);
     r2.broadcast(msg);
 
+    let ifindex = intf.ifindex;
     let encap = EthEncap::new(intf, &mut r2.counters);
+    let name = encap.name();
     let init = GnodeInit {
         name: encap.name(),
         next_names: encap.next_names(),
         cntrs: GnodeCntrs::new(&encap.name(), &mut r2.counters),
         perf: Perf::new(&encap.name(), &mut r2.counters),
+        depth: VEC_SIZE,
     };
     let msg = GnodeAddMsg {
         node: Box::new(encap),
@@ -137,6 +164,15 @@ fn create_eth_nodes(r2: &mut R2, intf: Arc<Interface>) {
     };
     let msg = R2Msg::GnodeAdd(msg);
     r2.broadcast(msg);
+
+    // EncapMux gets an edge to the encap node of every interface as and when it is added
+    let msg = EdgeAddMsg {
+        node: names::ENCAPMUX.to_string(),
+        next: name,
+        key: ifindex,
+        edge: 0,
+    };
+    r2.broadcast(R2Msg::EdgeAdd(msg));
 }
 
 pub fn create_interface_node(
@@ -189,10 +225,8 @@ pub fn create_interface_node(
     // list of descriptors we are polling on. Every forwarding thread is polling on its own
     // set of descriptors, every thread will receive this message, but only the ones marked
     // in 'thread' will add the fd to its epoll
-    let msg = EpollAddMsg {
-        fd: intf.fd(),
-        thread,
-    };
+    let fd = intf.fd();
+    let msg = EpollAddMsg { fd, thread };
     let msg = R2Msg::EpollAdd(msg);
     r2.broadcast(msg);
 
@@ -204,6 +238,7 @@ pub fn create_interface_node(
         next_names: intf.next_names(),
         cntrs: GnodeCntrs::new(&intf.name(), &mut r2.counters),
         perf: Perf::new(&intf.name(), &mut r2.counters),
+        depth: VEC_SIZE,
     };
     let msg = GnodeAddMsg {
         node: Box::new(intf),
@@ -223,12 +258,93 @@ pub fn create_interface_node(
     // And finally send the message to the thread that wants to do device I/O
     r2.unicast(msg, io.unwrap());
 
-    r2.ifd.add(ifname, ifindex, interface.clone());
+    r2.ifd.add(ifname, ifindex, interface.clone(), thread, fd);
     create_eth_nodes(r2, interface);
 
     Ok(())
 }
 
+// Undo everything create_interface_node() did. The routes pointing to the interface are
+// removed first so that no new packets are forwarded to the interface, then the owner
+// thread stops polling the interface, EncapMux forgets its edge to the interface, and then
+// the interface's nodes are removed from the graph in all threads. The nodes are handed back
+// to the control thread once they are off the graph, and the driver (the socket descriptor
+// or the dpdk port) is closed when the control thread drops it
+pub fn delete_interface_node(r2: &mut R2, ifname: &str, ifindex: usize) {
+    del_routes_ifindex(r2, ifindex);
+    del_routes6_ifindex(r2, ifindex);
+    del_rules_ifindex(r2, ifindex);
+    features_del_ifindex(r2, ifindex);
+
+    if let Some((thread, Some(fd))) = r2.ifd.io.get(&ifindex) {
+        let msg = EpollDelMsg {
+            fd: *fd,
+            thread: *thread,
+        };
+        r2.broadcast(R2Msg::EpollDel(msg));
+    }
+
+    let msg = EdgeDelMsg {
+        node: names::ENCAPMUX.to_string(),
+        key: ifindex,
+    };
+    r2.broadcast(R2Msg::EdgeDel(msg));
+
+    let names = [l2_eth_decap(ifindex), l2_eth_encap(ifindex), rx_tx(ifindex)];
+    for name in names.iter() {
+        let msg = GnodeDelMsg { name: name.clone() };
+        r2.broadcast(R2Msg::GnodeDel(msg));
+    }
+
+    r2.ifd.del(ifname);
+}
+
+// The state of an interface's nodes like the MAC table or the QoS classes lives in the
+// forwarding threads, so ask the thread that does I/O for the interface about it
+fn node_query(
+    r2: &mut R2,
+    ifindex: usize,
+    node: String,
+    query: NodeQuery,
+) -> Result<NodeReply, String> {
+    let thread = match r2.ifd.io.get(&ifindex) {
+        Some((thread, _)) => *thread,
+        None => return Err(format!("Cannot find ifindex {}", ifindex)),
+    };
+    let (sender, receiver) = channel();
+    let msg = R2Msg::NodeQuery(NodeQueryMsg {
+        node: node.clone(),
+        query,
+        reply: sender,
+    });
+    match r2.request(msg, receiver, Some(thread)).pop() {
+        Some((_, NodeReply::Unsupported)) => {
+            Err(format!("Node {} cannot answer {:?}", node, query))
+        }
+        Some((_, reply)) => Ok(reply),
+        None => Err(format!("No reply from thread {}", thread)),
+    }
+}
+
+// We broadcast a message to forwarding threads with a copy of the new interface parameters,
+// and the forwarding threads are expected to swap out the old interface structure with the
+// new one. The control thread keeps the new copy too, so that the next change starts from
+// these parameters
+fn modify_interface(r2: &mut R2, ifname: String, new_intf: Interface) -> Arc<Interface> {
+    let new_intf = Arc::new(new_intf);
+    r2.ifd.interfaces.insert(ifname, new_intf.clone());
+    let msg = R2Msg::ModifyInterface(ModifyInterfaceMsg {
+        intf: new_intf.clone(),
+    });
+    r2.broadcast(msg);
+    new_intf
+}
+
+fn mac_to_str(mac: &[u8]) -> String {
+    let bytes: Vec<String> = mac.iter().map(|b| format!("{:02x}", b)).collect();
+    bytes.join(":")
+}
+
 impl InterfaceSyncHandler for InterfaceApis {
     fn handle_add_if(&self, name: String, ifindex: i32, mac: String) -> thrift::Result<()> {
         let l2_addr;
@@ -253,66 +369,197 @@ impl InterfaceSyncHandler for InterfaceApis {
                 errno
             ))));
         };
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
         Ok(())
     }
 
-    fn handle_add_ip(&self, ifname: String, ip_and_mask: String) -> thrift::Result<()> {
+    fn handle_del_if(&self, name: String) -> thrift::Result<()> {
         let mut r2 = self.r2.lock().unwrap();
-        let intf;
-        let addr;
-        let masklen;
-        let ifindex;
-        if let Some(i) = r2.ifd.interfaces.get(&ifname) {
-            intf = i;
-            ifindex = intf.ifindex;
+        let ifindex = if let Some(ifindex) = r2.ifd.name2idx.get(&name) {
+            *ifindex
+        } else {
+            return Err(From::from(InterfaceErr::new(format!(
+                "Cannot find interface {}",
+                name
+            ))));
+        };
+        delete_interface_node(&mut r2, &name, ifindex);
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
+        Ok(())
+    }
+
+    fn handle_set_feature(
+        &self,
+        ifname: String,
+        arc: String,
+        feature: String,
+        enable: bool,
+    ) -> thrift::Result<()> {
+        let mut r2 = self.r2.lock().unwrap();
+        let ifindex = if let Some(ifindex) = r2.ifd.name2idx.get(&ifname) {
+            *ifindex
         } else {
             return Err(From::from(InterfaceErr::new(format!(
                 "Cannot find interface {}",
                 ifname
             ))));
+        };
+        if let Err(why) = feature_enable(&mut r2, &arc, &feature, ifindex, enable) {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
         }
-        if let Some((a, m)) = fwd::ip_mask_decode(&ip_and_mask) {
-            addr = a;
-            masklen = m;
+        Ok(())
+    }
+
+    fn handle_add_ip(&self, ifname: String, ip_and_mask: String) -> thrift::Result<()> {
+        let mut r2 = self.r2.lock().unwrap();
+        let intf = if let Some(i) = r2.ifd.interfaces.get(&ifname) {
+            i.clone()
+        } else {
+            return Err(From::from(InterfaceErr::new(format!(
+                "Cannot find interface {}",
+                ifname
+            ))));
+        };
+        let ifindex = intf.ifindex;
+        let mut new_intf = (*intf).clone();
+        if let Some((addr, masklen)) = fwd::ip_mask_decode(&ip_and_mask) {
+            if addr == ZERO_IP || masklen == 0 {
+                return Err(From::from(InterfaceErr::new(format!(
+                    "ZERO IP/MASK {}",
+                    ip_and_mask
+                ))));
+            }
+            new_intf.set_v4addr(addr, masklen);
+        } else if let Some((addr, masklen)) = fwd::ip6_mask_decode(&ip_and_mask) {
+            if addr == ZERO_IP6 || masklen == 0 {
+                return Err(From::from(InterfaceErr::new(format!(
+                    "ZERO IP/MASK {}",
+                    ip_and_mask
+                ))));
+            }
+            new_intf.set_v6addr(addr, masklen);
         } else {
             return Err(From::from(InterfaceErr::new(format!(
                 "Bad IP/MASK {}",
                 ip_and_mask
             ))));
         }
-        if addr == ZERO_IP || masklen == 0 {
+        let new_intf = modify_interface(&mut r2, ifname, new_intf);
+        drop(r2);
+        // Delete the old connected route and receive entry corresponding to the old IP,
+        // and add a new connected route and receive entry for the new IP, all in one go
+        let mut r2 = self.r2.lock().unwrap();
+        let (cur_addr, cur_masklen) = intf.get_v4addr();
+        let (addr, masklen) = new_intf.get_v4addr();
+        if (addr, masklen) != (cur_addr, cur_masklen) {
+            let mut ops = Vec::new();
+            if cur_addr != ZERO_IP {
+                ops.push(RouteOp::new(cur_addr, cur_masklen, ZERO_IP, ifindex, false));
+                ops.push(RouteOp::local(cur_addr, ifindex, false));
+            }
+            ops.push(RouteOp::new(addr, masklen, ZERO_IP, ifindex, true));
+            ops.push(RouteOp::local(addr, ifindex, true));
+            update_routes(&mut r2, new_intf.vrf, ops);
+        }
+        let (cur_addr, cur_masklen) = intf.get_v6addr();
+        let (addr, masklen) = new_intf.get_v6addr();
+        if (addr, masklen) != (cur_addr, cur_masklen) {
+            let mut ops = Vec::new();
+            if cur_addr != ZERO_IP6 {
+                ops.push(Route6Op::new(
+                    cur_addr,
+                    cur_masklen,
+                    ZERO_IP6,
+                    ifindex,
+                    false,
+                ));
+                ops.push(Route6Op::local(cur_addr, ifindex, false));
+            }
+            ops.push(Route6Op::new(addr, masklen, ZERO_IP6, ifindex, true));
+            ops.push(Route6Op::local(addr, ifindex, true));
+            update_routes6(&mut r2, ops);
+        }
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
+        Ok(())
+    }
+
+    // The IP MTU, packets bigger than this are fragmented (or dropped if they cant be) on
+    // the way out of the interface
+    fn handle_set_mtu(&self, ifname: String, mtu: i32) -> thrift::Result<()> {
+        if mtu < IP_MIN_MTU as i32 || mtu > u16::MAX as i32 {
             return Err(From::from(InterfaceErr::new(format!(
-                "ZERO IP/MASK {}",
-                ip_and_mask
+                "MTU {} not in range {}-{}",
+                mtu,
+                IP_MIN_MTU,
+                u16::MAX
             ))));
         }
-        let (cur_addr, cur_masklen) = intf.get_v4addr();
-        let mut new_intf = (**intf).clone();
-        new_intf.set_v4addr(addr, masklen);
-        // We broadcast a message to forwarding threads with a copy of the new interface
-        // parameters, and the forwarding threads are expected to swap out the old interface
-        // structure with the new one
-        let msg = R2Msg::ModifyInterface(ModifyInterfaceMsg {
-            intf: Arc::new(new_intf),
-        });
-        r2.broadcast(msg);
-        drop(r2);
-        // Delete the old connected route corresponding to the old IP,
-        // and add a new connected route for the new IP
         let mut r2 = self.r2.lock().unwrap();
-        if cur_addr != ZERO_IP {
-            del_route(
-                &mut r2,
-                cur_addr,
-                cur_masklen,
-                Ipv4Addr::new(0, 0, 0, 0),
-                ifindex,
-            );
-        }
-        add_route(&mut r2, addr, masklen, Ipv4Addr::new(0, 0, 0, 0), ifindex);
+        let intf = if let Some(i) = r2.ifd.interfaces.get(&ifname) {
+            i.clone()
+        } else {
+            return Err(From::from(InterfaceErr::new(format!(
+                "Cannot find interface {}",
+                ifname
+            ))));
+        };
+        let mut new_intf = (*intf).clone();
+        new_intf.mtu = mtu as usize;
+        modify_interface(&mut r2, ifname, new_intf);
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
         Ok(())
     }
 
+    // Move the interface to a VRF, the VRF is created if it does not exist yet. The connected
+    // route and receive entry of the interface move along with it, other routes through the
+    // interface stay in the VRF they were added to
+    fn handle_set_vrf(&self, ifname: String, vrf: String) -> thrift::Result<()> {
+        let mut r2 = self.r2.lock().unwrap();
+        let intf = if let Some(i) = r2.ifd.interfaces.get(&ifname) {
+            i.clone()
+        } else {
+            return Err(From::from(InterfaceErr::new(format!(
+                "Cannot find interface {}",
+                ifname
+            ))));
+        };
+        let vrf = r2.ipv4.vrf_add(&vrf);
+        if vrf == intf.vrf {
+            return Ok(());
+        }
+        let ifindex = intf.ifindex;
+        let mut new_intf = (*intf).clone();
+        new_intf.vrf = vrf;
+        modify_interface(&mut r2, ifname, new_intf);
+        let (addr, masklen) = intf.get_v4addr();
+        if addr != ZERO_IP {
+            let ops = vec![
+                RouteOp::new(addr, masklen, ZERO_IP, ifindex, false),
+                RouteOp::local(addr, ifindex, false),
+            ];
+            update_routes(&mut r2, intf.vrf, ops);
+            let ops = vec![
+                RouteOp::new(addr, masklen, ZERO_IP, ifindex, true),
+                RouteOp::local(addr, ifindex, true),
+            ];
+            update_routes(&mut r2, vrf, ops);
+        }
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
+        Ok(())
+    }
     fn handle_add_class(
         &self,
         ifname: String,
@@ -348,6 +595,60 @@ impl InterfaceSyncHandler for InterfaceApis {
             curves,
         };
         r2.broadcast(R2Msg::ClassAdd(class));
+        if let Err(why) = r2.sync() {
+            return Err(From::from(InterfaceErr::new(why)));
+        }
         Ok(())
     }
+
+    fn handle_show_macs(&self, ifname: String) -> thrift::Result<String> {
+        let mut r2 = self.r2.lock().unwrap();
+        let ifindex = match r2.ifd.name2idx.get(&ifname) {
+            Some(ifindex) => *ifindex,
+            None => {
+                return Err(From::from(InterfaceErr::new(format!(
+                    "Cannot find interface {}",
+                    ifname
+                ))))
+            }
+        };
+        match node_query(&mut r2, ifindex, l2_eth_encap(ifindex), NodeQuery::EthMacs) {
+            Ok(NodeReply::EthMacs(macs)) => {
+                let mut s = "IP\t\tMAC\t\t\tSTATE\n".to_string();
+                for (ip, mac, state) in macs.iter() {
+                    s.push_str(&format!("{}\t\t{}\t{}\n", ip, mac_to_str(mac), state));
+                }
+                Ok(s)
+            }
+            Ok(_) => Err(From::from(InterfaceErr::new("Bad reply".to_string()))),
+            Err(why) => Err(From::from(InterfaceErr::new(why))),
+        }
+    }
+
+    fn handle_show_classes(&self, ifname: String) -> thrift::Result<String> {
+        let mut r2 = self.r2.lock().unwrap();
+        let ifindex = match r2.ifd.name2idx.get(&ifname) {
+            Some(ifindex) => *ifindex,
+            None => {
+                return Err(From::from(InterfaceErr::new(format!(
+                    "Cannot find interface {}",
+                    ifname
+                ))))
+            }
+        };
+        match node_query(&mut r2, ifindex, rx_tx(ifindex), NodeQuery::Classes) {
+            Ok(NodeReply::Classes(classes)) => {
+                let mut s = "Class\tParent\tLeaf\tQlimit\tQlen\tQdrops\tBytes\n".to_string();
+                for c in classes.iter() {
+                    s.push_str(&format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                        c.name, c.parent, c.is_leaf, c.qlimit, c.qlen, c.qdrops, c.bytes
+                    ));
+                }
+                Ok(s)
+            }
+            Ok(_) => Err(From::from(InterfaceErr::new("Bad reply".to_string()))),
+            Err(why) => Err(From::from(InterfaceErr::new(why))),
+        }
+    }
 }
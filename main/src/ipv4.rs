@@ -1,14 +1,18 @@
 use super::*;
-use apis_route::{RouteErr, RouteSyncHandler};
-use fwd::ip_mask_decode;
+use crate::ipv6::{update_routes6, Route6Op};
+use apis_route::{RouteEntry, RouteErr, RouteSyncHandler};
 use fwd::ipv4::IPv4Table;
 use fwd::{adj::Adjacency, ipv4::IPv4Leaf, ipv4::IPv4TableMsg, Fwd};
+use fwd::{ip6_mask_decode, ip_mask_decode};
+use fwd::{DEFAULT_VRF, ZERO_IP, ZERO_IP6};
 use l3_ipv4_fwd::IPv4Fwd;
+use l3_ipv4_local::IPv4Local;
 use l3_ipv4_parse::IPv4Parse;
+use l3_ipv4_reass::IPv4Reass;
 use perf::Perf;
 use std::fs::File;
 use std::io::prelude::*;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
 pub struct RouteApis {
@@ -20,7 +24,7 @@ impl RouteApis {
         RouteApis { r2 }
     }
 
-    fn handle_show_all(&self, filename: String) -> thrift::Result<String> {
+    fn handle_show_all(&self, filename: String, vrf: usize) -> thrift::Result<String> {
         let mut file = match File::create(&filename) {
             Err(why) => {
                 return Err(From::from(RouteErr::new(format!(
@@ -31,101 +35,349 @@ impl RouteApis {
             }
             Ok(file) => file,
         };
-        file_write(&mut file, "{\n\"table1\":[\n");
+        file_write(&mut file, "{\n\"routes\":[\n");
         let r2 = self.r2.lock().unwrap();
-        let iter = r2.ipv4.table1.root.iter();
+        let v4 = r2.ipv4.active(vrf).iter();
+        let v4 = v4.map(|(prefix, masklen, leaf)| (IpAddr::V4(prefix), masklen, &leaf.next));
+        // The ipv6 routes are all in the default VRF
+        let v6 = r2.ipv6.active.iter().filter(|_| vrf == DEFAULT_VRF);
+        let v6 = v6.map(|(prefix, masklen, leaf)| (IpAddr::V6(prefix), masklen, &leaf.next));
         let mut first = true;
-        for (prefix, masklen, leaf) in iter {
-            if !first {
-                file_write(&mut file, ",\n");
+        for (prefix, masklen, next) in v4.chain(v6) {
+            match next {
+                Fwd::Blackhole | Fwd::Reject => {
+                    if !first {
+                        file_write(&mut file, ",\n");
+                    }
+                    first = false;
+                    let kind = Special::of(next).map_or("", |s| s.name());
+                    nointf_json_dump(&mut file, prefix, masklen, "", kind);
+                    continue;
+                }
+                _ => {}
+            }
+            // A route with more than one path is dumped as one route per path
+            for path in route_paths(prefix, next) {
+                if !first {
+                    file_write(&mut file, ",\n");
+                }
+                first = false;
+                route_json_dump(&mut file, &r2, prefix, masklen, path);
             }
-            first = false;
-            route_json_dump(&mut file, &r2, prefix, masklen, leaf);
         }
-        file_write(&mut file, "\n],\n");
-
-        file_write(&mut file, "\"table2\":[\n");
-        let iter = r2.ipv4.table2.root.iter();
-        let mut first = true;
-        for (prefix, masklen, leaf) in iter {
-            if !first {
-                file_write(&mut file, ",\n");
+        // The recursive routes are dumped as they were added, with their nexthops and no
+        // interface, not with the paths they resolved to
+        for (prefix, masklen, vias) in r2.ipv4.active(vrf).recursive() {
+            for via in vias.iter() {
+                if !first {
+                    file_write(&mut file, ",\n");
+                }
+                first = false;
+                let (prefix, via) = (IpAddr::V4(prefix), via.to_string());
+                nointf_json_dump(&mut file, prefix, masklen, &via, "");
             }
-            first = false;
-            route_json_dump(&mut file, &r2, prefix, masklen, leaf);
         }
         file_write(&mut file, "\n]\n}\n");
 
         Ok("".to_string())
     }
 
-    fn handle_show_one(&self, r2: &R2, table: &IPv4Table, addr: Ipv4Addr) -> String {
-        if let Some((prefix, mask, leaf)) = table.root.longest_match(addr) {
-            if let ipv4::Fwd::Adjacency(adj) = &leaf.next {
-                let ifname = if let Some(name) = r2.ifd.get_name(adj.ifindex) {
+    fn handle_show_one(&self, r2: &R2, addr: IpAddr, vrf: usize) -> String {
+        let found = match addr {
+            IpAddr::V4(addr) => r2
+                .ipv4
+                .active(vrf)
+                .longest_match(addr)
+                .map(|(prefix, mask, leaf)| (IpAddr::V4(prefix), mask, &leaf.next)),
+            IpAddr::V6(_) if vrf != DEFAULT_VRF => None,
+            IpAddr::V6(addr) => r2
+                .ipv6
+                .active
+                .longest_match(addr)
+                .map(|(prefix, mask, leaf)| (IpAddr::V6(prefix), mask, &leaf.next)),
+        };
+        if let Some((prefix, mask, next)) = found {
+            // A recursive route shows the paths its nexthops resolved to
+            let (next, recursive) = match next {
+                Fwd::IPv4Leaf(resolved) => (&resolved.next, true),
+                next => (next, false),
+            };
+            let mut s = "Destination\t\tNextHop\t\tInterface\n".to_string();
+            if let Fwd::Blackhole | Fwd::Reject = next {
+                let kind = Special::of(next).map_or("", |s| s.name());
+                s.push_str(&format!("{}/{}\t\t{}\n", prefix, mask, kind));
+                return s;
+            }
+            let paths = route_paths(prefix, next);
+            if paths.is_empty() {
+                return "".to_string();
+            }
+            for (nhop, ifindex, local) in paths {
+                let nhop = if local {
+                    "local".to_string()
+                } else {
+                    nhop.to_string()
+                };
+                let ifname = if let Some(name) = r2.ifd.get_name(ifindex) {
                     name
                 } else {
                     "Unknown_ifindex"
                 };
-                let mut s = "Destination\t\tNextHop\t\tInterface\n".to_string();
                 s.push_str(&format!(
                     "{}/{}\t\t{}\t\t{}[{}]\n",
-                    prefix, mask, adj.nhop, ifname, adj.ifindex
+                    prefix, mask, nhop, ifname, ifindex
                 ));
-                s
-            } else {
-                "".to_string()
             }
+            if let (true, IpAddr::V4(v4)) = (recursive, prefix) {
+                let mut vias = r2.ipv4.active(vrf).recursive();
+                if let Some((_, _, vias)) = vias.find(|(p, m, _)| *p == v4 && *m == mask) {
+                    let vias: Vec<String> = vias.iter().map(|v| v.to_string()).collect();
+                    s.push_str(&format!("Recursive via {}\n", vias.join(", ")));
+                }
+            }
+            s
         } else {
             "".to_string()
         }
     }
 }
 
-// We keep two route tables - ie two trie bitmaps - mirror image of each other. One is
-// actively used by forwarding threads, the other is what gets updated when there is
-// routing changes - and then we message forwarding threads to use the updated one, and
-// we then update the old one also with the same route changes, thus keeping both the
-// copies always in sync
-pub enum V4Table {
-    Table1,
-    Table2,
+// Route tables are updated RCU style. The forwarding threads use the 'active' table, route
+// changes are made to a 'standby' table which is then published to the forwarding threads
+// to use instead of the active one. The table that was active is then 'retired' - the
+// threads drop their references to it as they pick up the new table. If the last reference
+// to the retired table is gone by the time of the next route change, the retired table is
+// brought up to date with the changes it missed (the backlog) and becomes the next standby.
+// If not, the next standby is a fresh copy of the active table, and the retired table is
+// freed whenever the last thread lets go of it. Either way the control thread never waits
+// for the forwarding threads
+struct IPv4Vrf {
+    name: String,
+    active: Arc<IPv4Table>,
+    retired: Option<Arc<IPv4Table>>,
+    // The changes made to the active table after the retired table was retired
+    backlog: Vec<RouteOp>,
+}
+
+impl IPv4Vrf {
+    fn new(name: &str) -> IPv4Vrf {
+        IPv4Vrf {
+            name: name.to_string(),
+            active: Arc::new(IPv4Table::new()),
+            retired: None,
+            backlog: Vec::new(),
+        }
+    }
 }
 
+// The name of the VRF the interfaces are in unless they are put in another one. An empty
+// VRF name in the APIs means the default VRF too
+pub const DEFAULT_VRF_NAME: &str = "default";
+
+// The ipv4 route tables, one per VRF. Each VRF has a route table of its own that is updated
+// independently of the others, see IPv4Vrf. The VRFs are numbered in the order they are
+// created, the interfaces and the forwarding threads know a VRF by its number (the VRF id)
+// and the APIs by its name. VRFs are created the first time their name is used to add a
+// route or to put an interface in the VRF, and stay around after that
 pub struct IPv4Ctx {
-    table1: Arc<IPv4Table>,
-    table2: Arc<IPv4Table>,
-    which: V4Table,
+    vrfs: Vec<IPv4Vrf>,
 }
 
 impl IPv4Ctx {
     pub fn new() -> IPv4Ctx {
         IPv4Ctx {
-            table1: Arc::new(IPv4Table::new()),
-            table2: Arc::new(IPv4Table::new()),
-            which: V4Table::Table1,
+            vrfs: vec![IPv4Vrf::new(DEFAULT_VRF_NAME)],
+        }
+    }
+
+    pub fn vrf_id(&self, name: &str) -> Option<usize> {
+        if name.is_empty() {
+            return Some(DEFAULT_VRF);
+        }
+        self.vrfs.iter().position(|v| v.name == name)
+    }
+
+    // The id of the VRF, which is created if it does not exist
+    pub fn vrf_add(&mut self, name: &str) -> usize {
+        if let Some(vrf) = self.vrf_id(name) {
+            return vrf;
+        }
+        self.vrfs.push(IPv4Vrf::new(name));
+        self.vrfs.len() - 1
+    }
+
+    pub fn vrf_name(&self, vrf: usize) -> &str {
+        &self.vrfs[vrf].name
+    }
+
+    pub fn active(&self, vrf: usize) -> &Arc<IPv4Table> {
+        &self.vrfs[vrf].active
+    }
+}
+
+// The routes that do not forward packets to a nexthop, in either address family
+#[derive(Copy, Clone, PartialEq)]
+pub enum Special {
+    Blackhole,
+    Reject,
+    // Packets for R2 itself, on the interface with this ifindex
+    Local(usize),
+}
+
+impl Special {
+    // The kind of a route in the APIs, see RouteEntry
+    fn decode(kind: &str, ifindex: Option<usize>) -> Result<Special, String> {
+        match (kind, ifindex) {
+            ("blackhole", _) => Ok(Special::Blackhole),
+            ("reject", _) => Ok(Special::Reject),
+            ("local", Some(ifindex)) => Ok(Special::Local(ifindex)),
+            ("local", None) => Err("Need an interface for local routes".to_string()),
+            _ => Err(format!("Unknown route kind {}", kind)),
+        }
+    }
+
+    fn of(next: &Fwd) -> Option<Special> {
+        match next {
+            Fwd::Blackhole => Some(Special::Blackhole),
+            Fwd::Reject => Some(Special::Reject),
+            Fwd::Local(ifindex) => Some(Special::Local(*ifindex)),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Special::Blackhole => "blackhole",
+            Special::Reject => "reject",
+            Special::Local(_) => "local",
+        }
+    }
+
+    pub fn fwd(self) -> Fwd {
+        match self {
+            Special::Blackhole => Fwd::Blackhole,
+            Special::Reject => Fwd::Reject,
+            Special::Local(ifindex) => Fwd::Local(ifindex),
+        }
+    }
+}
+
+// What a RouteOp adds or deletes
+enum RouteChange {
+    Path(Arc<Adjacency>),
+    Via(Ipv4Addr),
+    Special(Special),
+}
+
+// Add or delete one path of a route (see IPv4Table::add_path), one recursive nexthop of a
+// route (see IPv4Table::add_via), or a route that does not go to a nexthop. Adding a path
+// to a special route or the other way around replaces the route
+pub struct RouteOp {
+    ip: Ipv4Addr,
+    masklen: u32,
+    change: RouteChange,
+    add: bool,
+}
+
+impl RouteOp {
+    pub fn new(ip: Ipv4Addr, masklen: u32, nhop: Ipv4Addr, ifindex: usize, add: bool) -> RouteOp {
+        let change = RouteChange::Path(Arc::new(Adjacency::new(nhop, ifindex)));
+        RouteOp {
+            ip,
+            masklen,
+            change,
+            add,
+        }
+    }
+
+    // A route through a nexthop that is not on a connected subnet
+    pub fn via(ip: Ipv4Addr, masklen: u32, via: Ipv4Addr, add: bool) -> RouteOp {
+        RouteOp {
+            ip,
+            masklen,
+            change: RouteChange::Via(via),
+            add,
+        }
+    }
+
+    pub fn special(ip: Ipv4Addr, masklen: u32, special: Special, add: bool) -> RouteOp {
+        RouteOp {
+            ip,
+            masklen,
+            change: RouteChange::Special(special),
+            add,
+        }
+    }
+
+    // The /32 receive entry for an address owned by the interface
+    pub fn local(ip: Ipv4Addr, ifindex: usize, add: bool) -> RouteOp {
+        RouteOp::special(ip, 32, Special::Local(ifindex), add)
+    }
+
+    fn apply(&self, table: &mut IPv4Table) -> bool {
+        let (ip, masklen) = (self.ip, self.masklen);
+        match (&self.change, self.add) {
+            (RouteChange::Path(adj), true) => table.add_path(ip, masklen, adj.clone()),
+            (RouteChange::Path(adj), false) => table.del_path(ip, masklen, adj.nhop, adj.ifindex),
+            (RouteChange::Via(via), true) => table.add_via(ip, masklen, *via),
+            (RouteChange::Via(via), false) => table.del_via(ip, masklen, *via),
+            (RouteChange::Special(special), true) => {
+                let leaf = IPv4Leaf::new(special.fwd());
+                table.add(ip, masklen, Arc::new(leaf))
+            }
+            // Only a route of the same kind goes, not the paths of a route at the prefix
+            (RouteChange::Special(special), false) => {
+                let leaf = table.exact_match(ip, masklen);
+                let found = leaf.and_then(|leaf| Special::of(&leaf.next));
+                found == Some(*special) && table.del(ip, masklen)
+            }
         }
     }
 }
 
 pub fn create_ipv4_nodes(r2: &mut R2, g: &mut Graph<R2Msg>) {
-    let ipv4_parse_node = IPv4Parse::new(&mut r2.counters);
+    let input = r2.features.arc(names::IP4_INPUT).clone();
+    let ipv4_parse_node = IPv4Parse::new(input, &mut r2.counters);
     let init = GnodeInit {
         name: ipv4_parse_node.name(),
         next_names: ipv4_parse_node.next_names(),
         cntrs: GnodeCntrs::new(&ipv4_parse_node.name(), &mut r2.counters),
         perf: Perf::new(&ipv4_parse_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
     };
     g.add(Box::new(ipv4_parse_node), init);
 
-    let ipv4_fwd_node = IPv4Fwd::new(r2.ipv4.table1.clone(), &mut r2.counters);
+    let output = r2.features.arc(names::IP4_OUTPUT).clone();
+    let table = r2.ipv4.active(DEFAULT_VRF).clone();
+    let ipv4_fwd_node = IPv4Fwd::new(table, output, &mut r2.counters);
     let init = GnodeInit {
         name: ipv4_fwd_node.name(),
         next_names: ipv4_fwd_node.next_names(),
         cntrs: GnodeCntrs::new(&ipv4_fwd_node.name(), &mut r2.counters),
         perf: Perf::new(&ipv4_fwd_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
     };
     g.add(Box::new(ipv4_fwd_node), init);
+
+    let ipv4_local_node = IPv4Local::new(&mut r2.counters);
+    let init = GnodeInit {
+        name: ipv4_local_node.name(),
+        next_names: ipv4_local_node.next_names(),
+        cntrs: GnodeCntrs::new(&ipv4_local_node.name(), &mut r2.counters),
+        perf: Perf::new(&ipv4_local_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
+    };
+    g.add(Box::new(ipv4_local_node), init);
+
+    let ipv4_reass_node = IPv4Reass::new(&mut r2.counters);
+    let init = GnodeInit {
+        name: ipv4_reass_node.name(),
+        next_names: ipv4_reass_node.next_names(),
+        cntrs: GnodeCntrs::new(&ipv4_reass_node.name(), &mut r2.counters),
+        perf: Perf::new(&ipv4_reass_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
+    };
+    g.add(Box::new(ipv4_reass_node), init);
 }
 
 fn file_write(f: &mut File, s: &str) {
@@ -134,23 +386,239 @@ fn file_write(f: &mut File, s: &str) {
     }
 }
 
-fn route_json_dump(f: &mut File, r2: &R2, prefix: Ipv4Addr, masklen: u32, leaf: &IPv4Leaf) {
-    if let ipv4::Fwd::Adjacency(adj) = &leaf.next {
-        let ifname = if let Some(name) = r2.ifd.get_name(adj.ifindex) {
-            name
+// The (nhop, ifindex, local) of each path of a route, local is set for the receive entries
+// of the interface addresses. Nothing for recursive routes, those have no paths of their own
+fn route_paths(prefix: IpAddr, next: &Fwd) -> Vec<(IpAddr, usize, bool)> {
+    match next {
+        Fwd::Adjacency(adj) => vec![(IpAddr::V4(adj.nhop), adj.ifindex, false)],
+        Fwd::Multipath(mp) => mp
+            .paths
+            .iter()
+            .map(|adj| (IpAddr::V4(adj.nhop), adj.ifindex, false))
+            .collect(),
+        Fwd::Adjacency6(adj) => vec![(IpAddr::V6(adj.nhop), adj.ifindex, false)],
+        Fwd::Local(ifindex) if prefix.is_ipv4() => vec![(IpAddr::V4(ZERO_IP), *ifindex, true)],
+        Fwd::Local(ifindex) => vec![(IpAddr::V6(ZERO_IP6), *ifindex, true)],
+        _ => Vec::new(),
+    }
+}
+
+// Whether a receive entry is the one for the address of the interface, rather than a local
+// route added through the APIs
+fn is_intf_addr(r2: &R2, prefix: IpAddr, ifindex: usize) -> bool {
+    let intf = r2.ifd.get_name(ifindex).and_then(|name| r2.ifd.get(name));
+    match (intf, prefix) {
+        (Some(intf), IpAddr::V4(addr)) => intf.ipv4_addr == addr,
+        (Some(intf), IpAddr::V6(addr)) => intf.ipv6_addr == addr,
+        (None, _) => false,
+    }
+}
+
+// The receive entries for the interface addresses are dumped with "local" set, they are
+// not routes that can be added back. Local routes added through the APIs are dumped with
+// the "local" kind instead
+fn route_json_dump(
+    f: &mut File,
+    r2: &R2,
+    prefix: IpAddr,
+    masklen: u32,
+    path: (IpAddr, usize, bool),
+) {
+    let (nhop, ifindex, local) = path;
+    let ifname = if let Some(name) = r2.ifd.get_name(ifindex) {
+        name
+    } else {
+        "Unknown_ifindex"
+    };
+    let kind = if local { "local" } else { "" };
+    let local = local && is_intf_addr(r2, prefix, ifindex);
+    let dump = format!(
+        "{{ \
+         \"prefix\": \"{}\", \
+         \"masklen\": {}, \
+         \"nhop\": \"{}\", \
+         \"ifname\": \"{}\", \
+         \"ifindex\": {}, \
+         \"kind\": \"{}\", \
+         \"local\": {}}}",
+        prefix, masklen, nhop, ifname, ifindex, kind, local,
+    );
+    file_write(f, &dump);
+}
+
+// A route without an interface - a recursive nexthop of a route, or a blackhole or reject
+// route without a nexthop
+fn nointf_json_dump(f: &mut File, prefix: IpAddr, masklen: u32, nhop: &str, kind: &str) {
+    let dump = format!(
+        "{{ \
+         \"prefix\": \"{}\", \
+         \"masklen\": {}, \
+         \"nhop\": \"{}\", \
+         \"ifname\": \"\", \
+         \"ifindex\": null, \
+         \"kind\": \"{}\", \
+         \"local\": false}}",
+        prefix, masklen, nhop, kind,
+    );
+    file_write(f, &dump);
+}
+
+// A route from the APIs as (prefix, masklen, nhop, ifindex), in either address family. A
+// Via route is an ipv4 route through a recursive nexthop, it has no interface. A Special
+// route has no nexthop
+enum Route {
+    V4(Ipv4Addr, u32, Ipv4Addr, usize),
+    Via(Ipv4Addr, u32, Ipv4Addr),
+    V6(Ipv6Addr, u32, Ipv6Addr, usize),
+    Special(IpAddr, u32, Special),
+}
+
+// Decode a route from the APIs, the prefix decides the address family and the nexthop has
+// to be of the same family. An ipv4 route without an interface is a recursive route. A
+// route of any kind other than the empty kind is a special route, the nexthop is ignored.
+// The ipv6 routes can only be in the default VRF, 'vrf' is None for a VRF that the route
+// would create
+fn route_decode(
+    r2: &R2,
+    ip_mask: &str,
+    nhop: &str,
+    ifname: &str,
+    kind: &str,
+    vrf: Option<usize>,
+) -> Result<Route, String> {
+    if vrf != Some(DEFAULT_VRF) && ip6_mask_decode(ip_mask).is_some() {
+        return Err("IPv6 routes can only be in the default VRF".to_string());
+    }
+    let ifindex = if ifname.is_empty() {
+        None
+    } else {
+        match r2.ifd.get(ifname) {
+            Some(intf) => Some(intf.ifindex),
+            None => return Err(format!("Cannot find interface {}", ifname)),
+        }
+    };
+    if !kind.is_empty() {
+        let special = Special::decode(kind, ifindex)?;
+        return if let Some((ip, mask)) = ip_mask_decode(ip_mask) {
+            Ok(Route::Special(IpAddr::V4(ip), mask, special))
+        } else if let Some((ip, mask)) = ip6_mask_decode(ip_mask) {
+            Ok(Route::Special(IpAddr::V6(ip), mask, special))
         } else {
-            "Unknown_ifindex"
+            Err("Unable to decode IP/MASK".to_string())
+        };
+    }
+    if let Some((ip, mask)) = ip_mask_decode(ip_mask) {
+        let n = match Ipv4Addr::from_str(nhop) {
+            Ok(n) => n,
+            Err(_) => return Err("Unable to decode NHOP".to_string()),
         };
-        let dump = format!(
-            "{{ \
-             \"prefix\": \"{}\", \
-             \"masklen\": {}, \
-             \"nhop\": \"{}\", \
-             \"ifname\": \"{}\", \
-             \"ifindex\": {}}}",
-            prefix, masklen, adj.nhop, ifname, adj.ifindex,
-        );
-        file_write(f, &dump);
+        match ifindex {
+            Some(ifindex) => Ok(Route::V4(ip, mask, n, ifindex)),
+            None if n == ZERO_IP => Err("Need an interface or a NHOP".to_string()),
+            None => Ok(Route::Via(ip, mask, n)),
+        }
+    } else if let Some((ip, mask)) = ip6_mask_decode(ip_mask) {
+        let n = match Ipv6Addr::from_str(nhop) {
+            Ok(n) => n,
+            Err(_) => return Err("Unable to decode NHOP".to_string()),
+        };
+        match ifindex {
+            Some(ifindex) => Ok(Route::V6(ip, mask, n, ifindex)),
+            None => Err("Need an interface for ipv6 routes".to_string()),
+        }
+    } else {
+        Err("Unable to decode IP/MASK".to_string())
+    }
+}
+
+// Decode one entry of a batch of routes
+fn route_entry_decode(r2: &R2, route: &RouteEntry, vrf: Option<usize>) -> Result<Route, String> {
+    let ip_mask = route.ip_and_mask.clone().unwrap_or_default();
+    let nhop = route.nhop.clone().unwrap_or_default();
+    let ifname = route.ifname.clone().unwrap_or_default();
+    let kind = route.kind.clone().unwrap_or_default();
+    route_decode(r2, &ip_mask, &nhop, &ifname, &kind, vrf)
+}
+
+// The id of a VRF from the APIs, for the APIs that need the VRF to be there
+fn vrf_decode(r2: &R2, vrf: &str) -> Result<usize, String> {
+    r2.ipv4
+        .vrf_id(vrf)
+        .ok_or_else(|| format!("Cannot find VRF {}", vrf))
+}
+
+// Adding routes to a VRF creates the VRF, but only once a route is about to go in, so that
+// a failed add does not leave an empty VRF behind. 'id' is the id of the VRF if it is there
+fn vrf_create(r2: &mut R2, vrf: &str, id: Option<usize>) -> usize {
+    match id {
+        Some(id) => id,
+        None => r2.ipv4.vrf_add(vrf),
+    }
+}
+
+impl RouteApis {
+    // All the valid routes in the batch go in with one table update, the return value has an
+    // error string per route, empty if the route went in fine
+    fn handle_routes(
+        &self,
+        routes: Vec<RouteEntry>,
+        vrf: String,
+        add: bool,
+    ) -> thrift::Result<Vec<String>> {
+        let mut r2 = self.r2.lock().unwrap();
+        let vrf_id = match vrf_decode(&r2, &vrf) {
+            Ok(vrf_id) => Some(vrf_id),
+            Err(_) if add => None,
+            Err(why) => return Err(From::from(RouteErr::new(why))),
+        };
+        let mut errors = vec![String::new(); routes.len()];
+        let mut ops = Vec::new();
+        let mut entries = Vec::new();
+        let mut ops6 = Vec::new();
+        let mut entries6 = Vec::new();
+        for (i, route) in routes.iter().enumerate() {
+            match route_entry_decode(&r2, route, vrf_id) {
+                Ok(Route::V4(ip, mask, nhop, ifindex)) => {
+                    ops.push(RouteOp::new(ip, mask, nhop, ifindex, add));
+                    entries.push(i);
+                }
+                Ok(Route::Via(ip, mask, nhop)) => {
+                    ops.push(RouteOp::via(ip, mask, nhop, add));
+                    entries.push(i);
+                }
+                Ok(Route::V6(ip, mask, nhop, ifindex)) => {
+                    ops6.push(Route6Op::new(ip, mask, nhop, ifindex, add));
+                    entries6.push(i);
+                }
+                Ok(Route::Special(IpAddr::V4(ip), mask, special)) => {
+                    ops.push(RouteOp::special(ip, mask, special, add));
+                    entries.push(i);
+                }
+                Ok(Route::Special(IpAddr::V6(ip), mask, special)) => {
+                    ops6.push(Route6Op::special(ip, mask, special, add));
+                    entries6.push(i);
+                }
+                Err(why) => errors[i] = why,
+            }
+        }
+        let mut done = Vec::new();
+        if !ops.is_empty() {
+            let vrf = vrf_create(&mut r2, &vrf, vrf_id);
+            done.extend(entries.into_iter().zip(update_routes(&mut r2, vrf, ops)));
+        }
+        if !ops6.is_empty() {
+            done.extend(entries6.into_iter().zip(update_routes6(&mut r2, ops6)));
+        }
+        for (i, done) in done {
+            // Adding a path that the route already has is not an error
+            if !add && !done {
+                errors[i] = "Route not found".to_string();
+            }
+        }
+        if let Err(why) = r2.sync() {
+            return Err(From::from(RouteErr::new(why)));
+        }
+        Ok(errors)
     }
 }
 
@@ -160,30 +628,37 @@ impl RouteSyncHandler for RouteApis {
         ip_mask: String,
         nhop: String,
         ifname: String,
+        kind: String,
+        vrf: String,
     ) -> thrift::Result<()> {
         let mut r2 = self.r2.lock().unwrap();
+        let vrf_id = r2.ipv4.vrf_id(&vrf);
 
-        let ip;
-        let mask;
-        let nhop_ip;
-        let ifindex;
-        if let Some((i, m)) = ip_mask_decode(&ip_mask) {
-            ip = i;
-            mask = m;
-        } else {
-            return Err(From::from(RouteErr::new("Unable to decode IP/MASK".to_string())));
-        }
-        if let Ok(n) = Ipv4Addr::from_str(&nhop) {
-            nhop_ip = n;
-        } else {
-            return Err(From::from(RouteErr::new("Unable to decode NHOP".to_string())));
+        match route_decode(&r2, &ip_mask, &nhop, &ifname, &kind, vrf_id) {
+            Ok(Route::V4(ip, mask, nhop_ip, ifindex)) => {
+                let vrf = vrf_create(&mut r2, &vrf, vrf_id);
+                add_route(&mut r2, vrf, ip, mask, nhop_ip, ifindex);
+            }
+            Ok(Route::Via(ip, mask, nhop_ip)) => {
+                let vrf = vrf_create(&mut r2, &vrf, vrf_id);
+                update_routes(&mut r2, vrf, vec![RouteOp::via(ip, mask, nhop_ip, true)]);
+            }
+            Ok(Route::V6(ip, mask, nhop_ip, ifindex)) => {
+                update_routes6(&mut r2, vec![Route6Op::new(ip, mask, nhop_ip, ifindex, true)]);
+            }
+            Ok(Route::Special(IpAddr::V4(ip), mask, special)) => {
+                let vrf = vrf_create(&mut r2, &vrf, vrf_id);
+                let op = RouteOp::special(ip, mask, special, true);
+                update_routes(&mut r2, vrf, vec![op]);
+            }
+            Ok(Route::Special(IpAddr::V6(ip), mask, special)) => {
+                update_routes6(&mut r2, vec![Route6Op::special(ip, mask, special, true)]);
+            }
+            Err(why) => return Err(From::from(RouteErr::new(why))),
         }
-        if let Some(intf) = r2.ifd.get(&ifname) {
-            ifindex = intf.ifindex;
-        } else {
-            return Err(From::from(RouteErr::new(format!("Cannot find interface {}", ifname))));
+        if let Err(why) = r2.sync() {
+            return Err(From::from(RouteErr::new(why)));
         }
-        add_route(&mut r2, ip, mask, nhop_ip, ifindex);
         Ok(())
     }
 
@@ -192,43 +667,74 @@ impl RouteSyncHandler for RouteApis {
         ip_mask: String,
         nhop: String,
         ifname: String,
+        kind: String,
+        vrf: String,
     ) -> thrift::Result<()> {
         let mut r2 = self.r2.lock().unwrap();
+        let vrf = match vrf_decode(&r2, &vrf) {
+            Ok(vrf) => vrf,
+            Err(why) => return Err(From::from(RouteErr::new(why))),
+        };
 
-        let ip;
-        let mask;
-        let nhop_ip;
-        let ifindex;
-        if let Some((i, m)) = ip_mask_decode(&ip_mask) {
-            ip = i;
-            mask = m;
-        } else {
-            return Err(From::from(RouteErr::new("Unable to decode IP/MASK".to_string())));
-        }
-        if let Ok(n) = Ipv4Addr::from_str(&nhop) {
-            nhop_ip = n;
-        } else {
-            return Err(From::from(RouteErr::new("Unable to decode NHOP".to_string())));
+        match route_decode(&r2, &ip_mask, &nhop, &ifname, &kind, Some(vrf)) {
+            Ok(Route::V4(ip, mask, nhop_ip, ifindex)) => {
+                del_route(&mut r2, vrf, ip, mask, nhop_ip, ifindex);
+            }
+            Ok(Route::Via(ip, mask, nhop_ip)) => {
+                update_routes(&mut r2, vrf, vec![RouteOp::via(ip, mask, nhop_ip, false)]);
+            }
+            Ok(Route::V6(ip, mask, nhop_ip, ifindex)) => {
+                update_routes6(
+                    &mut r2,
+                    vec![Route6Op::new(ip, mask, nhop_ip, ifindex, false)],
+                );
+            }
+            Ok(Route::Special(IpAddr::V4(ip), mask, special)) => {
+                let op = RouteOp::special(ip, mask, special, false);
+                update_routes(&mut r2, vrf, vec![op]);
+            }
+            Ok(Route::Special(IpAddr::V6(ip), mask, special)) => {
+                update_routes6(&mut r2, vec![Route6Op::special(ip, mask, special, false)]);
+            }
+            Err(why) => return Err(From::from(RouteErr::new(why))),
         }
-        if let Some(intf) = r2.ifd.get(&ifname) {
-            ifindex = intf.ifindex;
-        } else {
-            return Err(From::from(RouteErr::new(format!("Cannot find interface {}", ifname))));
+        if let Err(why) = r2.sync() {
+            return Err(From::from(RouteErr::new(why)));
         }
-        del_route(&mut r2, ip, mask, nhop_ip, ifindex);
         Ok(())
     }
 
-    fn handle_show(&self, prefix: String, filename: String) -> thrift::Result<String> {
-        if let Ok(ipaddr) = Ipv4Addr::from_str(&prefix) {
+    fn handle_add_routes(
+        &self,
+        routes: Vec<RouteEntry>,
+        vrf: String,
+    ) -> thrift::Result<Vec<String>> {
+        self.handle_routes(routes, vrf, true)
+    }
+
+    fn handle_del_routes(
+        &self,
+        routes: Vec<RouteEntry>,
+        vrf: String,
+    ) -> thrift::Result<Vec<String>> {
+        self.handle_routes(routes, vrf, false)
+    }
+
+    fn handle_show(&self, prefix: String, filename: String, vrf: String) -> thrift::Result<String> {
+        let vrf = match self.r2.lock().unwrap().ipv4.vrf_id(&vrf) {
+            Some(vrf) => vrf,
+            None => {
+                return Err(From::from(RouteErr::new(format!(
+                    "Cannot find VRF {}",
+                    vrf
+                ))));
+            }
+        };
+        if let Ok(ipaddr) = IpAddr::from_str(&prefix) {
             let r2 = self.r2.lock().unwrap();
-            let mut s = "Table1:\n".to_string();
-            s.push_str(&self.handle_show_one(&r2, &r2.ipv4.table1, ipaddr));
-            s.push_str("Table2:\n");
-            s.push_str(&self.handle_show_one(&r2, &r2.ipv4.table2, ipaddr));
-            Ok(s)
+            Ok(self.handle_show_one(&r2, ipaddr, vrf))
         } else if prefix == "all" {
-            self.handle_show_all(filename)
+            self.handle_show_all(filename, vrf)
         } else {
             Err(From::from(RouteErr::new(format!(
                 "Option should be ip address or keyword 'all': {}",
@@ -238,76 +744,92 @@ impl RouteSyncHandler for RouteApis {
     }
 }
 
-// First add to the  table thats not currently in use, then broadcast
-// that table for all pipelines to use/switch to. And then add the same
-// route onto the old table, looping till all the old table references
-// fall off (when all dataplanes switch to the new one)
-fn add_or_del_route(
+// Apply a batch of route changes to a standby table of the VRF and publish it to the
+// forwarding threads with one message, see IPv4Vrf. Returns whether each change took effect
+// - false for adding a path that the route already has or deleting a path that the route
+// doesnt have. The recursive routes are resolved again after every batch, so the routes
+// through a nexthop follow the route to the nexthop as it comes, goes or changes
+pub fn update_routes(r2: &mut R2, vrf: usize, ops: Vec<RouteOp>) -> Vec<bool> {
+    let ipv4 = &mut r2.ipv4.vrfs[vrf];
+    let mut standby = match ipv4.retired.take().map(Arc::try_unwrap) {
+        Some(Ok(mut table)) => {
+            for op in ipv4.backlog.iter() {
+                op.apply(&mut table);
+            }
+            table.resolve();
+            table
+        }
+        // Still in use by some thread, dropping our reference leaves it to the last user
+        _ => (*ipv4.active).clone(),
+    };
+    let ret = ops.iter().map(|op| op.apply(&mut standby)).collect();
+    // Any change can change what the recursive nexthops resolve to
+    standby.resolve();
+    let standby = Arc::new(standby);
+    ipv4.retired = Some(std::mem::replace(&mut ipv4.active, standby.clone()));
+    ipv4.backlog = ops;
+    r2.broadcast(R2Msg::IPv4TableAdd(IPv4TableMsg::new(vrf, standby)));
+    ret
+}
+
+pub fn add_route(
     r2: &mut R2,
+    vrf: usize,
     ip: Ipv4Addr,
     masklen: u32,
     nhop: Ipv4Addr,
     ifindex: usize,
-    add: bool,
 ) {
-    let next = Fwd::Adjacency(Arc::new(Adjacency::new(nhop, ifindex)));
-    let leaf = Arc::new(IPv4Leaf::new(next));
-    match r2.ipv4.which {
-        V4Table::Table1 => {
-            let table = Arc::get_mut(&mut r2.ipv4.table2).unwrap();
-            if add {
-                table.add(ip, masklen, leaf.clone());
-            } else {
-                table.del(ip, masklen);
+    update_routes(
+        r2,
+        vrf,
+        vec![RouteOp::new(ip, masklen, nhop, ifindex, true)],
+    );
+}
+
+pub fn del_route(
+    r2: &mut R2,
+    vrf: usize,
+    ip: Ipv4Addr,
+    masklen: u32,
+    nhop: Ipv4Addr,
+    ifindex: usize,
+) {
+    update_routes(
+        r2,
+        vrf,
+        vec![RouteOp::new(ip, masklen, nhop, ifindex, false)],
+    );
+}
+
+// Delete all the routes pointing to an interface and the receive entries for its address,
+// in all the VRFs, used when the interface goes away
+pub fn del_routes_ifindex(r2: &mut R2, ifindex: usize) {
+    for vrf in 0..r2.ipv4.vrfs.len() {
+        del_routes_ifindex_vrf(r2, vrf, ifindex);
+    }
+}
+
+fn del_routes_ifindex_vrf(r2: &mut R2, vrf: usize, ifindex: usize) {
+    let mut ops = Vec::new();
+    for (prefix, masklen, leaf) in r2.ipv4.active(vrf).iter() {
+        match &leaf.next {
+            Fwd::Adjacency(adj) if adj.ifindex == ifindex => {
+                ops.push(RouteOp::new(prefix, masklen, adj.nhop, ifindex, false));
             }
-            r2.ipv4.which = V4Table::Table2;
-            let msg = IPv4TableMsg::new(r2.ipv4.table2.clone());
-            r2.broadcast(R2Msg::IPv4TableAdd(msg));
-            loop {
-                // The Arc::get_mut() will succeed only when the table has a refcount of 1.
-                // Ie when all forwarding plane threads drop their reference and only the
-                // control plane thread has the reference.
-                if let Some(table) = Arc::get_mut(&mut r2.ipv4.table1) {
-                    if add {
-                        table.add(ip, masklen, leaf);
-                    } else {
-                        table.del(ip, masklen);
-                    }
-                    break;
+            Fwd::Multipath(mp) => {
+                for adj in mp.paths.iter().filter(|adj| adj.ifindex == ifindex) {
+                    ops.push(RouteOp::new(prefix, masklen, adj.nhop, ifindex, false));
                 }
             }
-        }
-        V4Table::Table2 => {
-            let table = Arc::get_mut(&mut r2.ipv4.table1).unwrap();
-            if add {
-                table.add(ip, masklen, leaf.clone());
-            } else {
-                table.del(ip, masklen);
-            }
-            r2.ipv4.which = V4Table::Table1;
-            let msg = IPv4TableMsg::new(r2.ipv4.table1.clone());
-            r2.broadcast(R2Msg::IPv4TableAdd(msg));
-            loop {
-                // The Arc::get_mut() will succeed only when the table has a refcount of 1.
-                // Ie when all forwarding plane threads drop their reference and only the
-                // control plane thread has the reference.
-                if let Some(table) = Arc::get_mut(&mut r2.ipv4.table2) {
-                    if add {
-                        table.add(ip, masklen, leaf);
-                    } else {
-                        table.del(ip, masklen);
-                    }
-                    break;
-                }
+            Fwd::Local(i) if *i == ifindex => {
+                let special = Special::Local(ifindex);
+                ops.push(RouteOp::special(prefix, masklen, special, false));
             }
+            _ => {}
         }
     }
-}
-
-pub fn add_route(r2: &mut R2, ip: Ipv4Addr, masklen: u32, nhop: Ipv4Addr, ifindex: usize) {
-    add_or_del_route(r2, ip, masklen, nhop, ifindex, true);
-}
-
-pub fn del_route(r2: &mut R2, ip: Ipv4Addr, masklen: u32, nhop: Ipv4Addr, ifindex: usize) {
-    add_or_del_route(r2, ip, masklen, nhop, ifindex, false);
+    if !ops.is_empty() {
+        update_routes(r2, vrf, ops);
+    }
 }
@@ -0,0 +1,132 @@
+use super::*;
+use crate::ipv4::Special;
+use fwd::ipv6::{IPv6Leaf, IPv6Table, IPv6TableMsg};
+use fwd::{adj::Adjacency6, Fwd};
+use l3_ipv6_fwd::IPv6Fwd;
+use l3_ipv6_parse::IPv6Parse;
+use perf::Perf;
+use std::net::Ipv6Addr;
+
+// The ipv6 route table, updated RCU style exactly like the ipv4 table - see IPv4Ctx
+pub struct IPv6Ctx {
+    pub active: Arc<IPv6Table>,
+    retired: Option<Arc<IPv6Table>>,
+    // The changes made to the active table after the retired table was retired
+    backlog: Vec<Route6Op>,
+}
+
+impl IPv6Ctx {
+    pub fn new() -> IPv6Ctx {
+        IPv6Ctx {
+            active: Arc::new(IPv6Table::new()),
+            retired: None,
+            backlog: Vec::new(),
+        }
+    }
+}
+
+// Add a route if leaf is present, delete it otherwise
+pub struct Route6Op {
+    ip: Ipv6Addr,
+    masklen: u32,
+    leaf: Option<Arc<IPv6Leaf>>,
+}
+
+impl Route6Op {
+    pub fn new(ip: Ipv6Addr, masklen: u32, nhop: Ipv6Addr, ifindex: usize, add: bool) -> Route6Op {
+        let leaf = if add {
+            let next = Fwd::Adjacency6(Arc::new(Adjacency6::new(nhop, ifindex)));
+            Some(Arc::new(IPv6Leaf::new(next)))
+        } else {
+            None
+        };
+        Route6Op { ip, masklen, leaf }
+    }
+
+    pub fn special(ip: Ipv6Addr, masklen: u32, special: Special, add: bool) -> Route6Op {
+        let leaf = if add {
+            Some(Arc::new(IPv6Leaf::new(special.fwd())))
+        } else {
+            None
+        };
+        Route6Op { ip, masklen, leaf }
+    }
+
+    // The /128 receive entry for an address owned by the interface
+    pub fn local(ip: Ipv6Addr, ifindex: usize, add: bool) -> Route6Op {
+        Route6Op::special(ip, 128, Special::Local(ifindex), add)
+    }
+
+    fn apply(&self, table: &mut IPv6Table) -> bool {
+        if let Some(leaf) = &self.leaf {
+            table.add(self.ip, self.masklen, leaf.clone())
+        } else {
+            table.del(self.ip, self.masklen)
+        }
+    }
+}
+
+pub fn create_ipv6_nodes(r2: &mut R2, g: &mut Graph<R2Msg>) {
+    let ipv6_parse_node = IPv6Parse::new(&mut r2.counters);
+    let init = GnodeInit {
+        name: ipv6_parse_node.name(),
+        next_names: ipv6_parse_node.next_names(),
+        cntrs: GnodeCntrs::new(&ipv6_parse_node.name(), &mut r2.counters),
+        perf: Perf::new(&ipv6_parse_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
+    };
+    g.add(Box::new(ipv6_parse_node), init);
+
+    let ipv6_fwd_node = IPv6Fwd::new(r2.ipv6.active.clone(), &mut r2.counters);
+    let init = GnodeInit {
+        name: ipv6_fwd_node.name(),
+        next_names: ipv6_fwd_node.next_names(),
+        cntrs: GnodeCntrs::new(&ipv6_fwd_node.name(), &mut r2.counters),
+        perf: Perf::new(&ipv6_fwd_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
+    };
+    g.add(Box::new(ipv6_fwd_node), init);
+}
+
+// Apply a batch of route changes to a standby table and publish it to the forwarding
+// threads, see update_routes() for the ipv4 table
+pub fn update_routes6(r2: &mut R2, ops: Vec<Route6Op>) -> Vec<bool> {
+    let ipv6 = &mut r2.ipv6;
+    let mut standby = match ipv6.retired.take().map(Arc::try_unwrap) {
+        Some(Ok(mut table)) => {
+            for op in ipv6.backlog.iter() {
+                op.apply(&mut table);
+            }
+            table
+        }
+        // Still in use by some thread, dropping our reference leaves it to the last user
+        _ => (*ipv6.active).clone(),
+    };
+    let ret = ops.iter().map(|op| op.apply(&mut standby)).collect();
+    let standby = Arc::new(standby);
+    ipv6.retired = Some(std::mem::replace(&mut ipv6.active, standby.clone()));
+    ipv6.backlog = ops;
+    r2.broadcast(R2Msg::IPv6TableAdd(IPv6TableMsg::new(standby)));
+    ret
+}
+
+// Delete all the routes pointing to an interface and the receive entries for its address,
+// used when the interface goes away
+pub fn del_routes6_ifindex(r2: &mut R2, ifindex: usize) {
+    let mut ops = Vec::new();
+    for (prefix, masklen, leaf) in r2.ipv6.active.iter() {
+        match &leaf.next {
+            Fwd::Adjacency6(adj) if adj.ifindex == ifindex => {
+                ops.push(Route6Op::new(prefix, masklen, adj.nhop, ifindex, false));
+            }
+            Fwd::Local(i) if *i == ifindex => {
+                let special = Special::Local(ifindex);
+                ops.push(Route6Op::special(prefix, masklen, special, false));
+            }
+            _ => {}
+        }
+    }
+    if !ops.is_empty() {
+        update_routes6(r2, ops);
+    }
+}
@@ -1,12 +1,16 @@
 use api::ApiSvr;
+use apis_graph::GraphSyncProcessor;
 use apis_interface::InterfaceSyncProcessor;
 use apis_log::LogSyncProcessor;
+use apis_pbr::PbrSyncProcessor;
 use apis_route::RouteSyncProcessor;
+use apis_trace::TraceSyncProcessor;
 use counters::Counters;
 use crossbeam_queue::ArrayQueue;
 use efd::Efd;
 use epoll::{Epoll, EpollClient, EPOLLIN};
-use graph::{GnodeCntrs, GnodeInit, Graph};
+use graph::trace::Trace;
+use graph::{GnodeCntrs, GnodeInit, Graph, VEC_SIZE};
 use l2_eth_encap::EncapMux;
 use log::Logger;
 use msg::R2Msg;
@@ -14,13 +18,17 @@ use names::rx_tx;
 use packet::PktsHeap;
 use std::collections::HashMap;
 use std::convert::From;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 mod ifd;
 use ifd::{IfdCtx, InterfaceApis};
 mod ipv4;
 use ipv4::{create_ipv4_nodes, IPv4Ctx, RouteApis};
+mod ipv6;
+use ipv6::{create_ipv6_nodes, IPv6Ctx};
 mod msgs;
 use msgs::{ctrl2fwd_messages, fwd2ctrl_messages};
 mod logs;
@@ -30,6 +38,17 @@ use dpdk::{DpdkGlobal, PktsDpdk};
 use ini::Ini;
 use logs::LogApis;
 use perf::Perf;
+mod trace;
+use trace::TraceApis;
+mod graphs;
+use graphs::GraphApis;
+mod features;
+use features::FeatureCtx;
+mod pbr;
+use pbr::{create_pbr_nodes, PbrApis, PbrCtx};
+mod plugins;
+use plugin::Plugin;
+use plugins::{create_plugin_nodes, load_plugins, register_plugin_apis};
 
 const THREADS: usize = 2;
 const LOGSZ: usize = 32;
@@ -40,6 +59,10 @@ const DEF_PARTS: usize = 2 * DEF_PKTS;
 const DEF_PARTICLE_SZ: usize = 3072;
 const R2_CFG_FILE: &str = "/etc/r2.cfg";
 pub const MAX_HEADROOM: usize = 100;
+// How long to wait for a forwarding thread to reply to a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+// How long to wait for the forwarding threads to apply a configuration change
+const SYNC_TIMEOUT: Duration = Duration::from_secs(2);
 
 // This holds various pieces of context for all of R2, like the interface context,
 // routing context etc.. This is shared across all control threads, but NOT shared
@@ -52,6 +75,10 @@ pub struct R2 {
     threads: Vec<R2PerThread>,
     ifd: IfdCtx,
     ipv4: IPv4Ctx,
+    ipv6: IPv6Ctx,
+    pbr: PbrCtx,
+    features: FeatureCtx,
+    plugins: Vec<Box<dyn Plugin>>,
     dpdk: DpdkGlobal,
 }
 
@@ -80,9 +107,12 @@ impl R2 {
             threads.push(R2PerThread {
                 thread: t,
                 ctrl2fwd: None,
+                epoch: 0,
+                acked: Arc::new(AtomicU64::new(0)),
                 efd,
                 poll_fds: Vec::new(),
                 logger,
+                trace: Arc::new(Trace::new(t)),
             });
         }
 
@@ -93,14 +123,19 @@ impl R2 {
             threads,
             ifd: IfdCtx::new(),
             ipv4: IPv4Ctx::new(),
+            ipv6: IPv6Ctx::new(),
+            pbr: PbrCtx::new(),
+            features: FeatureCtx::new(),
+            plugins: Vec::new(),
             dpdk: Default::default(),
         }
     }
 
     fn unicast(&mut self, msg: R2Msg, idx: usize) {
-        let t = &self.threads[idx];
+        let t = &mut self.threads[idx];
         if let Some(s) = &t.ctrl2fwd {
             s.send(msg).unwrap();
+            t.epoch += 1;
         }
         t.efd.write(1);
     }
@@ -110,14 +145,73 @@ impl R2 {
     // threads. But there can be exceptions like drivers which might want to send messages
     // specific to a thread, and those rare exceptions will use unicast() above
     fn broadcast(&mut self, msg: R2Msg) {
-        for t in self.threads.iter() {
+        for t in self.threads.iter_mut() {
             if let Some(s) = &t.ctrl2fwd {
                 s.send(msg.clone(&mut self.counters, t.logger.clone()))
                     .unwrap();
+                t.epoch += 1;
             }
             t.efd.write(1);
         }
     }
+
+    // Wait till every forwarding thread has processed all the messages sent to it so far. The
+    // messages to a thread are processed in the order they are sent, so the thread has caught
+    // up once the number of messages it acked reaches the number of messages sent to it (its
+    // epoch). API handlers call this before replying, so that the client knows the config is
+    // live in the forwarding plane when the API call returns.
+    // NOTE: The caller holds the r2 lock while we wait here, so the other API calls (and the
+    // messages from the forwarding threads) wait for up to SYNC_TIMEOUT behind a thread that
+    // is slow to catch up. The forwarding threads themselves never take the r2 lock, so they
+    // can always make progress and the wait cant deadlock
+    fn sync(&self) -> Result<(), String> {
+        let start = std::time::Instant::now();
+        for t in self.threads.iter() {
+            if t.ctrl2fwd.is_none() {
+                continue;
+            }
+            while t.acked.load(Ordering::Acquire) < t.epoch {
+                if start.elapsed() > SYNC_TIMEOUT {
+                    return Err(format!(
+                        "Thread {} did not apply config in {:?}",
+                        t.thread, SYNC_TIMEOUT
+                    ));
+                }
+                thread::sleep(Duration::from_micros(100));
+            }
+        }
+        Ok(())
+    }
+
+    // Send a request to the forwarding threads (all of them if thread is None) and wait for
+    // each thread to reply on the channel the request carries, this is how the control thread
+    // gets to see state that is owned by the forwarding threads. Threads that do not reply in
+    // time are left out, the replies are sorted by thread
+    fn request<R>(
+        &mut self,
+        msg: R2Msg,
+        receiver: Receiver<(usize, R)>,
+        thread: Option<usize>,
+    ) -> Vec<(usize, R)> {
+        let mut expected = 0;
+        for t in 0..self.threads.len() {
+            if self.threads[t].ctrl2fwd.is_none() || (thread.is_some() && thread != Some(t)) {
+                continue;
+            }
+            let m = msg.clone(&mut self.counters, self.threads[t].logger.clone());
+            self.unicast(m, t);
+            expected += 1;
+        }
+        let mut replies = Vec::new();
+        for _ in 0..expected {
+            match receiver.recv_timeout(REQUEST_TIMEOUT) {
+                Ok(r) => replies.push(r),
+                Err(_) => break,
+            }
+        }
+        replies.sort_by_key(|r| r.0);
+        replies
+    }
 }
 
 struct R2CfgDpdk {
@@ -131,6 +225,8 @@ struct R2Cfg {
     parts: usize,
     part_sz: usize,
     dpdk: R2CfgDpdk,
+    // Directory to load plugin libraries from
+    plugins: Option<String>,
 }
 
 impl Default for R2Cfg {
@@ -145,6 +241,7 @@ impl Default for R2Cfg {
                 mem: 0,
                 ncores: 0,
             },
+            plugins: None,
         }
     }
 }
@@ -153,9 +250,13 @@ impl Default for R2Cfg {
 struct R2PerThread {
     thread: usize,
     ctrl2fwd: Option<Sender<R2Msg>>,
+    // Number of messages sent to the thread, and the number the thread has processed
+    epoch: u64,
+    acked: Arc<AtomicU64>,
     efd: Arc<Efd>,
     poll_fds: Vec<i32>,
     logger: Arc<Logger>,
+    trace: Arc<Trace>,
 }
 
 struct R2Epoll {}
@@ -171,6 +272,7 @@ fn create_ethernet_mux(r2: &mut R2, g: &mut Graph<R2Msg>) {
         next_names: emux.next_names(),
         cntrs: GnodeCntrs::new(&emux.name(), &mut r2.counters),
         perf: Perf::new(&emux.name(), &mut r2.counters),
+        depth: VEC_SIZE,
     };
     g.add(Box::new(emux), init);
 }
@@ -180,7 +282,10 @@ fn create_ethernet_mux(r2: &mut R2, g: &mut Graph<R2Msg>) {
 // all other feature nodes should get created here.
 fn create_nodes(r2: &mut R2, g: &mut Graph<R2Msg>) {
     create_ipv4_nodes(r2, g);
+    create_pbr_nodes(r2, g);
+    create_ipv6_nodes(r2, g);
     create_ethernet_mux(r2, g);
+    create_plugin_nodes(r2, g);
     g.finalize();
 }
 
@@ -201,12 +306,27 @@ fn register_apis(r2: Arc<Mutex<R2>>) -> ApiSvr {
     let log_apis = LogApis::new(r2.clone());
     svr.register(common::LOG_APIS, Box::new(LogSyncProcessor::new(log_apis)));
 
-    let route_apis = RouteApis::new(r2);
+    let route_apis = RouteApis::new(r2.clone());
     svr.register(
         common::ROUTE_APIS,
         Box::new(RouteSyncProcessor::new(route_apis)),
     );
 
+    let trace_apis = TraceApis::new(r2.clone());
+    svr.register(
+        common::TRACE_APIS,
+        Box::new(TraceSyncProcessor::new(trace_apis)),
+    );
+
+    let graph_apis = GraphApis::new(r2.clone());
+    svr.register(
+        common::GRAPH_APIS,
+        Box::new(GraphSyncProcessor::new(graph_apis)),
+    );
+
+    let pbr_apis = PbrApis::new(r2);
+    svr.register(common::PBR_APIS, Box::new(PbrSyncProcessor::new(pbr_apis)));
+
     svr
 }
 
@@ -214,6 +334,8 @@ struct ThreadParams {
     thread: usize,
     epoll: Epoll,
     receiver: Receiver<R2Msg>,
+    acked: Arc<AtomicU64>,
+    fwd2ctrl: Sender<R2Msg>,
     g: Graph<R2Msg>,
 }
 
@@ -223,17 +345,28 @@ fn launch_pthread(mut t: Box<ThreadParams>) {
         .name(name)
         .spawn(move || loop {
             let mut work = true;
-            // For now we dont honor the 'time' parameter here, which mostly comes into play
-            // if the scheduler has work to be done at a future time in which case we can yield
-            // till that time.
+            let mut nsecs = 0;
+            // Keep running the graph as long as some node has work to do right now. Nodes
+            // that have work only at a future time (like a shaper) dont keep us running,
+            // instead we set a timer to wake us up at the earliest such time
             while work {
-                let (w, _) = t.g.run();
-                work = w;
+                let (w, time) = t.g.run();
+                work = w && time == 0;
+                nsecs = if w { time } else { 0 };
                 // interleave packet forwarding with checking for control messages, depending
                 // on performance measurements, this can be done (much) less frequently
-                ctrl2fwd_messages(t.thread, &mut t.epoll, &t.receiver, &mut t.g);
+                ctrl2fwd_messages(
+                    t.thread,
+                    &mut t.epoll,
+                    &t.receiver,
+                    &t.acked,
+                    &t.fwd2ctrl,
+                    &mut t.g,
+                );
             }
             // No more packets or control messages to process, sleep till someone wakes us up
+            // or till the time some node has work, whichever is earlier
+            t.epoll.timer(nsecs as u64);
             t.epoll.wait();
         })
         .unwrap();
@@ -258,15 +391,21 @@ extern "C" fn dpdk_eal_thread(arg: *mut core::ffi::c_void) -> i32 {
 fn dpdk_thread(mut t: Box<ThreadParams>) {
     loop {
         let mut work = true;
-        // For now we dont honor the 'time' parameter here, which mostly comes into play
-        // if the scheduler has work to be done at a future time in which case we can yield
-        // till that time.
+        // DPDK threads never sleep, they keep polling the drivers for packets, so the nodes
+        // that have work at a future time will get to run at that time anyways
         while work {
             let (w, _) = t.g.run();
             work = w;
             // interleave packet forwarding with checking for control messages, depending
             // on performance measurements, this can be done (much) less frequently
-            ctrl2fwd_messages(t.thread, &mut t.epoll, &t.receiver, &mut t.g);
+            ctrl2fwd_messages(
+                t.thread,
+                &mut t.epoll,
+                &t.receiver,
+                &t.acked,
+                &t.fwd2ctrl,
+                &mut t.g,
+            );
         }
     }
 }
@@ -277,7 +416,7 @@ fn dpdk_thread(mut t: Box<ThreadParams>) {
 // when control thread wants to send a message to this forwarding thread.
 // NOTE: The model here is an epoll driven wakeup model - but once we have tight polling
 // drivers lke DPDK integrated, this model will change - maybe epoll wait will be taken out
-fn create_thread(r2: &mut R2, g: Graph<R2Msg>, thread: usize) {
+fn create_thread(r2: &mut R2, mut g: Graph<R2Msg>, thread: usize) {
     // Channel to talk to and from control plane
     let (sender, receiver) = channel();
     // This is the descriptor used to wakeup the thread in genenarl, ie unlreated to any
@@ -285,6 +424,7 @@ fn create_thread(r2: &mut R2, g: Graph<R2Msg>, thread: usize) {
     let efd = r2.threads[thread].efd.clone();
     let epoll = Epoll::new(efd, MAX_FDS, -1, Box::new(R2Epoll {})).unwrap();
     r2.threads[thread].ctrl2fwd = Some(sender);
+    g.set_trace(r2.threads[thread].trace.clone());
     // The poll_fds are the descriptors that we know of at the moment (if any), when the
     // thread is getting launched. When interfaces are created later, they will come up
     // with their own descriptors.
@@ -295,6 +435,8 @@ fn create_thread(r2: &mut R2, g: Graph<R2Msg>, thread: usize) {
         thread,
         epoll,
         receiver,
+        acked: r2.threads[thread].acked.clone(),
+        fwd2ctrl: r2.fwd2ctrl.clone(),
         g,
     });
 
@@ -417,6 +559,16 @@ fn parse_cfg() -> R2Cfg {
                         }
                     }
                 }
+                "plugins" => {
+                    for (k, v) in prop.iter() {
+                        match k {
+                            "dir" => {
+                                ret.plugins = Some(v.to_string());
+                            }
+                            unknown => panic!("Unknown plugins config {}", unknown),
+                        }
+                    }
+                }
                 unknown => panic!("Unknown config {}", unknown),
             }
         }
@@ -472,10 +624,14 @@ fn main() {
         ));
         graph = Graph::<R2Msg>::new(0, pool, queue, &mut r2.counters);
     }
+    // Plugins have to be loaded before the nodes are created, because plugins might want
+    // to add features to the feature arcs of the nodes
+    load_plugins(&mut r2);
     create_nodes(&mut r2, &mut graph);
     launch_threads(&mut r2, graph);
 
-    let svr = register_apis(r2_rc.clone());
+    let mut svr = register_apis(r2_rc.clone());
+    register_plugin_apis(&mut r2, &mut svr);
     // Api server attempts to take r2 locks, so release it before api svr is launched
     drop(r2);
     launch_api_svr(svr);
@@ -1,4 +1,5 @@
 use super::*;
+use msg::{GnodeFreeMsg, NodeReply};
 use names::l2_eth_decap;
 use names::l2_eth_encap;
 
@@ -6,49 +7,115 @@ pub fn ctrl2fwd_messages(
     thread: usize,
     epoll: &mut Epoll,
     receiver: &Receiver<R2Msg>,
+    acked: &AtomicU64,
+    fwd2ctrl: &Sender<R2Msg>,
     g: &mut Graph<R2Msg>,
 ) {
     while let Ok(msg) = receiver.try_recv() {
-        match msg {
-            R2Msg::GnodeAdd(gnode_add) => {
-                g.add(gnode_add.node, gnode_add.init);
-                g.finalize();
+        ctrl2fwd_message(thread, epoll, fwd2ctrl, g, msg);
+        // Let control know how far we got, see R2::sync()
+        acked.fetch_add(1, Ordering::Release);
+    }
+}
+
+fn ctrl2fwd_message(
+    thread: usize,
+    epoll: &mut Epoll,
+    fwd2ctrl: &Sender<R2Msg>,
+    g: &mut Graph<R2Msg>,
+    msg: R2Msg,
+) {
+    match msg {
+        R2Msg::GnodeAdd(gnode_add) => {
+            g.add(gnode_add.node, gnode_add.init);
+            g.finalize();
+        }
+        R2Msg::GnodeDel(gnode_del) => {
+            // The node's counters are owned by the control thread, so hand the node
+            // back to control to free them
+            if let Some((node, init)) = g.del(&gnode_del.name) {
+                let msg = GnodeFreeMsg { node, init };
+                fwd2ctrl.send(R2Msg::GnodeFree(msg)).unwrap();
             }
-            R2Msg::EpollAdd(epoll_add) => {
-                if epoll_add.thread == thread {
-                    if let Some(fd) = epoll_add.fd {
-                        epoll.add(fd, EPOLLIN);
-                    }
-                }
+        }
+        R2Msg::EdgeAdd(mut edge_add) => {
+            // Let the node know which edge index it got for the new next node
+            if let Some(edge) = g.add_edge(&edge_add.node, &edge_add.next) {
+                edge_add.edge = edge;
+                let node = edge_add.node.clone();
+                g.control_msg(&node, R2Msg::EdgeAdd(edge_add));
             }
-            R2Msg::IPv4TableAdd(_) => {
-                g.control_msg(names::L3_IPV4_FWD, msg);
+        }
+        R2Msg::EdgeDel(edge_del) => {
+            let node = edge_del.node.clone();
+            g.control_msg(&node, R2Msg::EdgeDel(edge_del));
+        }
+        R2Msg::EpollAdd(epoll_add) => {
+            if epoll_add.thread == thread {
+                if let Some(fd) = epoll_add.fd {
+                    epoll.add(fd, EPOLLIN);
+                }
             }
-            R2Msg::ModifyInterface(mod_intf) => {
-                g.control_msg(
-                    &l2_eth_decap(mod_intf.intf.ifindex),
-                    R2Msg::ModifyInterface(mod_intf.clone()),
-                );
-                g.control_msg(
-                    &l2_eth_encap(mod_intf.intf.ifindex),
-                    R2Msg::ModifyInterface(mod_intf.clone()),
-                );
-                g.control_msg(
-                    &rx_tx(mod_intf.intf.ifindex),
-                    R2Msg::ModifyInterface(mod_intf),
-                );
+        }
+        R2Msg::EpollDel(epoll_del) => {
+            if epoll_del.thread == thread {
+                epoll.del(epoll_del.fd);
             }
-            R2Msg::EthMacAdd(mac_add) => {
-                g.control_msg(
-                    &l2_eth_decap(mac_add.ifindex),
-                    R2Msg::EthMacAdd(mac_add.clone()),
-                );
-                g.control_msg(&l2_eth_encap(mac_add.ifindex), R2Msg::EthMacAdd(mac_add));
+        }
+        R2Msg::GnodeFree(_) => panic!("Unexpected message"),
+        R2Msg::IPv4TableAdd(_) => {
+            g.control_msg(names::L3_IPV4_FWD, msg);
+        }
+        R2Msg::IPv6TableAdd(_) => {
+            g.control_msg(names::L3_IPV6_FWD, msg);
+        }
+        R2Msg::PbrRules(_) => {
+            g.control_msg(names::L3_IPV4_PBR, msg);
+        }
+        R2Msg::ModifyInterface(mod_intf) => {
+            g.control_msg(
+                &l2_eth_decap(mod_intf.intf.ifindex),
+                R2Msg::ModifyInterface(mod_intf.clone()),
+            );
+            g.control_msg(
+                &l2_eth_encap(mod_intf.intf.ifindex),
+                R2Msg::ModifyInterface(mod_intf.clone()),
+            );
+            g.control_msg(
+                &rx_tx(mod_intf.intf.ifindex),
+                R2Msg::ModifyInterface(mod_intf.clone()),
+            );
+            g.control_msg(names::L3_IPV4_FWD, R2Msg::ModifyInterface(mod_intf.clone()));
+            g.control_msg(names::L3_IPV6_FWD, R2Msg::ModifyInterface(mod_intf));
+        }
+        R2Msg::EthMacAdd(mac_add) => {
+            g.control_msg(
+                &l2_eth_decap(mac_add.ifindex),
+                R2Msg::EthMacAdd(mac_add.clone()),
+            );
+            g.control_msg(&l2_eth_encap(mac_add.ifindex), R2Msg::EthMacAdd(mac_add));
+        }
+        R2Msg::ClassAdd(class) => {
+            g.control_msg(&rx_tx(class.ifindex), R2Msg::ClassAdd(class));
+        }
+        R2Msg::FeatureEnable(feature) => {
+            for node in feature.nodes.iter() {
+                g.control_msg(node, R2Msg::FeatureEnable(feature.clone()));
             }
-            R2Msg::ClassAdd(class) => {
-                g.control_msg(&rx_tx(class.ifindex), R2Msg::ClassAdd(class));
+        }
+        R2Msg::NodeQuery(query) => {
+            // Reply on behalf of a node that is not there or that does not understand
+            // the query, so that the requester hears back from every thread
+            let reply = query.reply.clone();
+            let node = query.node.clone();
+            if !g.control_msg(&node, R2Msg::NodeQuery(query)) {
+                let _ = reply.send((thread, NodeReply::Unsupported));
             }
         }
+        R2Msg::GraphInfo(info) => {
+            // The requester might have given up waiting, nothing to do if so
+            let _ = info.reply.send((thread, g.info()));
+        }
     }
 }
 
@@ -59,6 +126,11 @@ pub fn fwd2ctrl_messages(r2: Arc<Mutex<R2>>, receiver: Receiver<R2Msg>) {
                 let mut r2 = r2.lock().unwrap();
                 r2.broadcast(R2Msg::EthMacAdd(mac_add));
             }
+            R2Msg::GnodeFree(gnode_free) => {
+                let mut r2 = r2.lock().unwrap();
+                gnode_free.node.free(&mut r2.counters);
+                gnode_free.init.free(&mut r2.counters);
+            }
             _ => panic!("Unexpected message"),
         }
     }
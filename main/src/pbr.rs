@@ -0,0 +1,237 @@
+use super::*;
+use apis_pbr::{PbrErr, PbrSyncHandler};
+use fwd::ip_mask_decode;
+use fwd::pbr::{PbrRule, PbrRulesMsg};
+use fwd::ZERO_IP;
+use l3_ipv4_pbr::IPv4Pbr;
+use perf::Perf;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+// The policy based routing rules, sorted by their sequence numbers. The forwarding threads
+// share the active list of rules, a change to the rules makes a new list and sends it to
+// the threads. Rules change rarely and there are few of them compared to routes, so unlike
+// the route tables there is no retired list kept around to be reused
+pub struct PbrCtx {
+    active: Arc<Vec<PbrRule>>,
+}
+
+impl PbrCtx {
+    pub fn new() -> PbrCtx {
+        PbrCtx {
+            active: Arc::new(Vec::new()),
+        }
+    }
+}
+
+pub struct PbrApis {
+    r2: Arc<Mutex<R2>>,
+}
+
+impl PbrApis {
+    pub fn new(r2: Arc<Mutex<R2>>) -> PbrApis {
+        PbrApis { r2 }
+    }
+}
+
+pub fn create_pbr_nodes(r2: &mut R2, g: &mut Graph<R2Msg>) {
+    let input = r2.features.arc(names::IP4_INPUT).clone();
+    let pbr_node = IPv4Pbr::new(r2.pbr.active.clone(), input, &mut r2.counters);
+    let init = GnodeInit {
+        name: pbr_node.name(),
+        next_names: pbr_node.next_names(),
+        cntrs: GnodeCntrs::new(&pbr_node.name(), &mut r2.counters),
+        perf: Perf::new(&pbr_node.name(), &mut r2.counters),
+        depth: VEC_SIZE,
+    };
+    g.add(Box::new(pbr_node), init);
+}
+
+// Change the rules and publish the new rules to the forwarding threads
+fn update_rules(r2: &mut R2, change: impl FnOnce(&mut Vec<PbrRule>)) {
+    let mut rules = (*r2.pbr.active).clone();
+    change(&mut rules);
+    let rules = Arc::new(rules);
+    r2.pbr.active = rules.clone();
+    r2.broadcast(R2Msg::PbrRules(PbrRulesMsg::new(rules)));
+}
+
+// The rules matching on an interface go away with the interface, so that an interface that
+// reuses the ifindex later does not inherit them
+pub fn del_rules_ifindex(r2: &mut R2, ifindex: usize) {
+    if r2.pbr.active.iter().any(|r| r.ifindex == Some(ifindex)) {
+        update_rules(r2, |rules| rules.retain(|r| r.ifindex != Some(ifindex)));
+    }
+}
+
+fn prefix_decode(prefix: &str, what: &str) -> Result<(Ipv4Addr, u32), String> {
+    if prefix.is_empty() {
+        return Ok((ZERO_IP, 0));
+    }
+    match ip_mask_decode(prefix) {
+        Some((ip, masklen)) if masklen <= 32 => Ok((ip, masklen)),
+        _ => Err(format!("Bad {} prefix {}", what, prefix)),
+    }
+}
+
+// A port or a range of ports like 8000-8080
+fn ports_decode(ports: &str, what: &str) -> Result<Option<(u16, u16)>, String> {
+    if ports.is_empty() {
+        return Ok(None);
+    }
+    let range: Vec<&str> = ports.split('-').collect();
+    let lo = range[0].parse::<u16>();
+    let hi = range[range.len() - 1].parse::<u16>();
+    match (range.len(), lo, hi) {
+        (1, Ok(lo), Ok(hi)) | (2, Ok(lo), Ok(hi)) if lo <= hi => Ok(Some((lo, hi))),
+        _ => Err(format!("Bad {} ports {}", what, ports)),
+    }
+}
+
+// A value that is -1 for any, or in the range 0 to max
+fn any_decode(val: i32, max: u8, what: &str) -> Result<Option<u8>, String> {
+    match val {
+        -1 => Ok(None),
+        v if v >= 0 && v <= max as i32 => Ok(Some(v as u8)),
+        _ => Err(format!("Bad {} {}", what, val)),
+    }
+}
+
+// Decode a rule from the APIs, the VRF of the rule has to exist
+fn rule_decode(r2: &R2, rule: &apis_pbr::PbrRule) -> Result<PbrRule, String> {
+    let seq = rule.seq.unwrap_or_default();
+    if seq < 0 {
+        return Err(format!("Bad sequence number {}", seq));
+    }
+    let src = prefix_decode(rule.src.as_deref().unwrap_or_default(), "src")?;
+    let dst = prefix_decode(rule.dst.as_deref().unwrap_or_default(), "dst")?;
+    let proto = any_decode(rule.proto.unwrap_or(-1), u8::MAX, "protocol")?;
+    let sport = ports_decode(rule.sport.as_deref().unwrap_or_default(), "src")?;
+    let dport = ports_decode(rule.dport.as_deref().unwrap_or_default(), "dst")?;
+    // The DSCP is the upper six bits of the TOS byte
+    let dscp = any_decode(rule.dscp.unwrap_or(-1), 63, "DSCP")?;
+    let ifname = rule.ifname.as_deref().unwrap_or_default();
+    let ifindex = if ifname.is_empty() {
+        None
+    } else {
+        match r2.ifd.get(ifname) {
+            Some(intf) => Some(intf.ifindex),
+            None => return Err(format!("Cannot find interface {}", ifname)),
+        }
+    };
+    let nhop = rule.nhop.as_deref().unwrap_or_default();
+    let nhop = if nhop.is_empty() {
+        None
+    } else {
+        match Ipv4Addr::from_str(nhop) {
+            Ok(ip) if ip != ZERO_IP => Some(ip),
+            _ => return Err(format!("Bad nexthop {}", nhop)),
+        }
+    };
+    let vrf = rule.vrf.as_deref().unwrap_or_default();
+    if nhop.is_none() && vrf.is_empty() {
+        return Err("Rule needs a nexthop or a VRF".to_string());
+    }
+    let vrf = if vrf.is_empty() {
+        None
+    } else {
+        match r2.ipv4.vrf_id(vrf) {
+            Some(vrf) => Some(vrf),
+            None => return Err(format!("Cannot find VRF {}", vrf)),
+        }
+    };
+    Ok(PbrRule {
+        seq: seq as u32,
+        src,
+        dst,
+        proto,
+        sport,
+        dport,
+        dscp,
+        ifindex,
+        vrf,
+        nhop,
+    })
+}
+
+fn any_show<T: ToString>(val: Option<T>) -> String {
+    val.map_or("any".to_string(), |v| v.to_string())
+}
+
+fn rule_show(r2: &R2, rule: &PbrRule) -> String {
+    let prefix = |(ip, masklen): (Ipv4Addr, u32)| {
+        if masklen == 0 {
+            "any".to_string()
+        } else {
+            format!("{}/{}", ip, masklen)
+        }
+    };
+    let ports = |ports: Option<(u16, u16)>| match ports {
+        Some((lo, hi)) if lo == hi => lo.to_string(),
+        Some((lo, hi)) => format!("{}-{}", lo, hi),
+        None => "any".to_string(),
+    };
+    let ifname = rule
+        .ifindex
+        .map(|i| r2.ifd.get_name(i).map_or("?", |n| n.as_str()));
+    let mut action = Vec::new();
+    if let Some(nhop) = rule.nhop {
+        action.push(format!("nhop {}", nhop));
+    }
+    if let Some(vrf) = rule.vrf {
+        action.push(format!("vrf {}", r2.ipv4.vrf_name(vrf)));
+    }
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+        rule.seq,
+        prefix(rule.src),
+        prefix(rule.dst),
+        any_show(rule.proto),
+        ports(rule.sport),
+        ports(rule.dport),
+        any_show(rule.dscp),
+        any_show(ifname),
+        action.join(" ")
+    )
+}
+
+impl PbrSyncHandler for PbrApis {
+    // A rule with the same sequence number as an existing rule replaces it
+    fn handle_add_rule(&self, rule: apis_pbr::PbrRule) -> thrift::Result<()> {
+        let mut r2 = self.r2.lock().unwrap();
+        let rule = match rule_decode(&r2, &rule) {
+            Ok(rule) => rule,
+            Err(why) => return Err(From::from(PbrErr::new(why))),
+        };
+        update_rules(&mut r2, |rules| {
+            rules.retain(|r| r.seq != rule.seq);
+            let pos = rules.partition_point(|r| r.seq < rule.seq);
+            rules.insert(pos, rule);
+        });
+        if let Err(why) = r2.sync() {
+            return Err(From::from(PbrErr::new(why)));
+        }
+        Ok(())
+    }
+
+    fn handle_del_rule(&self, seq: i32) -> thrift::Result<()> {
+        let mut r2 = self.r2.lock().unwrap();
+        if !r2.pbr.active.iter().any(|r| r.seq as i32 == seq) {
+            return Err(From::from(PbrErr::new(format!("Cannot find rule {}", seq))));
+        }
+        update_rules(&mut r2, |rules| rules.retain(|r| r.seq as i32 != seq));
+        if let Err(why) = r2.sync() {
+            return Err(From::from(PbrErr::new(why)));
+        }
+        Ok(())
+    }
+
+    fn handle_show(&self) -> thrift::Result<String> {
+        let r2 = self.r2.lock().unwrap();
+        let mut s = "Seq\tSrc\tDst\tProto\tSport\tDport\tDSCP\tIfname\tAction\n".to_string();
+        for rule in r2.pbr.active.iter() {
+            s.push_str(&rule_show(&r2, rule));
+        }
+        Ok(s)
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+
+// Load the plugin libraries from the plugin directory in the config file, and register the
+// features the plugins want on the feature arcs. A plugin that fails to load or register is
+// fatal, just like a bad config - we dont want R2 running with some features silently missing
+pub fn load_plugins(r2: &mut R2) {
+    let dir = match &r2.cfg.plugins {
+        Some(dir) => dir.clone(),
+        None => return,
+    };
+    let (plugins, errors) = plugin::load_dir(&dir);
+    if !errors.is_empty() {
+        panic!("Plugin load failed: {}", errors.join(", "));
+    }
+    for p in plugins.iter() {
+        for (arc, feature) in p.features() {
+            if !r2.features.register(&arc, &feature) {
+                panic!(
+                    "Plugin {} cannot register feature {} on arc {}",
+                    p.name(),
+                    feature,
+                    arc
+                );
+            }
+        }
+    }
+    r2.plugins = plugins;
+}
+
+pub fn create_plugin_nodes(r2: &mut R2, g: &mut Graph<R2Msg>) {
+    for p in r2.plugins.iter_mut() {
+        for node in p.nodes(&mut r2.counters, r2.features.arcs()) {
+            g.add(node.node, node.init);
+        }
+    }
+}
+
+pub fn register_plugin_apis(r2: &mut R2, svr: &mut ApiSvr) {
+    for p in r2.plugins.iter_mut() {
+        for (name, processor) in p.apis() {
+            svr.register(&name, processor);
+        }
+    }
+}
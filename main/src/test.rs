@@ -1,14 +1,20 @@
-use super::ipv4::add_route;
+use super::ipv4::{add_route, update_routes, RouteOp};
 use super::*;
+use apis_interface::InterfaceSyncHandler;
+use apis_route::{RouteEntry, RouteSyncHandler};
+use fwd::intf::Interface;
+use fwd::neigh::NeighState;
 use fwd::EthMacAddMsg;
 use fwd::EthMacRaw;
+use fwd::DEFAULT_VRF;
 use graph::Driver;
 use packet::{BoxPkt, PacketPool, PktsHeap};
 use socket::RawSock;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
 use std::process::Command;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
+use std::time::Duration;
 
 const ETH_HDR_IPV4: [u8; 14] = [
     0xaa, 0xbb, 0xde, 0xad, 0xbe, 0xef, 0, 0, 0, 0, 0, 0, 0x08, 0x00,
@@ -204,7 +210,13 @@ fn packet_send(done: Arc<AtomicUsize>) -> std::thread::JoinHandle<()> {
             while done.load(Ordering::Relaxed) == 0 {
                 let mut pkt = pool.pkt(0).unwrap();
                 assert!(pkt.append(&mut *pool, &ETH_HDR_IPV4));
-                let data: Vec<u8> = vec![0; DATA_LEN - 14];
+                let mut data: Vec<u8> = vec![0; DATA_LEN - 14];
+                // A minimal IPv4 header with a TTL that survives forwarding
+                data[0] = 0x45;
+                data[2..4].copy_from_slice(&((DATA_LEN - 14) as u16).to_be_bytes());
+                data[8] = fwd::IP_DEFAULT_TTL;
+                let csum = fwd::ip_checksum(&data[0..20]);
+                data[10..12].copy_from_slice(&csum.to_be_bytes());
                 assert!(pkt.append(&mut *pool, &data));
                 assert_eq!(raw.sendmsg(&mut *pool, pkt), DATA_LEN);
                 while let Ok(p) = queue.pop() {
@@ -250,10 +262,12 @@ fn create_interfaces(r2: &mut R2) {
     }
     let mac_add = EthMacAddMsg {
         ifindex: INPUT_IFINDEX,
-        ip: Ipv4Addr::new(0, 0, 0, 0),
+        ip: IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
         mac: EthMacRaw {
             bytes: Arc::new(mac_out.clone()),
         },
+        state: NeighState::Reachable,
+        advert: false,
     };
     r2.broadcast(R2Msg::EthMacAdd(mac_add));
 
@@ -263,10 +277,12 @@ fn create_interfaces(r2: &mut R2) {
     }
     let mac_add = EthMacAddMsg {
         ifindex: OUTPUT_IFINDEX,
-        ip: Ipv4Addr::new(0, 0, 0, 0),
+        ip: IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
         mac: EthMacRaw {
             bytes: Arc::new(mac_in),
         },
+        state: NeighState::Reachable,
+        advert: false,
     };
     r2.broadcast(R2Msg::EthMacAdd(mac_add));
 }
@@ -275,6 +291,8 @@ fn launch_test_threads(r2: &mut R2, done: Arc<AtomicUsize>, mut g: Graph<R2Msg>)
     let (sender, receiver) = channel();
     r2.threads[0].ctrl2fwd = Some(sender);
     let efd = r2.threads[0].efd.clone();
+    let acked = r2.threads[0].acked.clone();
+    let fwd2ctrl = r2.fwd2ctrl.clone();
     let mut epoll = Epoll::new(efd, MAX_FDS, -1, Box::new(R2Epoll {})).unwrap();
 
     let d = done.clone();
@@ -283,7 +301,7 @@ fn launch_test_threads(r2: &mut R2, done: Arc<AtomicUsize>, mut g: Graph<R2Msg>)
         .spawn(move || loop {
             while d.load(Ordering::Relaxed) == 0 {
                 g.run();
-                ctrl2fwd_messages(0, &mut epoll, &receiver, &mut g);
+                ctrl2fwd_messages(0, &mut epoll, &receiver, &acked, &fwd2ctrl, &mut g);
             }
         })
         .unwrap();
@@ -304,6 +322,7 @@ fn integ_test() {
             ncores: 2,
             mem: 1,
         },
+        plugins: None,
     };
 
     let (sender, _receiver) = channel();
@@ -331,6 +350,7 @@ fn integ_test() {
     // Add a default route
     add_route(
         &mut r2,
+        DEFAULT_VRF,
         Ipv4Addr::new(0, 0, 0, 0),
         0,
         Ipv4Addr::new(0, 0, 0, 0),
@@ -345,3 +365,391 @@ fn integ_test() {
 
     delete_veth();
 }
+
+// An R2 with one forwarding thread and without dpdk, for the tests that run the control
+// plane against fake_thread() below instead of a real graph
+fn test_r2(test: &str) -> R2 {
+    let cfg = R2Cfg {
+        nthreads: 1,
+        ..Default::default()
+    };
+    let (sender, _receiver) = channel();
+    R2::new(test, &format!("{}_logs", test), 32, 1000, sender, cfg)
+}
+
+// Stand in for forwarding thread 0, it hands each message over to the test and acks it,
+// delay after getting it. So the messages are with the test by the time R2::sync() returns
+fn fake_thread(r2: &mut R2, delay: Duration) -> Receiver<R2Msg> {
+    let (sender, receiver) = channel();
+    let (to_test, from_thread) = channel();
+    r2.threads[0].ctrl2fwd = Some(sender);
+    let acked = r2.threads[0].acked.clone();
+    thread::spawn(move || {
+        for msg in receiver.iter() {
+            thread::sleep(delay);
+            let _ = to_test.send(msg);
+            acked.fetch_add(1, Ordering::Release);
+        }
+    });
+    from_thread
+}
+
+#[test]
+fn sync_waits_for_threads() {
+    let mut r2 = test_r2("main_sync_wait");
+    let delay = Duration::from_millis(200);
+    let from_thread = fake_thread(&mut r2, delay);
+    let r2 = Arc::new(Mutex::new(r2));
+    let apis = RouteApis::new(r2.clone());
+
+    // The reply comes only after the thread acked the table update
+    let start = std::time::Instant::now();
+    let ret = apis.handle_add_route(
+        "10.1.1.0/24".to_string(),
+        String::new(),
+        String::new(),
+        "blackhole".to_string(),
+        String::new(),
+    );
+    assert!(ret.is_ok());
+    assert!(start.elapsed() >= delay);
+    let r2 = r2.lock().unwrap();
+    assert_eq!(
+        r2.threads[0].acked.load(Ordering::Acquire),
+        r2.threads[0].epoch
+    );
+    match from_thread.try_recv() {
+        Ok(R2Msg::IPv4TableAdd(_)) => {}
+        _ => panic!("Expected a table update"),
+    }
+}
+
+#[test]
+fn sync_timeout() {
+    let mut r2 = test_r2("main_sync_timeout");
+    // A thread that never gets around to its messages
+    let (sender, _receiver) = channel();
+    r2.threads[0].ctrl2fwd = Some(sender);
+    let r2 = Arc::new(Mutex::new(r2));
+    let apis = RouteApis::new(r2.clone());
+
+    let start = std::time::Instant::now();
+    let ret = apis.handle_add_route(
+        "10.1.1.0/24".to_string(),
+        String::new(),
+        String::new(),
+        "blackhole".to_string(),
+        String::new(),
+    );
+    let elapsed = start.elapsed();
+    assert!(elapsed >= SYNC_TIMEOUT && elapsed < 2 * SYNC_TIMEOUT);
+    let why = format!("{:?}", ret.unwrap_err());
+    assert!(why.contains("did not apply config"), "{}", why);
+    // The config is still there, the threads will pick it up when they catch up
+    assert_eq!(r2.lock().unwrap().threads[0].epoch, 1);
+}
+
+fn has_route(r2: &R2, vrf: usize, ip: [u8; 4], masklen: u32) -> bool {
+    let ip = Ipv4Addr::from(ip);
+    r2.ipv4.active(vrf).exact_match(ip, masklen).is_some()
+}
+
+#[test]
+fn update_routes_reuse() {
+    let mut r2 = test_r2("main_routes_reuse");
+    let nhop = Ipv4Addr::new(1, 1, 1, 1);
+    let op = |ip: [u8; 4], add| RouteOp::new(Ipv4Addr::from(ip), 24, nhop, 1, add);
+
+    update_routes(&mut r2, DEFAULT_VRF, vec![op([10, 1, 1, 0], true)]);
+    let first = Arc::as_ptr(r2.ipv4.active(DEFAULT_VRF));
+    update_routes(&mut r2, DEFAULT_VRF, vec![op([10, 1, 2, 0], true)]);
+    let second = Arc::as_ptr(r2.ipv4.active(DEFAULT_VRF));
+    assert_ne!(second, first);
+
+    // Nobody holds the first table anymore, so it comes back as the next table, with the
+    // changes it missed replayed on it
+    update_routes(
+        &mut r2,
+        DEFAULT_VRF,
+        vec![op([10, 1, 3, 0], true), op([10, 1, 1, 0], false)],
+    );
+    assert_eq!(Arc::as_ptr(r2.ipv4.active(DEFAULT_VRF)), first);
+    assert!(!has_route(&r2, DEFAULT_VRF, [10, 1, 1, 0], 24));
+    assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 2, 0], 24));
+    assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 3, 0], 24));
+
+    // And again the other way around
+    update_routes(&mut r2, DEFAULT_VRF, vec![op([10, 1, 4, 0], true)]);
+    assert_eq!(Arc::as_ptr(r2.ipv4.active(DEFAULT_VRF)), second);
+    assert!(!has_route(&r2, DEFAULT_VRF, [10, 1, 1, 0], 24));
+    for ip in [[10, 1, 2, 0], [10, 1, 3, 0], [10, 1, 4, 0]].iter() {
+        assert!(has_route(&r2, DEFAULT_VRF, *ip, 24));
+    }
+}
+
+#[test]
+fn update_routes_reader() {
+    let mut r2 = test_r2("main_routes_reader");
+    let nhop = Ipv4Addr::new(1, 1, 1, 1);
+    let op = |ip: [u8; 4], add| RouteOp::new(Ipv4Addr::from(ip), 24, nhop, 1, add);
+
+    update_routes(&mut r2, DEFAULT_VRF, vec![op([10, 1, 1, 0], true)]);
+    // Like a forwarding thread that is yet to pick up the next tables
+    let reader = r2.ipv4.active(DEFAULT_VRF).clone();
+    update_routes(&mut r2, DEFAULT_VRF, vec![op([10, 1, 2, 0], true)]);
+    update_routes(&mut r2, DEFAULT_VRF, vec![op([10, 1, 1, 0], false)]);
+
+    // The table in use is not touched, the next table is a copy of the active one
+    assert_ne!(
+        Arc::as_ptr(r2.ipv4.active(DEFAULT_VRF)),
+        Arc::as_ptr(&reader)
+    );
+    assert!(!has_route(&r2, DEFAULT_VRF, [10, 1, 1, 0], 24));
+    assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 2, 0], 24));
+    let ip = Ipv4Addr::new(10, 1, 1, 0);
+    assert!(reader.exact_match(ip, 24).is_some());
+    let ip = Ipv4Addr::new(10, 1, 2, 0);
+    assert!(reader.exact_match(ip, 24).is_none());
+    // And the control plane let go of it, it goes away with the reader
+    assert_eq!(Arc::strong_count(&reader), 1);
+}
+
+#[test]
+fn update_routes_done() {
+    let mut r2 = test_r2("main_routes_done");
+    let ip = Ipv4Addr::new(10, 1, 1, 0);
+    let nhop1 = Ipv4Addr::new(1, 1, 1, 1);
+    let nhop2 = Ipv4Addr::new(2, 2, 2, 2);
+
+    // A path the route already has, and deleting a path or a route that is not there
+    let ops = vec![
+        RouteOp::new(ip, 24, nhop1, 1, true),
+        RouteOp::new(ip, 24, nhop1, 1, true),
+        RouteOp::new(ip, 24, nhop2, 1, true),
+        RouteOp::new(ip, 24, nhop2, 2, false),
+        RouteOp::new(Ipv4Addr::new(10, 1, 2, 0), 24, nhop1, 1, false),
+    ];
+    let done = update_routes(&mut r2, DEFAULT_VRF, ops);
+    assert_eq!(done, vec![true, false, true, false, false]);
+    assert_eq!(r2.ipv4.active(DEFAULT_VRF).paths(ip, 24).len(), 2);
+
+    let ops = vec![
+        RouteOp::new(ip, 24, nhop1, 1, false),
+        RouteOp::new(ip, 24, nhop1, 1, false),
+        RouteOp::new(ip, 24, nhop2, 1, true),
+    ];
+    let done = update_routes(&mut r2, DEFAULT_VRF, ops);
+    assert_eq!(done, vec![true, false, false]);
+    assert_eq!(r2.ipv4.active(DEFAULT_VRF).paths(ip, 24).len(), 1);
+}
+
+fn route(ip_and_mask: &str, nhop: &str, ifname: &str, kind: &str) -> RouteEntry {
+    RouteEntry::new(
+        ip_and_mask.to_string(),
+        nhop.to_string(),
+        ifname.to_string(),
+        kind.to_string(),
+    )
+}
+
+// Whether the route has a recursive nexthop, resolved or not
+fn has_via(r2: &R2, ip: [u8; 4], masklen: u32) -> bool {
+    let ip = Ipv4Addr::from(ip);
+    let table = r2.ipv4.active(DEFAULT_VRF);
+    table.recursive().any(|(i, m, _)| i == ip && m == masklen)
+}
+
+// The number of route table updates the forwarding thread got
+fn table_updates(from_thread: &Receiver<R2Msg>) -> usize {
+    from_thread
+        .try_iter()
+        .filter(|msg| matches!(msg, R2Msg::IPv4TableAdd(_)))
+        .count()
+}
+
+#[test]
+fn routes_batch() {
+    let mut r2 = test_r2("main_routes_batch");
+    let from_thread = fake_thread(&mut r2, Duration::from_millis(0));
+    let r2 = Arc::new(Mutex::new(r2));
+    let apis = RouteApis::new(r2.clone());
+
+    // The good routes go in with one table update, the errors line up with the bad ones
+    let routes = vec![
+        route("10.1.1.0/24", "1.1.1.1", "", ""),
+        route("10.1.2.0/33", "1.1.1.1", "", ""),
+        route("10.1.3.0/24", "", "", "blackhole"),
+        route("10.1.4.0/24", "1.1.1.1", "veth9", ""),
+        route("10.1.5.0/24", "", "", "bogus"),
+        route("10.1.6.0/24", "1.1.1.1", "", ""),
+    ];
+    let errors = apis.handle_add_routes(routes, String::new()).unwrap();
+    assert_eq!(
+        errors,
+        vec![
+            "",
+            "Unable to decode IP/MASK",
+            "",
+            "Cannot find interface veth9",
+            "Unknown route kind bogus",
+            "",
+        ]
+    );
+    assert_eq!(table_updates(&from_thread), 1);
+    {
+        let r2 = r2.lock().unwrap();
+        assert!(has_via(&r2, [10, 1, 1, 0], 24));
+        assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 3, 0], 24));
+        assert!(has_via(&r2, [10, 1, 6, 0], 24));
+    }
+
+    // Deleting what is not there is an error too
+    let routes = vec![
+        route("10.1.1.0/24", "1.1.1.1", "", ""),
+        route("10.1.2.0/24", "1.1.1.1", "", ""),
+        route("10.1.3.0/24", "", "", "blackhole"),
+        route("10.1.6.0/24", "2.2.2.2", "", ""),
+        route("bad", "", "", ""),
+    ];
+    let errors = apis.handle_del_routes(routes, String::new()).unwrap();
+    assert_eq!(
+        errors,
+        vec![
+            "",
+            "Route not found",
+            "",
+            "Route not found",
+            "Unable to decode IP/MASK",
+        ]
+    );
+    assert_eq!(table_updates(&from_thread), 1);
+    let r2 = r2.lock().unwrap();
+    assert!(!has_via(&r2, [10, 1, 1, 0], 24));
+    assert!(!has_route(&r2, DEFAULT_VRF, [10, 1, 3, 0], 24));
+    assert!(has_via(&r2, [10, 1, 6, 0], 24));
+}
+
+// An interface the control plane knows of, without the graph nodes of a real interface
+fn test_interface(r2: &mut R2, ifname: &str, ifindex: usize) {
+    let intf = Interface::new(ifname, ifindex, vec![0; 6], 0);
+    r2.ifd.add(ifname, ifindex, Arc::new(intf), 0, None);
+}
+
+fn add_route_api(apis: &RouteApis, ip_mask: &str, nhop: &str, ifname: &str, vrf: &str) -> String {
+    match apis.handle_add_route(
+        ip_mask.to_string(),
+        nhop.to_string(),
+        ifname.to_string(),
+        String::new(),
+        vrf.to_string(),
+    ) {
+        Ok(()) => String::new(),
+        Err(why) => format!("{:?}", why),
+    }
+}
+
+#[test]
+fn vrf_set() {
+    let mut r2 = test_r2("main_vrf_set");
+    test_interface(&mut r2, "eth_vrf", 5);
+    let r2 = Arc::new(Mutex::new(r2));
+    let intf_apis = InterfaceApis::new(r2.clone());
+    let route_apis = RouteApis::new(r2.clone());
+    let set_vrf = |vrf: &str| {
+        let ret = intf_apis.handle_set_vrf("eth_vrf".to_string(), vrf.to_string());
+        assert!(ret.is_ok());
+    };
+
+    let ret = intf_apis.handle_add_ip("eth_vrf".to_string(), "10.1.1.1/24".to_string());
+    assert!(ret.is_ok());
+    let ret = add_route_api(&route_apis, "20.1.1.0/24", "10.1.1.2", "eth_vrf", "red");
+    assert!(ret.is_empty(), "{}", ret);
+    {
+        let r2 = r2.lock().unwrap();
+        assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 1, 0], 24));
+        assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 1, 1], 32));
+    }
+
+    // The connected route and the receive entry move with the interface
+    set_vrf("red");
+    {
+        let r2 = r2.lock().unwrap();
+        let red = r2.ipv4.vrf_id("red").unwrap();
+        assert_eq!(r2.ifd.get("eth_vrf").unwrap().vrf, red);
+        assert!(!has_route(&r2, DEFAULT_VRF, [10, 1, 1, 0], 24));
+        assert!(!has_route(&r2, DEFAULT_VRF, [10, 1, 1, 1], 32));
+        assert!(has_route(&r2, red, [10, 1, 1, 0], 24));
+        assert!(has_route(&r2, red, [10, 1, 1, 1], 32));
+    }
+
+    // Other routes through the interface stay where they were added
+    set_vrf("");
+    let r2 = r2.lock().unwrap();
+    let red = r2.ipv4.vrf_id("red").unwrap();
+    assert_eq!(r2.ifd.get("eth_vrf").unwrap().vrf, DEFAULT_VRF);
+    assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 1, 0], 24));
+    assert!(has_route(&r2, DEFAULT_VRF, [10, 1, 1, 1], 32));
+    assert!(!has_route(&r2, red, [10, 1, 1, 0], 24));
+    assert!(!has_route(&r2, red, [10, 1, 1, 1], 32));
+    assert!(has_route(&r2, red, [20, 1, 1, 0], 24));
+}
+
+#[test]
+fn vrf_unknown() {
+    let r2 = Arc::new(Mutex::new(test_r2("main_vrf_unknown")));
+    let apis = RouteApis::new(r2);
+
+    let ret = apis.handle_del_route(
+        "10.1.1.0/24".to_string(),
+        "1.1.1.1".to_string(),
+        String::new(),
+        String::new(),
+        "blue".to_string(),
+    );
+    assert!(format!("{:?}", ret.unwrap_err()).contains("Cannot find VRF blue"));
+    let routes = vec![route("10.1.1.0/24", "1.1.1.1", "", "")];
+    let ret = apis.handle_del_routes(routes, "blue".to_string());
+    assert!(format!("{:?}", ret.unwrap_err()).contains("Cannot find VRF blue"));
+    for prefix in ["all", "10.1.1.1"].iter() {
+        let filename = "/tmp/r2_vrf_unknown".to_string();
+        let ret = apis.handle_show(prefix.to_string(), filename, "blue".to_string());
+        assert!(format!("{:?}", ret.unwrap_err()).contains("Cannot find VRF blue"));
+    }
+}
+
+#[test]
+fn vrf_add_failed() {
+    let r2 = Arc::new(Mutex::new(test_r2("main_vrf_add_failed")));
+    let apis = RouteApis::new(r2.clone());
+
+    // Routes that dont go in dont create the VRF
+    let ret = add_route_api(&apis, "10.1.1.0/33", "1.1.1.1", "", "green");
+    assert!(ret.contains("Unable to decode IP/MASK"), "{}", ret);
+    let ret = add_route_api(&apis, "10.1.1.0/24", "1.1.1.1", "veth9", "green");
+    assert!(ret.contains("Cannot find interface veth9"), "{}", ret);
+    let ret = add_route_api(&apis, "2001:db8::/64", "fe80::1", "", "green");
+    assert!(
+        ret.contains("IPv6 routes can only be in the default VRF"),
+        "{}",
+        ret
+    );
+    let routes = vec![
+        route("bad", "", "", ""),
+        route("2001:db8::/64", "fe80::1", "", ""),
+    ];
+    let errors = apis.handle_add_routes(routes, "green".to_string()).unwrap();
+    assert!(errors.iter().all(|why| !why.is_empty()));
+    assert_eq!(r2.lock().unwrap().ipv4.vrf_id("green"), None);
+
+    // The first route that goes in does
+    let routes = vec![
+        route("bad", "", "", ""),
+        route("10.1.1.0/24", "1.1.1.1", "", ""),
+    ];
+    let errors = apis.handle_add_routes(routes, "green".to_string()).unwrap();
+    assert_eq!(errors[1], "");
+    let r2 = r2.lock().unwrap();
+    let green = r2.ipv4.vrf_id("green").unwrap();
+    assert_ne!(green, DEFAULT_VRF);
+    assert_eq!(r2.ipv4.active(green).recursive().count(), 1);
+}
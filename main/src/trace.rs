@@ -0,0 +1,86 @@
+use super::*;
+use apis_trace::{TraceErr, TraceSyncHandler};
+use graph::trace::TRACE_THREAD_SHIFT;
+use std::collections::BTreeMap;
+
+pub struct TraceApis {
+    r2: Arc<Mutex<R2>>,
+}
+
+impl TraceApis {
+    pub fn new(r2: Arc<Mutex<R2>>) -> TraceApis {
+        TraceApis { r2 }
+    }
+}
+
+// Display the records of each traced packet together, along with the time (in nanoseconds)
+// each node got the packet, relative to when the packet came into the graph. A packet handed
+// off to another thread (like to the thread owning the output interface) has records in the
+// traces of both threads under the same id, so the records from all the threads are merged
+fn trace_show(threads: &[R2PerThread]) -> String {
+    let mut pkts: BTreeMap<usize, Vec<(u64, usize, String, String)>> = BTreeMap::new();
+    for t in threads.iter() {
+        t.trace.walk(|r| {
+            let rec = (r.time, t.thread, r.node.clone(), r.summary.clone());
+            pkts.entry(r.id).or_default().push(rec);
+        });
+    }
+    let mut s = String::new();
+    for (id, recs) in pkts.iter_mut() {
+        recs.sort_by_key(|r| r.0);
+        let start = recs[0].0;
+        s.push_str(&format!(
+            "Packet {}:{}\n",
+            id >> TRACE_THREAD_SHIFT,
+            id & ((1 << TRACE_THREAD_SHIFT) - 1)
+        ));
+        for (time, thread, node, summary) in recs.iter() {
+            s.push_str(&format!(
+                "  +{:<10} thread {:<3} {:<24} {}\n",
+                time - start,
+                thread,
+                node,
+                summary
+            ));
+        }
+    }
+    s
+}
+
+impl TraceSyncHandler for TraceApis {
+    fn handle_enable(&self, ifname: String, count: i32) -> thrift::Result<()> {
+        let r2 = self.r2.lock().unwrap();
+        let ifindex = if let Some(intf) = r2.ifd.get(&ifname) {
+            intf.ifindex
+        } else {
+            return Err(From::from(TraceErr::new(format!(
+                "Cannot find interface {}",
+                ifname
+            ))));
+        };
+        if count <= 0 {
+            return Err(From::from(TraceErr::new(format!(
+                "Bad packet count {}",
+                count
+            ))));
+        }
+        // We dont know which thread the packets will come in on, so enable on all threads
+        for t in r2.threads.iter() {
+            t.trace.enable(ifindex, count as usize);
+        }
+        Ok(())
+    }
+
+    fn handle_clear(&self) -> thrift::Result<()> {
+        let r2 = self.r2.lock().unwrap();
+        for t in r2.threads.iter() {
+            t.trace.clear();
+        }
+        Ok(())
+    }
+
+    fn handle_show(&self) -> thrift::Result<String> {
+        let r2 = self.r2.lock().unwrap();
+        Ok(trace_show(&r2.threads))
+    }
+}
@@ -1,29 +1,54 @@
 use counters::Counters;
 use fwd::intf::ModifyInterfaceMsg;
 use fwd::ipv4::IPv4TableMsg;
+use fwd::ipv6::IPv6TableMsg;
+use fwd::neigh::NeighState;
+use fwd::pbr::PbrRulesMsg;
 use fwd::EthMacAddMsg;
-use graph::{Gclient, GnodeInit};
+use graph::{Gclient, GnodeInfo, GnodeInit};
 use log::Logger;
+use std::net::IpAddr;
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
 
 pub enum R2Msg {
     GnodeAdd(GnodeAddMsg),
+    GnodeDel(GnodeDelMsg),
+    GnodeFree(GnodeFreeMsg),
     EpollAdd(EpollAddMsg),
+    EpollDel(EpollDelMsg),
     IPv4TableAdd(IPv4TableMsg),
+    IPv6TableAdd(IPv6TableMsg),
+    PbrRules(PbrRulesMsg),
     ModifyInterface(ModifyInterfaceMsg),
     EthMacAdd(EthMacAddMsg),
     ClassAdd(ClassAddMsg),
+    GraphInfo(GraphInfoMsg),
+    FeatureEnable(FeatureEnableMsg),
+    EdgeAdd(EdgeAddMsg),
+    EdgeDel(EdgeDelMsg),
+    NodeQuery(NodeQueryMsg),
 }
 
 impl R2Msg {
     pub fn clone(&self, counters: &mut Counters, logger: Arc<Logger>) -> Self {
         match self {
             R2Msg::GnodeAdd(gnode_add) => R2Msg::GnodeAdd(gnode_add.clone(counters, logger)),
+            R2Msg::GnodeDel(gnode_del) => R2Msg::GnodeDel(gnode_del.clone()),
+            R2Msg::GnodeFree(gnode_free) => R2Msg::GnodeFree(gnode_free.clone(counters, logger)),
             R2Msg::EpollAdd(epoll_add) => R2Msg::EpollAdd(epoll_add.clone()),
+            R2Msg::EpollDel(epoll_del) => R2Msg::EpollDel(epoll_del.clone()),
             R2Msg::IPv4TableAdd(table_add) => R2Msg::IPv4TableAdd(table_add.clone()),
+            R2Msg::IPv6TableAdd(table_add) => R2Msg::IPv6TableAdd(table_add.clone()),
+            R2Msg::PbrRules(rules) => R2Msg::PbrRules(rules.clone()),
             R2Msg::ModifyInterface(mod_intf) => R2Msg::ModifyInterface(mod_intf.clone()),
             R2Msg::EthMacAdd(mac_add) => R2Msg::EthMacAdd(mac_add.clone()),
             R2Msg::ClassAdd(class) => R2Msg::ClassAdd(class.clone()),
+            R2Msg::GraphInfo(info) => R2Msg::GraphInfo(info.clone()),
+            R2Msg::FeatureEnable(feature) => R2Msg::FeatureEnable(feature.clone()),
+            R2Msg::EdgeAdd(edge_add) => R2Msg::EdgeAdd(edge_add.clone()),
+            R2Msg::EdgeDel(edge_del) => R2Msg::EdgeDel(edge_del.clone()),
+            R2Msg::NodeQuery(query) => R2Msg::NodeQuery(query.clone()),
         }
     }
 }
@@ -42,6 +67,74 @@ impl GnodeAddMsg {
     }
 }
 
+// Delete the node with the given name from the graph
+pub struct GnodeDelMsg {
+    pub name: String,
+}
+
+impl Clone for GnodeDelMsg {
+    fn clone(&self) -> GnodeDelMsg {
+        GnodeDelMsg {
+            name: self.name.clone(),
+        }
+    }
+}
+
+// A node removed from a forwarding thread's graph, handed back to control so that
+// the node's counters can be freed by the owner of the counters
+pub struct GnodeFreeMsg {
+    pub node: Box<dyn Gclient<R2Msg>>,
+    pub init: GnodeInit,
+}
+
+impl GnodeFreeMsg {
+    pub fn clone(&self, counters: &mut Counters, logger: Arc<Logger>) -> Self {
+        GnodeFreeMsg {
+            node: self.node.clone(counters, logger),
+            init: self.init.clone(counters),
+        }
+    }
+}
+
+// Add the node 'next' as a next node of the node 'node'. Once the forwarding thread adds the
+// edge to its graph, the message is handed to the node with 'edge' set to the index of the
+// new edge. The 'key' says what the edge is for, and is interpreted by the node - like the
+// ifindex of the interface the edge goes to, for mux nodes
+pub struct EdgeAddMsg {
+    pub node: String,
+    pub next: String,
+    pub key: usize,
+    pub edge: usize,
+}
+
+impl Clone for EdgeAddMsg {
+    fn clone(&self) -> EdgeAddMsg {
+        EdgeAddMsg {
+            node: self.node.clone(),
+            next: self.next.clone(),
+            key: self.key,
+            edge: self.edge,
+        }
+    }
+}
+
+// Tell the node 'node' that whatever its edge for 'key' went to is gone, the node stops
+// using the edge - like a mux node forgetting the edge of a deleted interface, so that an
+// interface reusing the ifindex does not inherit the edge
+pub struct EdgeDelMsg {
+    pub node: String,
+    pub key: usize,
+}
+
+impl Clone for EdgeDelMsg {
+    fn clone(&self) -> EdgeDelMsg {
+        EdgeDelMsg {
+            node: self.node.clone(),
+            key: self.key,
+        }
+    }
+}
+
 pub struct EpollAddMsg {
     pub fd: Option<i32>,
     pub thread: usize,
@@ -56,6 +149,20 @@ impl Clone for EpollAddMsg {
     }
 }
 
+pub struct EpollDelMsg {
+    pub fd: i32,
+    pub thread: usize,
+}
+
+impl Clone for EpollDelMsg {
+    fn clone(&self) -> EpollDelMsg {
+        EpollDelMsg {
+            fd: self.fd,
+            thread: self.thread,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Default)]
 pub struct Sc {
     pub m1: u64,
@@ -91,3 +198,86 @@ impl Clone for ClassAddMsg {
         }
     }
 }
+
+// Ask the forwarding threads for a snapshot of their graphs, the threads reply on the
+// given channel with their thread number and the graph nodes
+pub struct GraphInfoMsg {
+    pub reply: Sender<(usize, Vec<GnodeInfo>)>,
+}
+
+impl Clone for GraphInfoMsg {
+    fn clone(&self) -> GraphInfoMsg {
+        GraphInfoMsg {
+            reply: self.reply.clone(),
+        }
+    }
+}
+
+// Enable or disable a feature on an interface, the message is sent to all the nodes that
+// have a copy of the feature arc
+pub struct FeatureEnableMsg {
+    pub arc: String,
+    pub feature: String,
+    pub ifindex: usize,
+    pub enable: bool,
+    pub nodes: Vec<String>,
+}
+
+impl Clone for FeatureEnableMsg {
+    fn clone(&self) -> FeatureEnableMsg {
+        FeatureEnableMsg {
+            arc: self.arc.clone(),
+            feature: self.feature.clone(),
+            ifindex: self.ifindex,
+            enable: self.enable,
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+// The state of a forwarding node that the control thread can ask for
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NodeQuery {
+    // The IP to MAC mappings of an EthEncap node, with their neighbor states
+    EthMacs,
+    // The HFSC classes of an IfNode
+    Classes,
+}
+
+// The statistics of one HFSC class
+#[derive(Clone, Debug)]
+pub struct ClassStats {
+    pub name: String,
+    pub parent: String,
+    pub is_leaf: bool,
+    pub qlimit: usize,
+    pub qlen: usize,
+    pub qdrops: usize,
+    pub bytes: u64,
+}
+
+pub enum NodeReply {
+    EthMacs(Vec<(IpAddr, Vec<u8>, NeighState)>),
+    Classes(Vec<ClassStats>),
+    // The node does not exist on the thread, or it does not answer this query
+    Unsupported,
+}
+
+// Ask a forwarding node for its state, the node replies on the given channel with the thread
+// number and the reply. Every thread that gets the message replies, if the node is not there
+// or does not understand the query, the forwarding thread replies with NodeReply::Unsupported
+pub struct NodeQueryMsg {
+    pub node: String,
+    pub query: NodeQuery,
+    pub reply: Sender<(usize, NodeReply)>,
+}
+
+impl Clone for NodeQueryMsg {
+    fn clone(&self) -> NodeQueryMsg {
+        NodeQueryMsg {
+            node: self.node.clone(),
+            query: self.query,
+            reply: self.reply.clone(),
+        }
+    }
+}
@@ -6,6 +6,14 @@ pub const L2_ETH_DECAP: &str = "l2_eth_decap:";
 pub const L2_ETH_ENCAP: &str = "l2_eth_encap:";
 pub const L3_IPV4_PARSE: &str = "l3_ipv4_parse";
 pub const L3_IPV4_FWD: &str = "l3_ipv4_fwd";
+pub const L3_IPV4_LOCAL: &str = "l3_ipv4_local";
+pub const L3_IPV4_REASS: &str = "l3_ipv4_reass";
+pub const L3_IPV4_PBR: &str = "l3_ipv4_pbr";
+pub const L3_IPV6_PARSE: &str = "l3_ipv6_parse";
+pub const L3_IPV6_FWD: &str = "l3_ipv6_fwd";
+// Feature arcs
+pub const IP4_INPUT: &str = "ip4-input";
+pub const IP4_OUTPUT: &str = "ip4-output";
 
 pub fn rx_tx(ifindex: usize) -> String {
     let mut name = RX_TX.to_string();
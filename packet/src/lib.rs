@@ -2,13 +2,13 @@ use core::mem::ManuallyDrop;
 use counters::flavors::{Counter, CounterType};
 use counters::Counters;
 use crossbeam_queue::ArrayQueue;
-use fwd::ZERO_IP;
+use fwd::{ZERO_IP, ZERO_IP6};
 use std::alloc::alloc;
 use std::alloc::Layout;
 use std::cmp::min;
 use std::collections::VecDeque;
 use std::mem;
-use std::net::Ipv4Addr;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::ops::{Deref, DerefMut};
 use std::slice::from_raw_parts_mut;
 use std::sync::Arc;
@@ -120,6 +120,10 @@ impl BoxPkt {
         self.in_ifindex = 0;
         self.out_ifindex = 0;
         self.out_l3addr = ZERO_IP;
+        self.out_l3addr6 = ZERO_IP6;
+        self.pbr_vrf = None;
+        self.pbr_nhop = ZERO_IP;
+        self.trace = 0;
         self.particle = Some(ManuallyDrop::new(particle));
     }
 }
@@ -470,6 +474,16 @@ pub struct Packet {
     pub out_ifindex: usize,
     /// The next-hop IPv4 address out of out_ifindex, to use for ARP
     pub out_l3addr: Ipv4Addr,
+    /// The next-hop IPv6 address out of out_ifindex, for IPv6 packets
+    pub out_l3addr6: Ipv6Addr,
+    /// The VRF to route the packet in instead of the VRF of in_ifindex, set by policy
+    /// based routing
+    pub pbr_vrf: Option<usize>,
+    /// The IPv4 nexthop to route the packet towards instead of its destination, set by
+    /// policy based routing. ZERO_IP if the packet is routed to its destination
+    pub pbr_nhop: Ipv4Addr,
+    /// Non zero if the packet is being traced, the value is the packet's trace id
+    pub trace: usize,
 }
 
 impl Default for Packet {
@@ -484,6 +498,10 @@ impl Default for Packet {
             in_ifindex: 0,
             out_ifindex: 0,
             out_l3addr: ZERO_IP,
+            out_l3addr6: ZERO_IP6,
+            pbr_vrf: None,
+            pbr_nhop: ZERO_IP,
+            trace: 0,
         }
     }
 }
@@ -545,6 +563,34 @@ impl Packet {
         true
     }
 
+    /// Append 'len' bytes of another packet, starting at 'offset' in that packet. The data
+    /// can be spread across any number of particles in either packet. Returns false if the
+    /// other packet does not have that many bytes or if particles could not be allocated
+    pub fn append_pkt(
+        &mut self,
+        pool: &mut dyn PacketPool,
+        from: &Packet,
+        offset: usize,
+        len: usize,
+    ) -> bool {
+        let end = offset + len;
+        if end > from.len() {
+            return false;
+        }
+        let mut at = 0;
+        for (data, dlen) in from.slices() {
+            let (start, stop) = (offset.max(at), end.min(at + dlen));
+            if start < stop && !self.append(pool, &data[start - at..stop - at]) {
+                return false;
+            }
+            at += dlen;
+            if at >= end {
+                break;
+            }
+        }
+        true
+    }
+
     pub fn move_tail(&mut self, mv: isize) -> isize {
         let p = self.particle.as_mut().unwrap().last_particle();
         if p.move_tail(mv) != mv {
@@ -556,6 +602,28 @@ impl Packet {
         }
     }
 
+    /// Cut the packet down to 'len' bytes, like when removing the padding at the end of a
+    /// packet. The particles beyond 'len' are left empty, they are freed with the packet.
+    /// Returns false if the packet is not that long to begin with
+    pub fn truncate(&mut self, len: usize) -> bool {
+        if len > self.length {
+            return false;
+        }
+        let mut left = len;
+        let mut p: &mut Particle = self.particle.as_mut().unwrap();
+        loop {
+            let keep = min(p.len(), left);
+            p.tail = p.head + keep;
+            left -= keep;
+            if p.next.is_none() {
+                break;
+            }
+            p = p.next.as_deref_mut().unwrap();
+        }
+        self.length = len;
+        true
+    }
+
     fn move_head(&mut self, mv: isize) -> isize {
         let p = self.particle.as_mut().unwrap();
         if p.move_head(mv) != mv {
@@ -671,6 +739,22 @@ impl Packet {
         }
     }
 
+    pub fn get_l3_mut(&mut self) -> (&mut [u8], usize) {
+        if self.l3_len == 0 {
+            (&mut [], 0)
+        } else {
+            let l3 = self.l3;
+            let l3_len = self.l3_len;
+            let p = self.particle.as_mut().unwrap();
+            let d = p.data_raw_mut(l3);
+            if d.len() < l3_len {
+                (&mut [], 0)
+            } else {
+                (d, l3_len)
+            }
+        }
+    }
+
     pub fn data(&self, offset: usize) -> Option<(&[u8], usize)> {
         let mut l = 0;
         let mut p = self.particle.as_ref().unwrap();
@@ -699,6 +783,20 @@ impl Packet {
         p.data_raw_mut(0)
     }
 
+    /// Hint the cpu to start bringing the packet and its headers into the cache. Nodes
+    /// processing a burst of packets call this on every packet in the burst before they
+    /// start working on the burst, so that by the time the node gets to a packet, it is
+    /// (hopefully) in the cache
+    pub fn prefetch(&self) {
+        prefetch(self as *const Packet as *const u8);
+        if let Some(p) = self.particle.as_ref() {
+            let d = p.data_raw(p.head);
+            if !d.is_empty() {
+                prefetch(d.as_ptr());
+            }
+        }
+    }
+
     pub fn slices(&self) -> Vec<(&[u8], usize)> {
         let mut v = Vec::new();
         let mut p = self.particle.as_ref().unwrap();
@@ -715,5 +813,14 @@ impl Packet {
         v
     }
 }
+#[cfg(target_arch = "x86_64")]
+fn prefetch(addr: *const u8) {
+    use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+    unsafe { _mm_prefetch(addr as *const i8, _MM_HINT_T0) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn prefetch(_addr: *const u8) {}
+
 #[cfg(test)]
 mod test;
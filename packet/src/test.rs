@@ -162,6 +162,32 @@ fn move_tail_test() {
     check_last_part(&mut pkt, 0);
 }
 
+#[test]
+fn truncate_test() {
+    let mut pool = packet_pool("truncate_test");
+    // Cut off the end of the last particle
+    let headroom = 100;
+    let mut pkt = pool.pkt(headroom).unwrap();
+    let bytes = [0; 200];
+    assert!(pkt.append(&mut *pool, &bytes[0..]));
+    assert!(!pkt.truncate(201));
+    assert!(pkt.truncate(150));
+    assert_eq!(pkt.len(), 150);
+    check_last_part(&mut pkt, headroom + 150);
+
+    // Cut across particles, the last particle is left empty
+    let available = 2 * PARTICLE_SZ - headroom;
+    let mut pkt = pool.pkt(headroom).unwrap();
+    let bytes = vec![0; available];
+    assert!(pkt.append(&mut *pool, &bytes[0..]));
+    assert_eq!(nparticles(&pkt), 2);
+    assert!(pkt.truncate(PARTICLE_SZ - headroom - 10));
+    assert_eq!(pkt.len(), PARTICLE_SZ - headroom - 10);
+    assert_eq!(nparticles(&pkt), 2);
+    check_last_part(&mut pkt, 0);
+    assert_eq!(pkt.slices()[0].0.len(), PARTICLE_SZ - headroom - 10);
+}
+
 fn check_first_part(pkt: &BoxPkt, head: usize) {
     let p = &pkt.particle;
     assert_eq!(p.as_ref().unwrap().head, head);
@@ -280,3 +306,32 @@ fn l3_test() {
     assert_eq!(pkt.len(), ip.len());
     assert!(!pkt.set_l3(ip.len() + 1));
 }
+
+#[test]
+fn append_pkt_test() {
+    let mut pool = packet_pool("append_pkt_test");
+    let headroom = 100;
+    let v: Vec<u8> = (0..2 * PARTICLE_SZ).map(|x| (x % 256) as u8).collect();
+    let mut from = pool.pkt(headroom).unwrap();
+    assert!(from.append(&mut *pool, &v[0..]));
+    assert_eq!(nparticles(&from), 3);
+
+    // All of it, across all the particles
+    let mut pkt = pool.pkt(0).unwrap();
+    assert!(pkt.append_pkt(&mut *pool, &from, 0, v.len()));
+    assert_eq!(pkt.len(), v.len());
+    verify_pkt(&mut pkt);
+
+    // A piece from the middle of the first particle to the middle of the last one
+    let mut pkt = pool.pkt(headroom).unwrap();
+    let offset = PARTICLE_SZ - headroom - 10;
+    assert!(pkt.append_pkt(&mut *pool, &from, offset, PARTICLE_SZ + 20));
+    assert_eq!(pkt.len(), PARTICLE_SZ + 20);
+    for i in 0..pkt.len() {
+        assert_eq!(pkt.data(i).unwrap().0[0], v[offset + i]);
+    }
+
+    // Not that much data
+    let mut pkt = pool.pkt(headroom).unwrap();
+    assert!(!pkt.append_pkt(&mut *pool, &from, 10, v.len()));
+}
@@ -9,7 +9,9 @@ impl Perf {
 
     pub fn start(&mut self) {}
 
-    pub fn stop(&mut self) {}
+    pub fn stop(&mut self, _: usize) {}
+
+    pub fn free(&self, _: &mut Counters) {}
 
     pub fn get_count(&self) -> u64 {
         0
@@ -18,4 +20,8 @@ impl Perf {
     pub fn get_avg(&self) -> u64 {
         0
     }
+
+    pub fn get_avg_pkt(&self) -> u64 {
+        0
+    }
 }
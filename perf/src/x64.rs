@@ -5,19 +5,23 @@ use counters::{
 use std::arch::x86_64::_rdtsc;
 
 pub struct Perf {
-    // Three counters:
+    // Five counters:
     // index 0 is the timestamp rdtsc()
     // inex 1 is the hit count
     // index 2 is the sum of rdtsc() delta, the average of which is what usually is of interest
+    // index 3 is the number of packets handled in all the hits
+    // index 4 is the sum of rdtsc() delta of only the hits that handled packets
     cntrs: CounterArray,
 }
 
 impl Perf {
     pub fn new(name: &str, counters: &mut Counters) -> Self {
-        let mut cntrs = CounterArray::new(counters, "perf", CounterType::Info, name, 3);
+        let mut cntrs = CounterArray::new(counters, "perf", CounterType::Info, name, 5);
         cntrs.set(0, 0);
         cntrs.set(1, 0);
         cntrs.set(2, 0);
+        cntrs.set(3, 0);
+        cntrs.set(4, 0);
         Perf { cntrs }
     }
 
@@ -27,14 +31,26 @@ impl Perf {
         }
     }
 
-    pub fn stop(&mut self) {
+    /// Stop the measurement started by start(), 'pkts' is the number of packets handled
+    /// between the start and stop. The hits that handled no packets (like a node that ran
+    /// to find its queue empty) count towards the average per hit, but not towards the
+    /// average per packet - otherwise the cost per packet goes up as the load goes down
+    pub fn stop(&mut self, pkts: usize) {
         unsafe {
             let elapsed = _rdtsc() - self.cntrs.get(0);
             self.cntrs.add(1, 1);
             self.cntrs.add(2, elapsed);
+            if pkts != 0 {
+                self.cntrs.add(3, pkts as u64);
+                self.cntrs.add(4, elapsed);
+            }
         }
     }
 
+    pub fn free(&self, counters: &mut Counters) {
+        self.cntrs.free(counters);
+    }
+
     pub fn get_count(&self) -> u64 {
         self.cntrs.get(1)
     }
@@ -46,6 +62,15 @@ impl Perf {
             0
         }
     }
+
+    /// The average cycles spent per packet, in the hits that handled packets
+    pub fn get_avg_pkt(&self) -> u64 {
+        if self.cntrs.get(3) != 0 {
+            self.cntrs.get(4) / self.cntrs.get(3)
+        } else {
+            0
+        }
+    }
 }
 
 #[cfg(test)]
@@ -61,20 +86,24 @@ mod test {
         for _ in 0..100 {
             _i += 1;
         }
-        p.stop();
+        p.stop(2);
         assert_eq!(p.get_count(), 1);
         let mut total = p.cntrs.get(2);
         assert!(p.get_avg() > 50);
         assert!(p.get_avg() == total);
+        assert!(p.get_avg_pkt() == total / 2);
         p.start();
         let mut _i = 0;
         for _ in 0..100 {
             _i += 1;
         }
-        p.stop();
+        let avg_pkt = p.get_avg_pkt();
+        p.stop(0);
         assert_eq!(p.get_count(), 2);
         total = p.cntrs.get(2);
         assert!(total > p.get_avg());
         assert!(p.get_avg() > 50);
+        // A hit with no packets does not change the cost per packet
+        assert_eq!(p.get_avg_pkt(), avg_pkt);
     }
 }
@@ -0,0 +1,112 @@
+use api::ThreadSafeProcessor;
+use counters::Counters;
+use graph::feature::FeatureArc;
+use msg::GnodeAddMsg;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::path::Path;
+
+/// The symbol every plugin library has to export, its a function of type PluginEntry
+pub const PLUGIN_ENTRY: &str = "r2_plugin";
+
+/// A plugin library exports a function with this signature and the name PLUGIN_ENTRY, like
+/// #[no_mangle] pub fn r2_plugin() -> Box<dyn Plugin>. Note that this is a Rust function and
+/// not an extern "C" function - Rust does not have a stable ABI, so the plugin has to be built
+/// with the same compiler and the same versions of the R2 crates as R2 itself.
+pub type PluginEntry = fn() -> Box<dyn Plugin>;
+
+/// Third party graph nodes and APIs are added to R2 by implementing this trait. R2 calls
+/// the methods in the order they are defined here, all of them at startup before the
+/// forwarding threads are launched
+pub trait Plugin: Send {
+    /// Name of the plugin, for logging purposes
+    fn name(&self) -> String;
+
+    /// The features the plugin wants to register on feature arcs, as (arc, feature node name).
+    /// The features are registered before any of the nodes on the arcs are created
+    fn features(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Create the graph nodes of the plugin. Feature nodes need a copy of their arc, which
+    /// by now has all the features registered. The nodes are added to the graph, and cloned
+    /// to every forwarding thread like every other node
+    fn nodes(
+        &mut self,
+        counters: &mut Counters,
+        arcs: &HashMap<String, FeatureArc>,
+    ) -> Vec<GnodeAddMsg>;
+
+    /// The thrift processors the plugin wants to register on the API server, as (service
+    /// name, processor). The API handlers can share state with the plugin's nodes using
+    /// the same techniques as the rest of R2 - like atomics or messages
+    fn apis(&mut self) -> Vec<(String, ThreadSafeProcessor)> {
+        Vec::new()
+    }
+}
+
+fn dlerror() -> String {
+    unsafe {
+        let err = libc::dlerror();
+        if err.is_null() {
+            "unknown error".to_string()
+        } else {
+            CStr::from_ptr(err).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// Load one plugin library. The library is never unloaded, the forwarding threads run the
+/// plugin's code for the life of R2
+pub fn load(path: &Path) -> Result<Box<dyn Plugin>, String> {
+    let file = match CString::new(path.to_string_lossy().as_bytes()) {
+        Ok(f) => f,
+        Err(_) => return Err(format!("Bad plugin path {}", path.display())),
+    };
+    let entry = CString::new(PLUGIN_ENTRY).unwrap();
+    unsafe {
+        let handle = libc::dlopen(file.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+        if handle.is_null() {
+            return Err(format!("{}: {}", path.display(), dlerror()));
+        }
+        let sym = libc::dlsym(handle, entry.as_ptr());
+        if sym.is_null() {
+            let err = format!("{}: {}", path.display(), dlerror());
+            libc::dlclose(handle);
+            return Err(err);
+        }
+        let entry: PluginEntry = std::mem::transmute(sym);
+        Ok(entry())
+    }
+}
+
+/// Load all the plugin libraries (files ending in .so) in a directory, in the order of
+/// their file names. Returns the plugins loaded and the errors for ones that failed to load
+pub fn load_dir(dir: &str) -> (Vec<Box<dyn Plugin>>, Vec<String>) {
+    let mut plugins = Vec::new();
+    let mut errors = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(why) => {
+            errors.push(format!("{}: {}", dir, why));
+            return (plugins, errors);
+        }
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "so"))
+        .collect();
+    paths.sort();
+    for path in paths.iter() {
+        match load(path) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(why) => errors.push(why),
+        }
+    }
+    (plugins, errors)
+}
+
+#[cfg(test)]
+mod test;
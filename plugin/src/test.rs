@@ -0,0 +1,20 @@
+use super::*;
+
+#[test]
+fn load_errors() {
+    assert!(load(Path::new("/nonexistent/r2_plugin.so")).is_err());
+    // A perfectly good library that is not an R2 plugin
+    let err = match load(Path::new("libc.so.6")) {
+        Ok(_) => panic!("libc is not a plugin"),
+        Err(why) => why,
+    };
+    assert!(err.contains(PLUGIN_ENTRY));
+
+    let (plugins, errors) = load_dir("/nonexistent");
+    assert!(plugins.is_empty());
+    assert_eq!(errors.len(), 1);
+    // No .so files in this directory
+    let (plugins, errors) = load_dir(env!("CARGO_MANIFEST_DIR"));
+    assert!(plugins.is_empty());
+    assert!(errors.is_empty());
+}
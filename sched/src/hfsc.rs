@@ -1,5 +1,5 @@
 use common::time_nsecs;
-use msg::{Curves, Sc};
+use msg::{ClassStats, Curves, Sc};
 use packet::BoxPkt;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, VecDeque};
@@ -272,10 +272,60 @@ impl Hfsc {
         self.pkts_queued
     }
 
+    /// The time in nanoseconds from now when a packet can be dequeued, None if there are no
+    /// packets queued. Its the earlier of the time when the earliest real time class becomes
+    /// eligible, and the time when the earliest link sharing class fits within its upper
+    /// limit curve (and those of its parents). Classes without upper limits always fit, so
+    /// packets in those can go right away
+    pub fn wakeup(&self) -> Option<u64> {
+        if self.pkts_queued == 0 {
+            return None;
+        }
+        let mut time = u64::MAX;
+        if let Some((key, _)) = self.eligible.iter().next() {
+            time = key.time;
+        }
+        let root = &self.classes[self.root];
+        if !root.children.is_empty() && root.cfmin < time {
+            time = root.cfmin;
+        }
+        Some(time.saturating_sub((self.get_time_ns)()))
+    }
+
     pub fn has_classes(&self) -> bool {
         !self.classes[self.root].children.is_empty()
     }
 
+    /// A snapshot of the statistics of all the classes created on the scheduler, sorted by
+    /// class name. The bytes of a parent class include the bytes of all its children
+    pub fn class_stats(&self) -> Vec<ClassStats> {
+        let mut names = vec![String::new(); self.classes.len()];
+        for (name, index) in self.class_names.iter() {
+            if *index != 0 {
+                names[*index] = name.clone();
+            }
+        }
+        let mut stats: Vec<ClassStats> = self
+            .class_names
+            .iter()
+            .filter(|(_, index)| **index > self.root && self.classes[**index].in_use)
+            .map(|(name, index)| {
+                let c = &self.classes[*index];
+                ClassStats {
+                    name: name.clone(),
+                    parent: names[c.parent].clone(),
+                    is_leaf: c.leaf,
+                    qlimit: c.qlimit,
+                    qlen: c.packets.len(),
+                    qdrops: c.qdrops,
+                    bytes: c.f_bytes,
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
     pub fn create_class(
         &mut self,
         name: String,
@@ -355,10 +405,12 @@ impl Hfsc {
         class
     }
 
-    fn get_min_v(&mut self, parent: usize, _time: u64) -> usize {
+    // The active child with the smallest virtual time among the ones that fit within their
+    // upper limit curves at 'time', and down the hierarchy from that child
+    fn get_min_v(&mut self, parent: usize, time: u64) -> usize {
         let p = &self.classes[parent];
         let ch;
-        if let Some((_key, child)) = p.children.iter().next() {
+        if let Some(child) = p.children.values().find(|c| self.classes[**c].f <= time) {
             ch = *child;
         } else {
             return 0;
@@ -371,7 +423,7 @@ impl Hfsc {
         if vtime > p.vmin {
             p.vmin = vtime;
         }
-        let r = self.get_min_v(ch, _time);
+        let r = self.get_min_v(ch, time);
         if r == 0 {
             if leaf {
                 ch
@@ -429,6 +481,10 @@ impl Hfsc {
                     c.vadj += pvmin - c.vtime;
                     c.vtime = pvmin;
                 }
+                if c.u_isc.is_some() {
+                    c.myf = rtsc_y2x(&c.u_run, c.f_bytes);
+                }
+                c.f = c.myf.max(c.cfmin);
                 key = Key {
                     time: c.vtime,
                     index: class,
@@ -437,10 +493,39 @@ impl Hfsc {
             let parent = &mut self.classes[pindex];
             parent.children.insert(key, class);
         }
+        self.update_cfmin(pindex);
         self.update_v(pindex, len, _time, go_passive);
     }
 
-    fn init_v(&mut self, class: usize, _len: usize, active: bool) {
+    // The earliest fit time among the active children of the class
+    fn update_cfmin(&mut self, class: usize) {
+        let cfmin = self.classes[class]
+            .children
+            .values()
+            .map(|c| self.classes[*c].f)
+            .min()
+            .unwrap_or(0);
+        self.classes[class].cfmin = cfmin;
+    }
+
+    // The fit time of the class changes with the fit times of its children, and the change
+    // has to go up to the parents
+    fn update_f(&mut self, class: usize) {
+        let mut class = class;
+        loop {
+            let c = &mut self.classes[class];
+            let f = c.myf.max(c.cfmin);
+            if f == c.f || c.parent == 0 {
+                c.f = f;
+                return;
+            }
+            c.f = f;
+            class = c.parent;
+            self.update_cfmin(class);
+        }
+    }
+
+    fn init_v(&mut self, class: usize, _len: usize, time: u64, active: bool) {
         let go_active;
         let pindex;
         {
@@ -454,6 +539,10 @@ impl Hfsc {
                 c.nactive += 1;
             }
         }
+        if !go_active {
+            // Already active, but a child that just became active can change the fit time
+            self.update_f(class);
+        }
 
         if go_active {
             let mut max_child = 0;
@@ -513,6 +602,11 @@ impl Hfsc {
                 if pnactive == 0 {
                     c.pvperiod += 1;
                 }
+                if let Some(ref u_isc) = c.u_isc {
+                    rtsc_min(&mut c.u_run, u_isc, time, c.f_bytes);
+                    c.myf = rtsc_y2x(&c.u_run, c.f_bytes);
+                }
+                c.f = c.myf.max(c.cfmin);
                 key = Key {
                     time: c.vtime,
                     index: c.index,
@@ -520,7 +614,8 @@ impl Hfsc {
             }
             let parent = &mut self.classes[pindex];
             parent.children.insert(key, class);
-            self.init_v(pindex, _len, go_active);
+            self.update_cfmin(pindex);
+            self.init_v(pindex, _len, time, go_active);
         }
     }
 
@@ -538,10 +633,11 @@ impl Hfsc {
             }
         }
         if qlen == 0 {
-            self.init_v(classid, pkt.len(), true);
+            let time = (self.get_time_ns)();
+            self.init_v(classid, pkt.len(), time, true);
             let c = &mut self.classes[classid];
             if c.r_isc.is_some() {
-                c.init_ed(pkt.len(), (self.get_time_ns)());
+                c.init_ed(pkt.len(), time);
                 let key = Key {
                     time: c.eligible,
                     index: c.index,
@@ -646,6 +742,12 @@ pub struct Class {
     e_run: RuntimeSc,
     d_run: RuntimeSc,
     u_run: RuntimeSc,
+    // The time when the class fits within its upper limit curve, and the time when the class
+    // and its children fit - which is the later of the class's own fit time and the earliest
+    // fit time of its children (cfmin). The class can be picked for link sharing only then
+    myf: u64,
+    f: u64,
+    cfmin: u64,
     nactive: usize,
     children: BTreeMap<Key, usize>,
     packets: VecDeque<BoxPkt>,
@@ -707,6 +809,9 @@ impl Class {
             e_run,
             d_run,
             u_run,
+            myf: 0,
+            f: 0,
+            cfmin: 0,
             nactive: 0,
             children: BTreeMap::new(),
             packets: VecDeque::new(),
@@ -784,6 +889,9 @@ impl Clone for Class {
             e_run: self.e_run,
             d_run: self.d_run,
             u_run: self.u_run,
+            myf: self.myf,
+            f: self.f,
+            cfmin: self.cfmin,
             nactive: self.nactive,
             children: BTreeMap::new(),
             packets: VecDeque::new(),
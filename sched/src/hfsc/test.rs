@@ -424,6 +424,7 @@ fn single_level_realtime() {
     let class4 = hfsc.class_index("class4".to_string()).unwrap();
     assert_eq!(hfsc.classes[1].children.len(), 0);
     assert_eq!(hfsc.eligible.len(), 0);
+    assert_eq!(hfsc.wakeup(), None);
 
     for _ in 0..512 {
         let mut pkt = pool.pkt(0).unwrap();
@@ -445,6 +446,14 @@ fn single_level_realtime() {
     }
     assert_eq!(hfsc.classes[hfsc.root].children.len(), 4);
     assert_eq!(hfsc.eligible.len(), 2);
+    // Link sharing classes are active, so packets can go right away
+    assert_eq!(hfsc.wakeup(), Some(0));
+    let stats = hfsc.class_stats();
+    assert_eq!(stats.len(), 4);
+    assert_eq!(stats[0].name, "class1");
+    assert_eq!(stats[0].parent, "root");
+    assert_eq!(stats[3].name, "class4");
+    assert!(stats.iter().all(|s| s.qlen == 512 && s.bytes == 0));
     assert_eq!(hfsc.classes[class1].children.len(), 0);
     assert_eq!(hfsc.classes[class2].children.len(), 0);
 
@@ -486,4 +495,113 @@ fn single_level_realtime() {
 
     assert_eq!(hfsc.classes[hfsc.root].children.len(), 0);
     assert_eq!(hfsc.eligible.len(), 0);
+    let stats = hfsc.class_stats();
+    assert!(stats
+        .iter()
+        .all(|s| s.qlen == 0 && s.bytes == 512 * DATA.len() as u64));
+}
+
+static CLOCK: AtomicU64 = AtomicU64::new(1_000_000_000);
+
+fn test_clock_ns() -> u64 {
+    CLOCK.load(Ordering::Relaxed)
+}
+
+// A real time class and a link sharing class, both shaped by upper limit curves. Once the
+// classes have sent what their curves allow, nothing can be dequeued till the wakeup time,
+// and the wakeup has to be in the future - otherwise the thread would keep polling
+#[test]
+fn upper_limit_wakeup() {
+    let q = Arc::new(ArrayQueue::new(NUM_PKTS));
+    let mut pool = packet_pool("hfsc_ulimit", q.clone());
+    let sc_1mb = Sc {
+        m1: 0,
+        d: 0,
+        m2: 1_000_000,
+    };
+    let sc_2mb = Sc {
+        m1: 0,
+        d: 0,
+        m2: 2_000_000,
+    };
+    let mut hfsc = Hfsc::new(100_000_000);
+    hfsc.get_time_ns = test_clock_ns;
+
+    hfsc.create_class(
+        "rt".to_string(),
+        "root".to_string(),
+        0,
+        true,
+        Curves {
+            r_sc: Some(sc_1mb),
+            u_sc: Some(sc_1mb),
+            f_sc: sc_1mb,
+        },
+    )
+    .unwrap();
+    let rt = hfsc.class_index("rt".to_string()).unwrap();
+    hfsc.create_class(
+        "ls".to_string(),
+        "root".to_string(),
+        0,
+        true,
+        Curves {
+            r_sc: None,
+            u_sc: Some(sc_2mb),
+            f_sc: sc_2mb,
+        },
+    )
+    .unwrap();
+    let ls = hfsc.class_index("ls".to_string()).unwrap();
+
+    for _ in 0..16 {
+        for class in [rt, ls] {
+            let mut pkt = pool.pkt(0).unwrap();
+            assert!(pkt.append(&mut *pool, &DATA));
+            pkt.out_ifindex = class;
+            assert!(hfsc.enqueue(class, pkt));
+        }
+    }
+    assert_eq!(hfsc.wakeup(), Some(0));
+
+    let mut sent = 0;
+    while let Some(pkt) = hfsc.dequeue() {
+        assert!(pkt.out_ifindex == rt || pkt.out_ifindex == ls);
+        sent += 1;
+    }
+    assert!(sent > 0 && sent < 32);
+    let wakeup = hfsc.wakeup().unwrap();
+    assert!(wakeup > 0);
+    // At 2Mbps, the link sharing class can send its next packet in half a packet time
+    assert!(wakeup <= PKT_TIME);
+
+    // Nothing goes out till the wakeup time, and something does once its there
+    CLOCK.fetch_add(wakeup - 1, Ordering::Relaxed);
+    assert!(hfsc.dequeue().is_none());
+    CLOCK.fetch_add(1, Ordering::Relaxed);
+    assert!(hfsc.dequeue().is_some());
+
+    // The rest of the packets drain as the clock moves from one wakeup to the next, and in
+    // that time the classes do not get more than what their upper limits allow
+    let start = test_clock_ns();
+    let end = test_clock_ns() + 1_000_000_000;
+    let (mut rt_pkts, mut ls_pkts) = (0, 0);
+    while test_clock_ns() < end {
+        while let Some(pkt) = hfsc.dequeue() {
+            if pkt.out_ifindex == rt {
+                rt_pkts += 1;
+            } else {
+                ls_pkts += 1;
+            }
+        }
+        match hfsc.wakeup() {
+            Some(nsecs) => CLOCK.fetch_add(nsecs.max(1), Ordering::Relaxed),
+            None => break,
+        };
+    }
+    assert_eq!(hfsc.pkts_queued(), 0);
+    assert_eq!(rt_pkts + ls_pkts + sent + 1, 32);
+    let elapsed = test_clock_ns() - start;
+    assert!(elapsed >= (ls_pkts as u64).saturating_sub(1) * PKT_TIME / 2);
+    packet_free(q, &mut *pool);
 }
@@ -1,5 +1,6 @@
 use efd::Efd;
 use std::sync::Arc;
+use timerfd::Timerfd;
 
 pub const EPOLLIN: u32 = libc::EPOLLIN as u32;
 pub const EPOLLOUT: u32 = libc::EPOLLOUT as u32;
@@ -15,6 +16,7 @@ pub struct Epoll {
     nfds: i32,
     timeout: i32,
     wakeup: Arc<Efd>,
+    timer: Timerfd,
     client: Box<dyn EpollClient>,
     events: Vec<libc::epoll_event>,
 }
@@ -33,6 +35,7 @@ impl Epoll {
             }
             epoll
         };
+        let timer = Timerfd::new()?;
         let event = libc::epoll_event { events: 0, u64: 0 };
         let events: Vec<libc::epoll_event> = vec![event; nfds as usize];
         let epoll = Epoll {
@@ -41,9 +44,11 @@ impl Epoll {
             timeout,
             client,
             wakeup: efd,
+            timer,
             events,
         };
         epoll.add(epoll.wakeup.fd, EPOLLIN);
+        epoll.add(epoll.timer.fd, EPOLLIN);
         Ok(epoll)
     }
 
@@ -79,6 +84,12 @@ impl Epoll {
         }
     }
 
+    /// Wake up the next wait() 'nsecs' nanoseconds from now, even if there are no events.
+    /// Zero nsecs cancels the wakeup
+    pub fn timer(&self, nsecs: u64) {
+        self.timer.arm(nsecs);
+    }
+
     pub fn wait(&mut self) -> i32 {
         let ret = unsafe {
             let ret = libc::epoll_wait(
@@ -100,6 +111,8 @@ impl Epoll {
             let fd = e.u64 as i32;
             if fd == self.wakeup.fd {
                 self.wakeup.read();
+            } else if fd == self.timer.fd {
+                self.timer.read();
             }
             self.client.event(fd, e.events);
         }
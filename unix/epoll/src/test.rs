@@ -3,6 +3,7 @@ use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
 
 const HELLO_WORLD: &str = "Hello World";
 
@@ -73,3 +74,39 @@ fn epoll_test() {
     }
     handler.unwrap().join().unwrap();
 }
+
+struct TimerTest {
+    nevents: Arc<AtomicUsize>,
+}
+
+impl EpollClient for TimerTest {
+    fn event(&mut self, _fd: i32, _event: u32) {
+        self.nevents.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn epoll_timer() {
+    let nevents = Arc::new(AtomicUsize::new(0));
+    let edata = Box::new(TimerTest {
+        nevents: nevents.clone(),
+    });
+    let efd = Arc::new(Efd::new(0).unwrap());
+    let mut epoll = match Epoll::new(efd, 4, -1, edata) {
+        Ok(e) => e,
+        Err(errno) => panic!("epoll create failed, errno {}", errno),
+    };
+    // No events other than the timer, so wait should return only after the timer expires
+    let start = Instant::now();
+    epoll.timer(10_000_000);
+    assert_eq!(epoll.wait(), 1);
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert_eq!(nevents.load(Ordering::Relaxed), 1);
+
+    // Cancelled timer should not wake us up
+    epoll.timer(10_000_000);
+    epoll.timer(0);
+    epoll.timeout = 50;
+    assert_eq!(epoll.wait(), 0);
+    assert_eq!(nevents.load(Ordering::Relaxed), 1);
+}
@@ -135,5 +135,14 @@ impl RawSock {
     }
 }
 
+// The socket is closed when the interface owning it is deleted
+impl Drop for RawSock {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test;
@@ -0,0 +1,57 @@
+// A one-shot timer that can be polled on, the descriptor becomes readable when the timer
+// expires. Re-arming the timer replaces the previous expiry time
+pub struct Timerfd {
+    pub fd: i32,
+}
+
+impl Timerfd {
+    pub fn new() -> Result<Timerfd, i32> {
+        unsafe {
+            let fd = libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK);
+            if fd < 0 {
+                return Err(*(libc::__errno_location()));
+            }
+            Ok(Timerfd { fd })
+        }
+    }
+
+    // Expire the timer 'nsecs' nanoseconds from now, zero nsecs disarms the timer
+    pub fn arm(&self, nsecs: u64) -> i32 {
+        let value = libc::itimerspec {
+            it_interval: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: libc::timespec {
+                tv_sec: (nsecs / 1_000_000_000) as libc::time_t,
+                tv_nsec: (nsecs % 1_000_000_000) as libc::c_long,
+            },
+        };
+        unsafe {
+            if libc::timerfd_settime(self.fd, 0, &value, std::ptr::null_mut()) < 0 {
+                return -*(libc::__errno_location());
+            }
+        }
+        0
+    }
+
+    // Returns the number of expiries since the last read, zero if the timer has not expired
+    pub fn read(&self) -> u64 {
+        unsafe {
+            let data: [u64; 1] = [0; 1];
+            libc::read(self.fd, data.as_ptr() as *mut libc::c_void, 8);
+            data[0]
+        }
+    }
+}
+
+impl Drop for Timerfd {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,24 @@
+use super::*;
+use std::thread;
+use std::time::Duration;
+
+// Arm the timer and check it expires, and that dropping the timer closes its descriptor
+#[test]
+fn timer_basic() {
+    let timer = Timerfd::new().unwrap();
+    assert_eq!(timer.read(), 0);
+    assert_eq!(timer.arm(1_000_000), 0);
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(timer.read(), 1);
+    assert_eq!(timer.read(), 0);
+
+    let fd = timer.fd;
+    unsafe {
+        assert!(libc::fcntl(fd, libc::F_GETFD) >= 0);
+    }
+    drop(timer);
+    unsafe {
+        assert_eq!(libc::fcntl(fd, libc::F_GETFD), -1);
+        assert_eq!(*(libc::__errno_location()), libc::EBADF);
+    }
+}
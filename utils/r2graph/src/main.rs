@@ -0,0 +1,107 @@
+use api::api_client;
+use apis_graph::{GraphApi, GraphSyncClient, TGraphSyncClient};
+#[macro_use]
+extern crate clap;
+use clap::App;
+use clap::ArgMatches;
+use std::fs::File;
+use std::io::prelude::*;
+
+fn show_thread(graph: &GraphApi) {
+    println!("Thread {}", graph.thread.unwrap_or(0));
+    println!(
+        "{:<6}{:<24}{:>12}{:>12}{:>8}{:>10}{:>10}  Next",
+        "Index", "Name", "Enq", "Drop", "Depth", "Cycles", "Pkt"
+    );
+    if let Some(ref nodes) = graph.nodes {
+        for n in nodes.iter() {
+            let mut next = Vec::new();
+            if let (Some(names), Some(nodes)) = (&n.next_names, &n.next_nodes) {
+                // Names that did not resolve to a node (other than the drop node) are left out
+                for (name, index) in names.iter().zip(nodes.iter()) {
+                    if *index != 0 || name == names::DROP {
+                        next.push(format!("{}[{}]", name, index));
+                    }
+                }
+            }
+            println!(
+                "{:<6}{:<24}{:>12}{:>12}{:>8}{:>10}{:>10}  {}",
+                n.index.unwrap_or(0),
+                n.name.as_ref().map_or("", |s| s),
+                n.enqed.unwrap_or(0),
+                n.drops.unwrap_or(0),
+                n.depth.unwrap_or(0),
+                n.perf_avg.unwrap_or(0),
+                n.perf_pkt.unwrap_or(0),
+                next.join(" ")
+            );
+        }
+    }
+}
+
+fn show() {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::GRAPH_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = GraphSyncClient::new(i_prot, o_prot);
+    match client.show() {
+        Ok(graphs) => {
+            for g in graphs.iter() {
+                show_thread(g);
+            }
+        }
+        Err(e) => println!("Show failed: {}", e),
+    }
+}
+
+fn dot(thread: i32, filename: Option<&str>) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::GRAPH_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = GraphSyncClient::new(i_prot, o_prot);
+    let dot = match client.dot(thread) {
+        Ok(dot) => dot,
+        Err(e) => {
+            println!("Dot failed: {}", e);
+            return;
+        }
+    };
+    if let Some(name) = filename {
+        let mut file = match File::create(name) {
+            Ok(file) => file,
+            Err(why) => {
+                println!("couldn't create {}: {}", name, why);
+                return;
+            }
+        };
+        if let Err(why) = file.write_all(dot.as_bytes()) {
+            println!("couldn't write {}: {}", name, why);
+        }
+    } else {
+        println!("{}", dot);
+    }
+}
+
+fn dot_subcmd(matches: &ArgMatches) {
+    let thread = value_t!(matches, "THREAD", i32).unwrap_or_else(|e| e.exit());
+    dot(thread, matches.value_of("FILENAME"));
+}
+
+fn main() {
+    let yaml = load_yaml!("./r2graph.yml");
+    let matches = App::from(yaml).get_matches();
+
+    if matches.subcommand_matches("show").is_some() {
+        show();
+    } else if let Some(matches) = matches.subcommand_matches("dot") {
+        dot_subcmd(matches);
+    }
+}
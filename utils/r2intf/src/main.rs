@@ -20,6 +20,21 @@ fn interface_add(ifname: String, ifindex: i32, mac: String) {
     }
 }
 
+fn interface_del(ifname: String) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = InterfaceSyncClient::new(i_prot, o_prot);
+
+    if let Err(e) = client.del_if(ifname) {
+        println!("Delete failed: {}", e);
+    }
+}
+
 fn add_ip(ifname: String, ip_and_mask: String) {
     let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
         Ok((i, o)) => (i, o),
@@ -35,6 +50,83 @@ fn add_ip(ifname: String, ip_and_mask: String) {
     }
 }
 
+fn set_mtu(ifname: String, mtu: i32) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = InterfaceSyncClient::new(i_prot, o_prot);
+
+    if let Err(e) = client.set_mtu(ifname, mtu) {
+        println!("MTU failed: {}", e);
+    }
+}
+
+fn set_vrf(ifname: String, vrf: String) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = InterfaceSyncClient::new(i_prot, o_prot);
+
+    if let Err(e) = client.set_vrf(ifname, vrf) {
+        println!("VRF failed: {}", e);
+    }
+}
+
+fn set_feature(ifname: String, arc: String, feature: String, enable: bool) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = InterfaceSyncClient::new(i_prot, o_prot);
+
+    if let Err(e) = client.set_feature(ifname, arc, feature, enable) {
+        println!("Feature failed: {}", e);
+    }
+}
+
+fn show_macs(ifname: String) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = InterfaceSyncClient::new(i_prot, o_prot);
+
+    match client.show_macs(ifname) {
+        Ok(s) => print!("{}", s),
+        Err(e) => println!("Show failed: {}", e),
+    }
+}
+
+fn show_classes(ifname: String) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::INTF_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = InterfaceSyncClient::new(i_prot, o_prot);
+
+    match client.show_classes(ifname) {
+        Ok(s) => print!("{}", s),
+        Err(e) => println!("Show failed: {}", e),
+    }
+}
+
 fn class_add_del(
     del: bool,
     ifname: &str,
@@ -144,13 +236,35 @@ fn add_subcmd(ifname: &str, matches: &ArgMatches) {
 
 fn ip_subcmd(ifname: &str, matches: &ArgMatches) {
     let ip_and_mask = value_t!(matches, "IPMASK", String).unwrap_or_else(|e| e.exit());
-    if fwd::ip_mask_decode(&ip_and_mask).is_none() {
+    if fwd::ip_mask_decode(&ip_and_mask).is_none() && fwd::ip6_mask_decode(&ip_and_mask).is_none() {
         println!("Bad IP/MASK {}", &ip_and_mask);
         return;
     }
     add_ip(ifname.to_string(), ip_and_mask);
 }
 
+fn mtu_subcmd(ifname: &str, matches: &ArgMatches) {
+    let mtu = value_t!(matches, "MTU", i32).unwrap_or_else(|e| e.exit());
+    set_mtu(ifname.to_string(), mtu);
+}
+
+fn vrf_subcmd(ifname: &str, matches: &ArgMatches) {
+    let vrf = matches.value_of("VRF").unwrap();
+    set_vrf(ifname.to_string(), vrf.to_string());
+}
+
+fn feature_subcmd(ifname: &str, matches: &ArgMatches) {
+    let arc = matches.value_of("ARC").unwrap();
+    let feature = matches.value_of("FEATURE").unwrap();
+    let enable = !matches.is_present("disable");
+    set_feature(
+        ifname.to_string(),
+        arc.to_string(),
+        feature.to_string(),
+        enable,
+    );
+}
+
 fn main() {
     let yaml = load_yaml!("./r2intf.yml");
     let matches = App::from(yaml).get_matches();
@@ -159,9 +273,21 @@ fn main() {
 
     if let Some(matches) = matches.subcommand_matches("add") {
         add_subcmd(ifname, matches);
+    } else if matches.subcommand_matches("del").is_some() {
+        interface_del(ifname.to_string());
     } else if let Some(matches) = matches.subcommand_matches("class") {
         class_subcmd(ifname, matches);
     } else if let Some(matches) = matches.subcommand_matches("ip") {
         ip_subcmd(ifname, matches);
+    } else if let Some(matches) = matches.subcommand_matches("mtu") {
+        mtu_subcmd(ifname, matches);
+    } else if let Some(matches) = matches.subcommand_matches("vrf") {
+        vrf_subcmd(ifname, matches);
+    } else if let Some(matches) = matches.subcommand_matches("feature") {
+        feature_subcmd(ifname, matches);
+    } else if matches.subcommand_matches("macs").is_some() {
+        show_macs(ifname.to_string());
+    } else if matches.subcommand_matches("classes").is_some() {
+        show_classes(ifname.to_string());
     }
 }
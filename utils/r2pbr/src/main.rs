@@ -0,0 +1,95 @@
+use api::api_client;
+use apis_pbr::{PbrRule, PbrSyncClient, TPbrSyncClient};
+#[macro_use]
+extern crate clap;
+use clap::App;
+use clap::ArgMatches;
+use fwd::{IP_PROTO_ICMP, IP_PROTO_TCP, IP_PROTO_UDP};
+
+fn pbr_client() -> Option<impl TPbrSyncClient> {
+    match api_client(common::API_SVR, common::PBR_APIS) {
+        Ok((i_prot, o_prot)) => Some(PbrSyncClient::new(i_prot, o_prot)),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            None
+        }
+    }
+}
+
+// The protocol number, for a number or one of the protocol names
+fn proto_decode(proto: &str) -> Option<i32> {
+    match proto {
+        "tcp" => Some(IP_PROTO_TCP as i32),
+        "udp" => Some(IP_PROTO_UDP as i32),
+        "icmp" => Some(IP_PROTO_ICMP as i32),
+        _ => proto.parse::<u8>().ok().map(i32::from),
+    }
+}
+
+fn rule_subcmd(matches: &ArgMatches) {
+    let seq = value_t!(matches, "SEQ", i32).unwrap_or_else(|e| e.exit());
+    let mut client = match pbr_client() {
+        Some(client) => client,
+        None => return,
+    };
+    if matches.is_present("delete") {
+        if let Err(e) = client.del_rule(seq) {
+            println!("Delete failed: {}", e);
+        }
+        return;
+    }
+    let proto = match matches.value_of("proto").map(proto_decode) {
+        None => -1,
+        Some(Some(proto)) => proto,
+        Some(None) => {
+            println!("Protocol invalid");
+            return;
+        }
+    };
+    let dscp = match matches.value_of("dscp").map(|d| d.parse::<u8>()) {
+        None => -1,
+        Some(Ok(dscp)) => i32::from(dscp),
+        Some(Err(_)) => {
+            println!("DSCP invalid");
+            return;
+        }
+    };
+    let arg = |name| matches.value_of(name).unwrap_or("").to_string();
+    let rule = PbrRule::new(
+        seq,
+        arg("src"),
+        arg("dst"),
+        proto,
+        arg("sport"),
+        arg("dport"),
+        dscp,
+        arg("in"),
+        arg("nhop"),
+        arg("vrf"),
+    );
+    if let Err(e) = client.add_rule(rule) {
+        println!("Add failed: {}", e);
+    }
+}
+
+fn show() {
+    let mut client = match pbr_client() {
+        Some(client) => client,
+        None => return,
+    };
+    match client.show() {
+        Ok(s) => println!("{}", s),
+        Err(e) => println!("Show failed: {}", e),
+    }
+}
+
+fn main() {
+    let yaml = load_yaml!("./r2pbr.yml");
+    let matches = App::from(yaml).get_matches();
+
+    if let Some(matches) = matches.subcommand_matches("rule") {
+        rule_subcmd(matches);
+    } else if matches.subcommand_matches("show").is_some() {
+        show();
+    }
+}
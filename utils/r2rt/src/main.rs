@@ -1,14 +1,32 @@
 use api::api_client;
-use apis_route::{RouteSyncClient, TRouteSyncClient};
+use apis_route::{RouteEntry, RouteSyncClient, TRouteSyncClient};
 #[macro_use]
 extern crate clap;
 use clap::App;
 use clap::ArgMatches;
-use fwd::ip_mask_decode;
-use std::net::Ipv4Addr;
+use fwd::{ip6_mask_decode, ip_mask_decode};
+use serde_json::Value;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
-fn add_del_ip(ip_and_mask: &str, nhop: &str, ifname: &str, del: bool) {
+// The number of routes sent in one API call when loading routes from a file
+const LOAD_BATCH: usize = 10000;
+
+// The keywords that can be given in place of the nexthop, for routes that do not forward
+// packets to a nexthop
+const KINDS: &[&str] = &["blackhole", "reject", "local"];
+
+// The (nhop, kind) of a route, for the nexthop (or keyword) on the command line
+fn nhop_kind(nhop: &str) -> (&str, &str) {
+    if KINDS.contains(&nhop) {
+        ("", nhop)
+    } else {
+        (nhop, "")
+    }
+}
+
+fn add_del_ip(ip_and_mask: &str, nhop: &str, ifname: &str, kind: &str, vrf: &str, del: bool) {
     let (i_prot, o_prot) = match api_client(common::API_SVR, common::ROUTE_APIS) {
         Ok((i, o)) => (i, o),
         Err(why) => {
@@ -23,12 +41,16 @@ fn add_del_ip(ip_and_mask: &str, nhop: &str, ifname: &str, del: bool) {
             ip_and_mask.to_string(),
             nhop.to_string(),
             ifname.to_string(),
+            kind.to_string(),
+            vrf.to_string(),
         )
     } else {
         client.add_route(
             ip_and_mask.to_string(),
             nhop.to_string(),
             ifname.to_string(),
+            kind.to_string(),
+            vrf.to_string(),
         )
     };
     if let Err(e) = ret {
@@ -36,13 +58,142 @@ fn add_del_ip(ip_and_mask: &str, nhop: &str, ifname: &str, del: bool) {
     }
 }
 
-fn show(prefix: &str, filename: &str) -> String {
+// Routes in a text file, one route per line, blank lines and lines starting with # ignored.
+// A line without an interface is a route through a recursive nexthop, or a blackhole or
+// reject route if the nexthop is one of those keywords
+fn routes_text(data: &str) -> Result<Vec<RouteEntry>, String> {
+    let mut routes = Vec::new();
+    for (n, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 2 && fields.len() != 3 {
+            return Err(format!("Line {}: expected IPMASK NHOP [IFNAME]", n + 1));
+        }
+        let ifname = fields.get(2).unwrap_or(&"");
+        let (nhop, kind) = nhop_kind(fields[1]);
+        routes.push(RouteEntry::new(
+            fields[0].to_string(),
+            nhop.to_string(),
+            ifname.to_string(),
+            kind.to_string(),
+        ));
+    }
+    Ok(routes)
+}
+
+// Routes in a JSON file, in the same format as the routes written out by 'show all'. The
+// receive entries R2 adds for interface addresses are marked "local" and are skipped
+fn routes_json(data: &str) -> Result<Vec<RouteEntry>, String> {
+    let json: Value = match serde_json::from_str(data) {
+        Ok(json) => json,
+        Err(why) => return Err(format!("Bad JSON: {}", why)),
+    };
+    let list = match json["routes"].as_array() {
+        Some(list) => list,
+        None => return Err("Expected a list of \"routes\"".to_string()),
+    };
+    let mut routes = Vec::new();
+    for (n, r) in list.iter().enumerate() {
+        if r["local"].as_bool() == Some(true) {
+            continue;
+        }
+        match (
+            r["prefix"].as_str(),
+            r["masklen"].as_u64(),
+            r["nhop"].as_str(),
+            r["ifname"].as_str(),
+        ) {
+            (Some(prefix), Some(masklen), Some(nhop), Some(ifname)) => {
+                let kind = r["kind"].as_str().unwrap_or("");
+                routes.push(RouteEntry::new(
+                    format!("{}/{}", prefix, masklen),
+                    nhop.to_string(),
+                    ifname.to_string(),
+                    kind.to_string(),
+                ));
+            }
+            _ => {
+                return Err(format!(
+                    "Route {}: expected prefix, masklen, nhop, ifname",
+                    n
+                ))
+            }
+        }
+    }
+    Ok(routes)
+}
+
+fn load(filename: &str, vrf: &str, del: bool) {
+    let data = match fs::read_to_string(filename) {
+        Ok(data) => data,
+        Err(why) => {
+            println!("Cannot read {}: {}", filename, why);
+            return;
+        }
+    };
+    let routes = if data.trim_start().starts_with('{') {
+        routes_json(&data)
+    } else {
+        routes_text(&data)
+    };
+    let routes = match routes {
+        Ok(routes) => routes,
+        Err(why) => {
+            println!("{}: {}", filename, why);
+            return;
+        }
+    };
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::ROUTE_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = RouteSyncClient::new(i_prot, o_prot);
+
+    let mut failed = 0;
+    for batch in routes.chunks(LOAD_BATCH) {
+        let ret = if del {
+            client.del_routes(batch.to_vec(), vrf.to_string())
+        } else {
+            client.add_routes(batch.to_vec(), vrf.to_string())
+        };
+        let errors = match ret {
+            Ok(errors) => errors,
+            Err(e) => {
+                println!("Load failed: {}", e);
+                return;
+            }
+        };
+        for (route, why) in batch.iter().zip(errors.iter()) {
+            if !why.is_empty() {
+                let nhop = route.nhop.as_ref().unwrap();
+                let kind = route.kind.as_ref().unwrap();
+                println!(
+                    "{} {} {}: {}",
+                    route.ip_and_mask.as_ref().unwrap(),
+                    if nhop.is_empty() { kind } else { nhop },
+                    route.ifname.as_ref().unwrap(),
+                    why
+                );
+                failed += 1;
+            }
+        }
+    }
+    println!("{} routes, {} failed", routes.len(), failed);
+}
+
+fn show(prefix: &str, filename: &str, vrf: &str) -> String {
     let (i_prot, o_prot) = match api_client(common::API_SVR, common::ROUTE_APIS) {
         Ok((i, o)) => (i, o),
         Err(why) => panic!("Client connection failed: {}", why),
     };
     let mut client = RouteSyncClient::new(i_prot, o_prot);
-    let ret = client.show(prefix.to_string(), filename.to_string());
+    let ret = client.show(prefix.to_string(), filename.to_string(), vrf.to_string());
     if let Err(e) = ret {
         format!("Show failed: {}", e)
     } else {
@@ -53,35 +204,43 @@ fn show(prefix: &str, filename: &str) -> String {
 fn add_del_subcmd(matches: &ArgMatches) {
     let ip_mask = matches.value_of("IPMASK").unwrap();
     let nhop = matches.value_of("NHOP").unwrap();
-    let ifname = matches.value_of("IFNAME").unwrap();
+    let ifname = matches.value_of("IFNAME").unwrap_or("");
+    let vrf = matches.value_of("vrf").unwrap_or("");
     let del = matches.is_present("delete");
+    let (nhop, kind) = nhop_kind(nhop);
 
-    if ip_mask_decode(ip_mask).is_none() {
+    // The nexthop has to be of the same address family as the prefix
+    let nhop_ok = if ip_mask_decode(ip_mask).is_some() {
+        !kind.is_empty() || Ipv4Addr::from_str(nhop).is_ok()
+    } else if ip6_mask_decode(ip_mask).is_some() {
+        !kind.is_empty() || Ipv6Addr::from_str(nhop).is_ok()
+    } else {
         println!("IP/Mask invalid");
         return;
-    }
-    if Ipv4Addr::from_str(nhop).is_err() {
+    };
+    if !nhop_ok {
         println!("Nhop invalid");
         return;
     }
-    add_del_ip(ip_mask, nhop, ifname, del);
+    add_del_ip(ip_mask, nhop, ifname, kind, vrf, del);
 }
 
 fn show_subcmd(matches: &ArgMatches) -> String {
     let prefix = matches.value_of("PREFIX").unwrap();
+    let vrf = matches.value_of("vrf").unwrap_or("");
     if prefix != "all" {
-        if let Err(_n) = Ipv4Addr::from_str(prefix) {
+        if let Err(_n) = IpAddr::from_str(prefix) {
             return "Prefix should be a valid ip address or keyword 'all'".to_string();
         }
     }
     if let Some(name) = matches.value_of("FILENAME") {
-        show(prefix, name);
+        show(prefix, name, vrf);
         String::new()
     } else {
         if prefix == "all" {
             println!("Writing routes to file /tmp/r2_routes.json");
         }
-        show(prefix, "/tmp/r2_routes.json")
+        show(prefix, "/tmp/r2_routes.json", vrf)
     }
 }
 
@@ -91,8 +250,15 @@ fn main() {
 
     if let Some(matches) = matches.subcommand_matches("route") {
         add_del_subcmd(matches);
+    } else if let Some(matches) = matches.subcommand_matches("load") {
+        let filename = matches.value_of("FILENAME").unwrap();
+        let vrf = matches.value_of("vrf").unwrap_or("");
+        load(filename, vrf, matches.is_present("delete"));
     } else if let Some(matches) = matches.subcommand_matches("show") {
         let show = show_subcmd(matches);
         println!("{}", show);
     }
 }
+
+#[cfg(test)]
+mod test;
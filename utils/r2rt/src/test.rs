@@ -0,0 +1,107 @@
+use super::*;
+
+// (ip_and_mask, nhop, ifname, kind) of the routes, to compare them easily
+fn fields(routes: &[RouteEntry]) -> Vec<(&str, &str, &str, &str)> {
+    routes
+        .iter()
+        .map(|r| {
+            (
+                r.ip_and_mask.as_deref().unwrap(),
+                r.nhop.as_deref().unwrap(),
+                r.ifname.as_deref().unwrap(),
+                r.kind.as_deref().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn text() {
+    let data = "
+# Comments and blank lines are skipped
+
+10.1.1.0/24 1.1.1.1 veth1
+  # Even indented ones
+  10.1.2.0/24   1.1.1.1
+10.1.3.0/24 blackhole
+10.1.4.0/24 reject
+10.1.5.1/32 local veth1
+2001:db8::/64 fe80::1 veth1
+";
+    let routes = routes_text(data).unwrap();
+    assert_eq!(
+        fields(&routes),
+        vec![
+            ("10.1.1.0/24", "1.1.1.1", "veth1", ""),
+            ("10.1.2.0/24", "1.1.1.1", "", ""),
+            ("10.1.3.0/24", "", "", "blackhole"),
+            ("10.1.4.0/24", "", "", "reject"),
+            ("10.1.5.1/32", "", "veth1", "local"),
+            ("2001:db8::/64", "fe80::1", "veth1", ""),
+        ]
+    );
+    assert!(routes_text("# Nothing here\n\n").unwrap().is_empty());
+}
+
+#[test]
+fn text_errors() {
+    // The lines are counted from one, including the comments and blank lines
+    let data = "# comment\n\n10.1.1.0/24\n";
+    assert_eq!(
+        routes_text(data).unwrap_err(),
+        "Line 3: expected IPMASK NHOP [IFNAME]"
+    );
+    let data = "10.1.1.0/24 1.1.1.1 veth1\n10.1.2.0/24 1.1.1.1 veth1 extra\n";
+    assert_eq!(
+        routes_text(data).unwrap_err(),
+        "Line 2: expected IPMASK NHOP [IFNAME]"
+    );
+    // The addresses are left to R2 to check, per route
+    let routes = routes_text("10.1.1.0/33 bad veth1\n").unwrap();
+    assert_eq!(fields(&routes), vec![("10.1.1.0/33", "bad", "veth1", "")]);
+}
+
+#[test]
+fn json() {
+    let data = r#"{ "routes": [
+        { "prefix": "10.1.1.0", "masklen": 24, "nhop": "1.1.1.1", "ifname": "veth1",
+          "ifindex": 1, "kind": "", "local": false },
+        { "prefix": "10.1.1.1", "masklen": 32, "nhop": "0.0.0.0", "ifname": "veth1",
+          "ifindex": 1, "kind": "local", "local": true },
+        { "prefix": "10.1.2.1", "masklen": 32, "nhop": "0.0.0.0", "ifname": "veth1",
+          "ifindex": 1, "kind": "local", "local": false },
+        { "prefix": "10.1.3.0", "masklen": 24, "nhop": "0.0.0.0", "ifname": "",
+          "ifindex": null, "kind": "blackhole", "local": false },
+        { "prefix": "10.1.4.0", "masklen": 24, "nhop": "1.1.1.1", "ifname": "" }
+    ]}"#;
+    let routes = routes_json(data).unwrap();
+    // The interface address is not a route, the local route added by the API is
+    assert_eq!(
+        fields(&routes),
+        vec![
+            ("10.1.1.0/24", "1.1.1.1", "veth1", ""),
+            ("10.1.2.1/32", "0.0.0.0", "veth1", "local"),
+            ("10.1.3.0/24", "0.0.0.0", "", "blackhole"),
+            ("10.1.4.0/24", "1.1.1.1", "", ""),
+        ]
+    );
+}
+
+#[test]
+fn json_errors() {
+    assert!(routes_json("{ \"routes\": [")
+        .unwrap_err()
+        .starts_with("Bad JSON"));
+    assert_eq!(
+        routes_json("{ \"route\": [] }").unwrap_err(),
+        "Expected a list of \"routes\""
+    );
+    let data = r#"{ "routes": [
+        { "prefix": "10.1.1.0", "masklen": 24, "nhop": "1.1.1.1", "ifname": "veth1" },
+        { "prefix": "10.1.2.0", "masklen": "24", "nhop": "1.1.1.1", "ifname": "veth1" }
+    ]}"#;
+    assert_eq!(
+        routes_json(data).unwrap_err(),
+        "Route 1: expected prefix, masklen, nhop, ifname"
+    );
+}
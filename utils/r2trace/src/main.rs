@@ -0,0 +1,71 @@
+use api::api_client;
+use apis_trace::{TTraceSyncClient, TraceSyncClient};
+#[macro_use]
+extern crate clap;
+use clap::App;
+use clap::ArgMatches;
+
+fn enable(ifname: &str, count: i32) {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::TRACE_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = TraceSyncClient::new(i_prot, o_prot);
+
+    if let Err(e) = client.enable(ifname.to_string(), count) {
+        println!("Enable failed: {}", e);
+    }
+}
+
+fn clear() {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::TRACE_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => {
+            println!("Client connection failed: {}", why);
+            return;
+        }
+    };
+    let mut client = TraceSyncClient::new(i_prot, o_prot);
+
+    if let Err(e) = client.clear() {
+        println!("Clear failed: {}", e);
+    }
+}
+
+fn show() -> String {
+    let (i_prot, o_prot) = match api_client(common::API_SVR, common::TRACE_APIS) {
+        Ok((i, o)) => (i, o),
+        Err(why) => panic!("Client connection failed: {}", why),
+    };
+    let mut client = TraceSyncClient::new(i_prot, o_prot);
+    match client.show() {
+        Ok(s) => s,
+        Err(e) => format!("Show failed: {}", e),
+    }
+}
+
+fn enable_subcmd(matches: &ArgMatches) {
+    let ifname = matches.value_of("IFNAME").unwrap();
+    let count = value_t!(matches, "COUNT", i32).unwrap_or_else(|e| e.exit());
+    if count <= 0 {
+        println!("Packet count should be greater than zero");
+        return;
+    }
+    enable(ifname, count);
+}
+
+fn main() {
+    let yaml = load_yaml!("./r2trace.yml");
+    let matches = App::from(yaml).get_matches();
+
+    if let Some(matches) = matches.subcommand_matches("enable") {
+        enable_subcmd(matches);
+    } else if matches.subcommand_matches("clear").is_some() {
+        clear();
+    } else if matches.subcommand_matches("show").is_some() {
+        println!("{}", show());
+    }
+}